@@ -163,6 +163,51 @@ pub struct UpdaterConfig {
   /// Optional pubkey.
   #[serde(default)]
   pub pubkey: Option<String>,
+  /// Additional trusted public keys, used to rotate the signing key.
+  ///
+  /// A signature is valid if it matches `pubkey` or any non-expired key on this list.
+  #[serde(default)]
+  pub pubkeys: Vec<UpdaterPubkey>,
+  /// Whether the update manifest must be signed or not.
+  ///
+  /// The manifest signature is downloaded from the endpoint URL with a `.sig` suffix.
+  #[serde(default)]
+  pub verify_manifest: bool,
+  /// Number of launches the installed update has to confirm it started successfully,
+  /// before the previous version is restored.
+  ///
+  /// The previous version is only kept as a backup when this is set.
+  #[serde(default)]
+  pub rollback_launches: Option<u32>,
+  /// Periodic update checks, in addition to the check on startup.
+  #[serde(default)]
+  pub schedule: Option<UpdaterSchedule>,
+  /// The proxy used to reach the update server, e.g. `http://proxy.example.com:8080`.
+  #[serde(default)]
+  pub proxy: Option<String>,
+}
+
+/// The schedule of the periodic update checks.
+#[derive(PartialEq, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdaterSchedule {
+  /// Number of seconds between two update checks.
+  pub interval: u64,
+  /// Maximum number of seconds randomly added to each interval,
+  /// so the clients don't all check at the same time.
+  #[serde(default)]
+  pub jitter: u64,
+}
+
+/// A public key trusted by the updater.
+#[derive(PartialEq, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdaterPubkey {
+  /// The public key generated with the Tauri CLI.
+  pub key: String,
+  /// Unix timestamp (in seconds) after which the key is no longer trusted.
+  #[serde(default)]
+  pub expires_at: Option<u64>,
 }
 
 fn default_updater_dialog() -> bool {
@@ -176,6 +221,11 @@ impl Default for UpdaterConfig {
       dialog: true,
       endpoints: None,
       pubkey: None,
+      pubkeys: Vec::new(),
+      verify_manifest: false,
+      rollback_launches: None,
+      schedule: None,
+      proxy: None,
     }
   }
 }
@@ -188,6 +238,43 @@ pub struct SecurityConfig {
   pub csp: Option<String>,
 }
 
+/// Filesystem API allowlist configuration.
+///
+/// The API features are enabled at build time, only the runtime options are read here.
+#[derive(PartialEq, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct FsAllowlistConfig {
+  /// The paths the fs APIs and the HTTP downloads and uploads can access, as glob patterns.
+  ///
+  /// Patterns can start with a base directory variable such as `$APP`, `$HOME` or `$DOWNLOAD`.
+  /// All paths are allowed if the scope isn't set.
+  pub scope: Option<Vec<String>>,
+}
+
+/// WebSocket API allowlist configuration.
+///
+/// The API is enabled at build time, only the runtime options are read here.
+#[derive(PartialEq, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct WebsocketAllowlistConfig {
+  /// The URLs the WebSocket API can connect to, as glob patterns, e.g. `wss://dashboard.example.com/*`.
+  /// The scheme, host, port and path are matched separately, e.g. `wss://*.example.com/*` only matches the subdomains.
+  /// All URLs are allowed if the scope isn't set.
+  pub scope: Option<Vec<String>>,
+}
+
+/// Allowlist configuration.
+#[derive(PartialEq, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct AllowlistConfig {
+  /// Filesystem API allowlist.
+  #[serde(default)]
+  pub fs: FsAllowlistConfig,
+  /// WebSocket API allowlist.
+  #[serde(default)]
+  pub websocket: WebsocketAllowlistConfig,
+}
+
 /// Configuration for application system tray icon.
 #[derive(PartialEq, Deserialize, Debug, Clone, Default)]
 #[serde(rename_all = "camelCase")]
@@ -378,6 +465,9 @@ pub struct TauriConfig {
   pub security: SecurityConfig,
   /// System tray configuration.
   pub system_tray: Option<SystemTrayConfig>,
+  /// The allowlist configuration.
+  #[serde(default)]
+  pub allowlist: AllowlistConfig,
 }
 
 impl Default for TauriConfig {
@@ -389,6 +479,7 @@ impl Default for TauriConfig {
       updater: UpdaterConfig::default(),
       security: SecurityConfig::default(),
       system_tray: None,
+      allowlist: AllowlistConfig::default(),
     }
   }
 }
@@ -833,8 +924,43 @@ mod build {
       let dialog = self.dialog;
       let pubkey = opt_str_lit(self.pubkey.as_ref());
       let endpoints = opt_vec_str_lit(self.endpoints.as_ref());
+      let pubkeys = vec_lit(&self.pubkeys, identity);
+      let verify_manifest = self.verify_manifest;
+      let rollback_launches = opt_lit(self.rollback_launches.as_ref());
+      let schedule = opt_lit(self.schedule.as_ref());
+      let proxy = opt_str_lit(self.proxy.as_ref());
+
+      literal_struct!(
+        tokens,
+        UpdaterConfig,
+        active,
+        dialog,
+        pubkey,
+        endpoints,
+        pubkeys,
+        verify_manifest,
+        rollback_launches,
+        schedule,
+        proxy
+      );
+    }
+  }
+
+  impl ToTokens for UpdaterSchedule {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+      let interval = self.interval;
+      let jitter = self.jitter;
+
+      literal_struct!(tokens, UpdaterSchedule, interval, jitter);
+    }
+  }
+
+  impl ToTokens for UpdaterPubkey {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+      let key = str_lit(&self.key);
+      let expires_at = opt_lit(self.expires_at.as_ref());
 
-      literal_struct!(tokens, UpdaterConfig, active, dialog, pubkey, endpoints);
+      literal_struct!(tokens, UpdaterPubkey, key, expires_at);
     }
   }
 
@@ -853,6 +979,31 @@ mod build {
     }
   }
 
+  impl ToTokens for FsAllowlistConfig {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+      let scope = opt_vec_str_lit(self.scope.as_ref());
+
+      literal_struct!(tokens, FsAllowlistConfig, scope);
+    }
+  }
+
+  impl ToTokens for WebsocketAllowlistConfig {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+      let scope = opt_vec_str_lit(self.scope.as_ref());
+
+      literal_struct!(tokens, WebsocketAllowlistConfig, scope);
+    }
+  }
+
+  impl ToTokens for AllowlistConfig {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+      let fs = &self.fs;
+      let websocket = &self.websocket;
+
+      literal_struct!(tokens, AllowlistConfig, fs, websocket);
+    }
+  }
+
   impl ToTokens for TauriConfig {
     fn to_tokens(&self, tokens: &mut TokenStream) {
       let windows = vec_lit(&self.windows, identity);
@@ -861,6 +1012,7 @@ mod build {
       let updater = &self.updater;
       let security = &self.security;
       let system_tray = opt_lit(self.system_tray.as_ref());
+      let allowlist = &self.allowlist;
 
       literal_struct!(
         tokens,
@@ -870,7 +1022,8 @@ mod build {
         bundle,
         updater,
         security,
-        system_tray
+        system_tray,
+        allowlist
       );
     }
   }
@@ -966,9 +1119,15 @@ mod test {
         dialog: true,
         pubkey: None,
         endpoints: None,
+        pubkeys: Vec::new(),
+        verify_manifest: false,
+        rollback_launches: None,
+        schedule: None,
+        proxy: None,
       },
       security: SecurityConfig { csp: None },
       system_tray: None,
+      allowlist: AllowlistConfig::default(),
     };
 
     // create a build config
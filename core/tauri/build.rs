@@ -24,6 +24,11 @@ fn main() {
     fs_remove_file: { any(fs_all, feature = "fs-remove-file") },
     fs_rename_file: { any(fs_all, feature = "fs-rename-file") },
     fs_path: { any(fs_all, feature = "fs-path") },
+    fs_watch: { any(fs_all, feature = "fs-watch") },
+    fs_metadata: { any(fs_all, feature = "fs-metadata") },
+    fs_set_permissions: { any(fs_all, feature = "fs-set-permissions") },
+    fs_archive: { any(fs_all, feature = "fs-archive") },
+    fs_trash: { any(fs_all, feature = "fs-trash") },
 
     // window
     window_all: { any(api_all, feature = "window-all") },
@@ -38,6 +43,8 @@ fn main() {
     dialog_all: { any(api_all, feature = "dialog-all") },
     dialog_open: { any(dialog_all, feature = "dialog-open") },
     dialog_save: { any(dialog_all, feature = "dialog-save") },
+    // the non-blocking dialogs, used by the file dialogs, the updater prompt and the notification permission prompt
+    dialog_async: { any(dialog_open, dialog_save, feature = "updater", notification_all) },
 
     // http
     http_all: { any(api_all, feature = "http-all") },
@@ -51,5 +58,8 @@ fn main() {
 
     // global shortcut
     global_shortcut_all: { any(api_all, feature = "global_shortcut-all") },
+
+    // websocket
+    websocket_all: { any(api_all, feature = "websocket-all") },
   }
 }
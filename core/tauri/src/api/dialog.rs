@@ -2,6 +2,9 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-License-Identifier: MIT
 
+//! The non-blocking `*_with` and `*_async` variants of the dialogs are available
+//! with the `dialog-open`, `dialog-save`, `updater` or `notification-all` features.
+
 #[cfg(any(dialog_open, dialog_save))]
 use std::path::{Path, PathBuf};
 
@@ -36,6 +39,27 @@ impl FileDialogBuilder {
     self
   }
 
+  /// Set the title of the dialog.
+  ///
+  /// The file dialogs of `rfd` 0.4 don't support titles, so the title is currently ignored.
+  pub fn set_title(self, _title: impl AsRef<str>) -> Self {
+    self
+  }
+
+  /// Set whether the user can create directories from the dialog.
+  ///
+  /// The file dialogs of `rfd` 0.4 don't support this option, so it's currently ignored.
+  pub fn set_can_create_directories(self, _can: bool) -> Self {
+    self
+  }
+
+  /// Set whether the dialog shows hidden files.
+  ///
+  /// The file dialogs of `rfd` 0.4 don't support this option, so it's currently ignored.
+  pub fn set_show_hidden_files(self, _show: bool) -> Self {
+    self
+  }
+
   #[cfg(windows)]
   /// Sets the parent window of the dialog.
   pub fn set_parent<W: raw_window_handle::HasRawWindowHandle>(mut self, parent: &W) -> Self {
@@ -44,24 +68,255 @@ impl FileDialogBuilder {
   }
 
   /// Pick one file.
+  ///
+  /// Blocks the current thread until the dialog is closed. On Linux, it must be called on the main thread.
   pub fn pick_file(self) -> Option<PathBuf> {
     self.0.pick_file()
   }
 
+  /// Shows the open file dialog without blocking the current thread, calling `f` with the picked file.
+  pub fn pick_file_with<F: FnOnce(Option<PathBuf>) + Send + 'static>(self, f: F) {
+    run_dialog(move || self.0.pick_file(), f)
+  }
+
+  /// Shows the open file dialog without blocking the current thread, resolving to the picked file.
+  pub async fn pick_file_async(self) -> crate::api::Result<Option<PathBuf>> {
+    run_dialog_async(move || self.0.pick_file()).await
+  }
+
   /// Pick multiple files.
+  ///
+  /// Blocks the current thread until the dialog is closed. On Linux, it must be called on the main thread.
   pub fn pick_files(self) -> Option<Vec<PathBuf>> {
     self.0.pick_files()
   }
 
+  /// Shows the open file dialog allowing multiple selection without blocking the current thread,
+  /// calling `f` with the picked files.
+  pub fn pick_files_with<F: FnOnce(Option<Vec<PathBuf>>) + Send + 'static>(self, f: F) {
+    run_dialog(move || self.0.pick_files(), f)
+  }
+
+  /// Shows the open file dialog allowing multiple selection without blocking the current thread,
+  /// resolving to the picked files.
+  pub async fn pick_files_async(self) -> crate::api::Result<Option<Vec<PathBuf>>> {
+    run_dialog_async(move || self.0.pick_files()).await
+  }
+
   /// Pick one folder.
+  ///
+  /// Blocks the current thread until the dialog is closed. On Linux, it must be called on the main thread.
   pub fn pick_folder(self) -> Option<PathBuf> {
     self.0.pick_folder()
   }
 
+  /// Shows the open folder dialog without blocking the current thread, calling `f` with the picked folder.
+  pub fn pick_folder_with<F: FnOnce(Option<PathBuf>) + Send + 'static>(self, f: F) {
+    run_dialog(move || self.0.pick_folder(), f)
+  }
+
+  /// Shows the open folder dialog without blocking the current thread, resolving to the picked folder.
+  pub async fn pick_folder_async(self) -> crate::api::Result<Option<PathBuf>> {
+    run_dialog_async(move || self.0.pick_folder()).await
+  }
+
   /// Opens save file dialog.
+  ///
+  /// Blocks the current thread until the dialog is closed. On Linux, it must be called on the main thread.
   pub fn save_file(self) -> Option<PathBuf> {
     self.0.save_file()
   }
+
+  /// Shows the save file dialog without blocking the current thread, calling `f` with the picked path.
+  pub fn save_file_with<F: FnOnce(Option<PathBuf>) + Send + 'static>(self, f: F) {
+    run_dialog(move || self.0.save_file(), f)
+  }
+
+  /// Shows the save file dialog without blocking the current thread, resolving to the picked path.
+  pub async fn save_file_async(self) -> crate::api::Result<Option<PathBuf>> {
+    run_dialog_async(move || self.0.save_file()).await
+  }
+}
+
+// Shows the dialog and calls `f` with its result without blocking the current thread.
+// GTK isn't thread safe, so on Linux the dialog runs on the main thread, where the event loop runs GTK.
+#[cfg(all(dialog_async, unix, not(target_os = "macos")))]
+fn run_dialog<T, D, F>(dialog: D, f: F)
+where
+  T: Send + 'static,
+  D: FnOnce() -> T + Send + 'static,
+  F: FnOnce(T) + Send + 'static,
+{
+  glib::MainContext::default().invoke(move || f(dialog()));
+}
+
+// Shows the dialog and calls `f` with its result without blocking the current thread.
+// On macOS and Windows the dialog runs on its own thread, so it doesn't block the event loop either.
+#[cfg(all(dialog_async, any(windows, target_os = "macos")))]
+fn run_dialog<T, D, F>(dialog: D, f: F)
+where
+  T: Send + 'static,
+  D: FnOnce() -> T + Send + 'static,
+  F: FnOnce(T) + Send + 'static,
+{
+  std::thread::spawn(move || f(dialog()));
+}
+
+#[cfg(dialog_async)]
+async fn run_dialog_async<T, D>(dialog: D) -> crate::api::Result<T>
+where
+  T: Send + 'static,
+  D: FnOnce() -> T + Send + 'static,
+{
+  let (tx, rx) = tokio::sync::oneshot::channel();
+  run_dialog(dialog, move |result| {
+    let _ = tx.send(result);
+  });
+  // the sender is dropped without a result if the dialog panicked
+  rx.await
+    .map_err(|_| crate::api::Error::Dialog("the dialog failed to return a result".into()))
+}
+
+/// The kind of a message dialog, defining its icon.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageDialogKind {
+  /// An information dialog.
+  Info,
+  /// A warning dialog.
+  Warning,
+  /// An error dialog.
+  Error,
+}
+
+impl Default for MessageDialogKind {
+  fn default() -> Self {
+    Self::Info
+  }
+}
+
+/// The buttons of a message dialog.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageDialogButtons {
+  /// An "Ok" button.
+  Ok,
+  /// "Ok" and "Cancel" buttons.
+  OkCancel,
+  /// "Yes" and "No" buttons.
+  YesNo,
+}
+
+impl Default for MessageDialogButtons {
+  fn default() -> Self {
+    Self::Ok
+  }
+}
+
+/// The button pressed to close a message dialog.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageDialogResult {
+  /// The "Ok" button.
+  Ok,
+  /// The "Cancel" button, or the Ok/Cancel dialog was dismissed.
+  Cancel,
+  /// The "Yes" button.
+  Yes,
+  /// The "No" button, or the Yes/No dialog was dismissed.
+  No,
+}
+
+/// The message dialog builder.
+///
+/// # Examples
+///
+/// ```no_run
+/// use tauri::api::dialog::{MessageDialogBuilder, MessageDialogButtons, MessageDialogKind, MessageDialogResult};
+///
+/// MessageDialogBuilder::new("Unsaved changes", "Save the changes before closing?")
+///   .kind(MessageDialogKind::Warning)
+///   .buttons(MessageDialogButtons::YesNo)
+///   .show_with(|result| {
+///     if result == MessageDialogResult::Yes {
+///       // save the changes
+///     }
+///   });
+/// ```
+pub struct MessageDialogBuilder {
+  dialog: rfd::MessageDialog,
+  buttons: MessageDialogButtons,
+}
+
+impl MessageDialogBuilder {
+  /// Creates an information dialog with an "Ok" button.
+  pub fn new(title: impl AsRef<str>, message: impl AsRef<str>) -> Self {
+    Self {
+      dialog: rfd::MessageDialog::new()
+        .set_title(title.as_ref())
+        .set_description(message.as_ref())
+        .set_level(rfd::MessageLevel::Info)
+        .set_buttons(rfd::MessageButtons::Ok),
+      buttons: MessageDialogButtons::Ok,
+    }
+  }
+
+  /// Sets the kind of the dialog.
+  pub fn kind(mut self, kind: MessageDialogKind) -> Self {
+    let level = match kind {
+      MessageDialogKind::Info => rfd::MessageLevel::Info,
+      MessageDialogKind::Warning => rfd::MessageLevel::Warning,
+      MessageDialogKind::Error => rfd::MessageLevel::Error,
+    };
+    self.dialog = self.dialog.set_level(level);
+    self
+  }
+
+  /// Sets the buttons of the dialog.
+  pub fn buttons(mut self, buttons: MessageDialogButtons) -> Self {
+    let rfd_buttons = match buttons {
+      MessageDialogButtons::Ok => rfd::MessageButtons::Ok,
+      MessageDialogButtons::OkCancel => rfd::MessageButtons::OkCancel,
+      MessageDialogButtons::YesNo => rfd::MessageButtons::YesNo,
+    };
+    self.dialog = self.dialog.set_buttons(rfd_buttons);
+    self.buttons = buttons;
+    self
+  }
+
+  /// Sets the parent window of the dialog, making it modal.
+  ///
+  /// Requires the `dialog-open` or `dialog-save` feature, which enable the `raw-window-handle` dependency.
+  #[cfg(all(windows, any(dialog_open, dialog_save)))]
+  pub fn parent<W: raw_window_handle::HasRawWindowHandle>(mut self, parent: &W) -> Self {
+    self.dialog = self.dialog.set_parent(parent);
+    self
+  }
+
+  /// Shows the dialog, returning the pressed button.
+  ///
+  /// Blocks the current thread until the dialog is closed. On Linux, it must be called on the main thread.
+  /// Dismissing the dialog reports the "Cancel" or "No" button.
+  pub fn show(self) -> MessageDialogResult {
+    // `rfd` only reports whether the dialog was confirmed
+    let confirmed = self.dialog.show();
+    match (self.buttons, confirmed) {
+      (MessageDialogButtons::Ok, _) => MessageDialogResult::Ok,
+      (MessageDialogButtons::OkCancel, true) => MessageDialogResult::Ok,
+      (MessageDialogButtons::OkCancel, false) => MessageDialogResult::Cancel,
+      (MessageDialogButtons::YesNo, true) => MessageDialogResult::Yes,
+      (MessageDialogButtons::YesNo, false) => MessageDialogResult::No,
+    }
+  }
+
+  /// Shows the dialog without blocking the current thread, calling `f` with the pressed button. See [`Self::show`].
+  #[cfg(dialog_async)]
+  pub fn show_with<F: FnOnce(MessageDialogResult) + Send + 'static>(self, f: F) {
+    run_dialog(move || self.show(), f)
+  }
+
+  /// Shows the dialog without blocking the current thread, resolving to the pressed button. See [`Self::show`].
+  #[cfg(dialog_async)]
+  pub async fn show_async(self) -> crate::api::Result<MessageDialogResult> {
+    run_dialog_async(move || self.show()).await
+  }
 }
 
 /// Response for the ask dialog
@@ -72,26 +327,54 @@ pub enum AskResponse {
   No,
 }
 
-/// Displays a dialog with a message and an optional title with a "yes" and a "no" button
+impl From<MessageDialogResult> for AskResponse {
+  fn from(result: MessageDialogResult) -> Self {
+    match result {
+      MessageDialogResult::Yes => Self::Yes,
+      _ => Self::No,
+    }
+  }
+}
+
+/// Displays a dialog with a message and an optional title with a "yes" and a "no" button,
+/// blocking the current thread until the user answers.
 pub fn ask(title: impl AsRef<str>, message: impl AsRef<str>) -> AskResponse {
-  match rfd::MessageDialog::new()
-    .set_title(title.as_ref())
-    .set_description(message.as_ref())
-    .set_buttons(rfd::MessageButtons::YesNo)
-    .set_level(rfd::MessageLevel::Info)
+  MessageDialogBuilder::new(title, message)
+    .buttons(MessageDialogButtons::YesNo)
     .show()
-  {
-    true => AskResponse::Yes,
-    false => AskResponse::No,
-  }
+    .into()
 }
 
-/// Displays a message dialog
+/// Displays a dialog with a message and an optional title with a "yes" and a "no" button,
+/// resolving to the user answer without blocking the current thread.
+#[cfg(dialog_async)]
+pub async fn ask_async(
+  title: impl AsRef<str>,
+  message: impl AsRef<str>,
+) -> crate::api::Result<AskResponse> {
+  MessageDialogBuilder::new(title, message)
+    .buttons(MessageDialogButtons::YesNo)
+    .show_async()
+    .await
+    .map(Into::into)
+}
+
+/// Displays a message dialog, blocking the current thread until it's closed.
 pub fn message(title: impl AsRef<str>, message: impl AsRef<str>) {
-  rfd::MessageDialog::new()
-    .set_title(title.as_ref())
-    .set_description(message.as_ref())
-    .set_buttons(rfd::MessageButtons::Ok)
-    .set_level(rfd::MessageLevel::Info)
-    .show();
+  MessageDialogBuilder::new(title, message).show();
+}
+
+#[cfg(all(test, dialog_async))]
+mod test {
+  use super::*;
+
+  #[test]
+  fn run_dialog_result() {
+    let value = tokio_test::block_on(run_dialog_async(|| 42)).unwrap();
+    assert_eq!(value, 42);
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    run_dialog(|| "picked", move |result| tx.send(result).unwrap());
+    assert_eq!(rx.recv().unwrap(), "picked");
+  }
 }
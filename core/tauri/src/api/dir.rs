@@ -2,7 +2,9 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-License-Identifier: MIT
 
-use serde::Serialize;
+use crate::api::file::{self, Metadata};
+use ignore::{overrides::OverrideBuilder, Walk, WalkBuilder};
+use serde::{Deserialize, Serialize};
 use std::{
   fs::{self, metadata},
   path::{Path, PathBuf},
@@ -23,6 +25,200 @@ pub struct DiskEntry {
   /// The children of this entry if it's a directory.
   #[serde(skip_serializing_if = "Option::is_none")]
   pub children: Option<Vec<DiskEntry>>,
+  /// The metadata of this entry, if requested with [`ReadDirOptions::metadata`].
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub metadata: Option<Metadata>,
+}
+
+/// How [`read_dir_with_options`] handles the symlinks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub enum SymlinkPolicy {
+  /// Follows the symlinks, the linked directories are read too.
+  /// Broken symlinks and symlink loops are listed as files.
+  Follow,
+  /// Lists the symlinks as files without following them.
+  List,
+  /// Skips the symlinks.
+  Skip,
+}
+
+impl Default for SymlinkPolicy {
+  fn default() -> Self {
+    Self::Follow
+  }
+}
+
+/// The options of the [`read_dir_with_options`] and [`walk_dir`] functions.
+#[derive(Debug, Clone)]
+pub struct ReadDirOptions {
+  recursive: bool,
+  max_depth: Option<usize>,
+  metadata: bool,
+  include: Vec<String>,
+  exclude: Vec<String>,
+  gitignore: bool,
+  hidden: bool,
+  symlinks: SymlinkPolicy,
+}
+
+impl Default for ReadDirOptions {
+  fn default() -> Self {
+    Self {
+      recursive: false,
+      max_depth: None,
+      metadata: false,
+      include: Vec::new(),
+      exclude: Vec::new(),
+      gitignore: false,
+      hidden: true,
+      symlinks: SymlinkPolicy::Follow,
+    }
+  }
+}
+
+impl ReadDirOptions {
+  /// Creates the default options: the directory is read non-recursively, without metadata,
+  /// including the hidden files and following the symlinks.
+  pub fn new() -> Self {
+    Default::default()
+  }
+
+  /// Whether the children of the subdirectories should be read too.
+  pub fn recursive(mut self, recursive: bool) -> Self {
+    self.recursive = recursive;
+    self
+  }
+
+  /// The maximum depth of a recursive read, the entries of the directory itself have depth 1.
+  pub fn max_depth(mut self, max_depth: usize) -> Self {
+    self.max_depth.replace(max_depth);
+    self
+  }
+
+  /// Whether the metadata of each entry should be read.
+  pub fn metadata(mut self, metadata: bool) -> Self {
+    self.metadata = metadata;
+    self
+  }
+
+  /// Only lists the files matching the glob, relative to the directory, e.g. `*.rs` or `src/**/*.md`.
+  /// The directories are always listed.
+  pub fn include(mut self, glob: impl Into<String>) -> Self {
+    self.include.push(glob.into());
+    self
+  }
+
+  /// Skips the files and directories matching the glob, relative to the directory.
+  pub fn exclude(mut self, glob: impl Into<String>) -> Self {
+    self.exclude.push(glob.into());
+    self
+  }
+
+  /// Whether the files matched by the `.gitignore`, `.ignore` and `.git/info/exclude` files should be skipped.
+  pub fn gitignore(mut self, gitignore: bool) -> Self {
+    self.gitignore = gitignore;
+    self
+  }
+
+  /// Whether the hidden files and directories should be listed.
+  pub fn hidden(mut self, hidden: bool) -> Self {
+    self.hidden = hidden;
+    self
+  }
+
+  /// How the symlinks are handled.
+  pub fn symlinks(mut self, symlinks: SymlinkPolicy) -> Self {
+    self.symlinks = symlinks;
+    self
+  }
+}
+
+/// An iterator over the entries of a directory tree, created with [`walk_dir`].
+///
+/// The entries are flattened: a directory is followed by its entries, and its `children` are always empty.
+pub struct WalkDir {
+  root: PathBuf,
+  walk: Walk,
+  metadata: bool,
+}
+
+impl WalkDir {
+  // the next entry and its depth
+  fn next_entry(&mut self) -> Option<crate::api::Result<(usize, DiskEntry)>> {
+    loop {
+      let (depth, path, is_dir) = match self.walk.next()? {
+        Ok(entry) => {
+          if entry.depth() == 0 {
+            continue;
+          }
+          let is_dir = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
+          (entry.depth(), entry.into_path(), is_dir)
+        }
+        Err(error) => {
+          // broken symlinks and symlink loops are listed without following them
+          let symlink = error_path(&error)
+            .filter(|path| {
+              fs::symlink_metadata(path)
+                .map(|m| m.file_type().is_symlink())
+                .unwrap_or(false)
+            })
+            .map(Path::to_path_buf);
+          match symlink {
+            Some(path) => {
+              let depth = path
+                .strip_prefix(&self.root)
+                .map(|relative| relative.components().count())
+                .unwrap_or(1);
+              (depth, path, false)
+            }
+            None => return Some(Err(error.into())),
+          }
+        }
+      };
+
+      let metadata = if self.metadata {
+        match file::metadata(&path) {
+          Ok(metadata) => Some(metadata),
+          Err(e) => return Some(Err(e)),
+        }
+      } else {
+        None
+      };
+      return Some(Ok((
+        depth,
+        DiskEntry {
+          name: path
+            .file_name()
+            .map(|name| name.to_string_lossy())
+            .map(|name| name.to_string()),
+          children: if is_dir { Some(Vec::new()) } else { None },
+          metadata,
+          path,
+        },
+      )));
+    }
+  }
+}
+
+impl Iterator for WalkDir {
+  type Item = crate::api::Result<DiskEntry>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    self.next_entry().map(|entry| entry.map(|(_, entry)| entry))
+  }
+}
+
+fn error_path(error: &ignore::Error) -> Option<&Path> {
+  match error {
+    ignore::Error::WithPath { path, .. } => Some(path),
+    ignore::Error::Loop { child, .. } => Some(child),
+    ignore::Error::WithDepth { err, .. } | ignore::Error::WithLineNumber { err, .. } => {
+      error_path(err)
+    }
+    _ => None,
+  }
 }
 
 /// Checks if the given path is a directory.
@@ -32,31 +228,100 @@ pub fn is_dir<P: AsRef<Path>>(path: P) -> crate::api::Result<bool> {
 
 /// Reads a directory. Can perform recursive operations.
 pub fn read_dir<P: AsRef<Path>>(path: P, recursive: bool) -> crate::api::Result<Vec<DiskEntry>> {
-  let mut files_and_dirs: Vec<DiskEntry> = vec![];
-  for entry in fs::read_dir(path)? {
-    let path = entry?.path();
-    let path_as_string = path.display().to_string();
-
-    if let Ok(flag) = is_dir(&path_as_string) {
-      files_and_dirs.push(DiskEntry {
-        path: path.clone(),
-        children: if flag {
-          Some(if recursive {
-            read_dir(&path_as_string, true)?
-          } else {
-            vec![]
-          })
-        } else {
-          None
-        },
-        name: path
-          .file_name()
-          .map(|name| name.to_string_lossy())
-          .map(|name| name.to_string()),
-      });
+  read_dir_with_options(path, &ReadDirOptions::new().recursive(recursive))
+}
+
+/// Reads a directory with the given options.
+///
+/// The entries are sorted by name.
+pub fn read_dir_with_options<P: AsRef<Path>>(
+  path: P,
+  options: &ReadDirOptions,
+) -> crate::api::Result<Vec<DiskEntry>> {
+  let mut walker = walk_dir(path, options)?;
+  let mut entries = Vec::new();
+  // the directories being filled, the walk visits the entries of a directory right after it
+  let mut parents: Vec<DiskEntry> = Vec::new();
+  while let Some(entry) = walker.next_entry() {
+    let (depth, entry) = entry?;
+    while parents.len() >= depth {
+      let dir = parents.pop().unwrap();
+      push_entry(&mut parents, &mut entries, dir);
+    }
+    if entry.children.is_some() {
+      parents.push(entry);
+    } else {
+      push_entry(&mut parents, &mut entries, entry);
+    }
+  }
+  while let Some(dir) = parents.pop() {
+    push_entry(&mut parents, &mut entries, dir);
+  }
+  Ok(entries)
+}
+
+fn push_entry(parents: &mut [DiskEntry], entries: &mut Vec<DiskEntry>, entry: DiskEntry) {
+  match parents.last_mut() {
+    Some(parent) => parent.children.get_or_insert_with(Vec::new).push(entry),
+    None => entries.push(entry),
+  }
+}
+
+/// Lazily walks a directory with the given options, without building the tree in memory.
+///
+/// # Examples
+///
+/// ```no_run
+/// use tauri::api::dir::{walk_dir, ReadDirOptions};
+///
+/// let options = ReadDirOptions::new()
+///   .recursive(true)
+///   .include("*.rs")
+///   .gitignore(true);
+/// for entry in walk_dir("/home/user/project", &options).unwrap() {
+///   println!("{}", entry.unwrap().path.display());
+/// }
+/// ```
+pub fn walk_dir<P: AsRef<Path>>(path: P, options: &ReadDirOptions) -> crate::api::Result<WalkDir> {
+  let root = path.as_ref().to_path_buf();
+  // fail early if the directory can't be read
+  fs::read_dir(&root)?;
+
+  let mut builder = WalkBuilder::new(&root);
+  builder
+    .standard_filters(false)
+    .hidden(!options.hidden)
+    .ignore(options.gitignore)
+    .git_ignore(options.gitignore)
+    .git_exclude(options.gitignore)
+    .parents(options.gitignore)
+    .require_git(false)
+    .follow_links(options.symlinks == SymlinkPolicy::Follow)
+    .max_depth(if options.recursive {
+      options.max_depth
+    } else {
+      Some(1)
+    })
+    .sort_by_file_name(|a, b| a.cmp(b));
+  if options.symlinks == SymlinkPolicy::Skip {
+    builder.filter_entry(|entry| !entry.path_is_symlink());
+  }
+  if !options.include.is_empty() || !options.exclude.is_empty() {
+    let mut overrides = OverrideBuilder::new(&root);
+    for glob in &options.include {
+      overrides.add(glob)?;
     }
+    for glob in &options.exclude {
+      overrides.add(&format!("!{}", glob))?;
+    }
+    builder.overrides(overrides.build()?);
   }
-  Result::Ok(files_and_dirs)
+
+  Ok(WalkDir {
+    root,
+    walk: builder.build(),
+    metadata: options.metadata,
+  })
 }
 
 /// Runs a closure with a temp dir argument.
@@ -187,6 +452,131 @@ mod test {
     }
   }
 
+  #[test]
+  // check the read_dir_with_options function with the metadata option
+  fn check_read_dir_metadata() {
+    let entries =
+      read_dir_with_options("test/api/", &ReadDirOptions::new().metadata(true)).unwrap();
+    assert_eq!(entries.len(), 2);
+    for entry in entries {
+      let metadata = entry.metadata.unwrap();
+      assert!(metadata.is_file);
+      assert_eq!(metadata.size, fs::metadata(&entry.path).unwrap().len());
+    }
+
+    let entries = read_dir("test/api/", false).unwrap();
+    assert!(entries.iter().all(|entry| entry.metadata.is_none()));
+  }
+
+  fn names(entries: &[DiskEntry]) -> Vec<String> {
+    entries
+      .iter()
+      .map(|entry| entry.name.clone().unwrap())
+      .collect()
+  }
+
+  #[test]
+  // check the read_dir_with_options filters
+  fn check_read_dir_filters() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    fs::create_dir_all(root.join("sub/deep")).unwrap();
+    fs::create_dir(root.join("target")).unwrap();
+    for file in &[
+      "a.txt",
+      "b.rs",
+      ".hidden",
+      "sub/c.rs",
+      "sub/deep/d.rs",
+      "target/e.rs",
+    ] {
+      fs::write(root.join(file), "").unwrap();
+    }
+    fs::write(root.join(".gitignore"), "target/\n").unwrap();
+
+    let all = read_dir(root, true).unwrap();
+    assert_eq!(
+      names(&all),
+      vec![".gitignore", ".hidden", "a.txt", "b.rs", "sub", "target"]
+    );
+    assert_eq!(
+      names(all[4].children.as_ref().unwrap()),
+      vec!["c.rs", "deep"]
+    );
+
+    let shallow =
+      read_dir_with_options(root, &ReadDirOptions::new().recursive(true).max_depth(2)).unwrap();
+    let deep = &shallow[4].children.as_ref().unwrap()[1];
+    assert_eq!(deep.children.as_ref().unwrap().len(), 0);
+
+    let filtered = read_dir_with_options(
+      root,
+      &ReadDirOptions::new()
+        .recursive(true)
+        .include("*.rs")
+        .exclude("deep")
+        .gitignore(true)
+        .hidden(false),
+    )
+    .unwrap();
+    assert_eq!(names(&filtered), vec!["b.rs", "sub"]);
+    assert_eq!(names(filtered[1].children.as_ref().unwrap()), vec!["c.rs"]);
+
+    let walked = walk_dir(root, &ReadDirOptions::new().recursive(true).include("*.rs"))
+      .unwrap()
+      .map(|entry| entry.unwrap().name.unwrap())
+      .collect::<Vec<_>>();
+    assert_eq!(
+      walked,
+      vec!["b.rs", "sub", "c.rs", "deep", "d.rs", "target", "e.rs"]
+    );
+  }
+
+  #[cfg(unix)]
+  #[test]
+  // check the symlink policies
+  fn check_read_dir_symlinks() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    fs::create_dir(root.join("sub")).unwrap();
+    fs::write(root.join("sub/file.txt"), "").unwrap();
+    std::os::unix::fs::symlink(root.join("sub"), root.join("link")).unwrap();
+    std::os::unix::fs::symlink(root.join("missing"), root.join("broken")).unwrap();
+    // a symlink loop
+    std::os::unix::fs::symlink(root, root.join("sub/parent")).unwrap();
+
+    let followed = read_dir(root, true).unwrap();
+    assert_eq!(names(&followed), vec!["broken", "link", "sub"]);
+    assert!(followed[0].children.is_none());
+    assert_eq!(
+      names(followed[1].children.as_ref().unwrap()),
+      vec!["file.txt", "parent"]
+    );
+
+    let listed = read_dir_with_options(
+      root,
+      &ReadDirOptions::new()
+        .recursive(true)
+        .symlinks(SymlinkPolicy::List),
+    )
+    .unwrap();
+    assert_eq!(names(&listed), vec!["broken", "link", "sub"]);
+    assert!(listed[1].children.is_none());
+
+    let skipped = read_dir_with_options(
+      root,
+      &ReadDirOptions::new()
+        .recursive(true)
+        .symlinks(SymlinkPolicy::Skip),
+    )
+    .unwrap();
+    assert_eq!(names(&skipped), vec!["sub"]);
+    assert_eq!(
+      names(skipped[0].children.as_ref().unwrap()),
+      vec!["file.txt"]
+    );
+  }
+
   #[test]
   // test the with_temp_dir function
   fn check_test_dir() {
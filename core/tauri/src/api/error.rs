@@ -12,9 +12,21 @@ pub enum Error {
   /// The extract archive error.
   #[error("Extract Error: {0}")]
   Extract(String),
+  /// The create archive error.
+  #[error("Archive Error: {0}")]
+  Archive(String),
+  /// The trash error.
+  #[error("Trash Error: {0}")]
+  Trash(String),
+  /// The compression isn't enabled.
+  #[error("{0} compression requires the `{1}` feature")]
+  CompressionUnsupported(&'static str, &'static str),
   /// The path operation error.
   #[error("Path Error: {0}")]
   Path(String),
+  /// The path is not allowed by the filesystem scope.
+  #[error("path not allowed on the configured scope: {0}")]
+  PathNotAllowed(std::path::PathBuf),
   /// The path StripPrefixError error.
   #[error("Path Error: {0}")]
   PathPrefix(#[from] std::path::StripPrefixError),
@@ -32,18 +44,51 @@ pub enum Error {
   #[cfg(feature = "reqwest-client")]
   #[error("Network Error: {0}")]
   Network(#[from] reqwest::Error),
+  /// The HTTP client option is not supported by the HTTP backend.
+  #[error("{0} is only supported with the `reqwest-client` feature")]
+  HttpClientOptionUnsupported(String),
+  /// The cookie store couldn't be loaded or saved.
+  #[error("cookie store error: {0}")]
+  CookieStore(String),
   /// HTTP method error.
   #[error("{0}")]
   HttpMethod(#[from] http::method::InvalidMethod),
   /// Invalid HTTO header.
   #[error("{0}")]
   HttpHeader(#[from] http::header::InvalidHeaderName),
+  /// Invalid HTTP header value.
+  #[error("{0}")]
+  HttpHeaderValue(#[from] http::header::InvalidHeaderValue),
+  /// Invalid URL.
+  #[error("invalid URL: {0}")]
+  Url(#[from] url::ParseError),
+  /// Invalid scope pattern.
+  #[error("invalid scope pattern: {0}")]
+  ScopePattern(#[from] glob::PatternError),
+  /// The URL is not allowed by the scope.
+  #[error("URL not allowed on the configured scope: {0}")]
+  UrlNotAllowed(String),
+  /// WebSocket error.
+  #[cfg(websocket_all)]
+  #[error("WebSocket error: {0}")]
+  WebSocket(#[from] tokio_tungstenite::tungstenite::Error),
   /// Failed to serialize header value as string.
   #[error("failed to convert response header value to string")]
   HttpHeaderToString(#[from] http::header::ToStrError),
   /// HTTP form to must be an object.
   #[error("http form must be an object")]
   InvalidHttpForm,
+  /// The download was cancelled.
+  #[error("download cancelled")]
+  DownloadCancelled,
+  /// The downloaded file doesn't match the expected checksum.
+  #[error("checksum mismatch: expected {expected}, got {actual}")]
+  ChecksumMismatch {
+    /// The expected checksum.
+    expected: String,
+    /// The checksum of the downloaded file.
+    actual: String,
+  },
   /// Semver error.
   #[error("{0}")]
   Semver(#[from] semver::Error),
@@ -62,10 +107,22 @@ pub enum Error {
   /// ZIP error.
   #[error("{0}")]
   Zip(#[from] zip::result::ZipError),
+  /// File system watcher error.
+  #[cfg(fs_watch)]
+  #[error("failed to watch path: {0}")]
+  Watch(#[from] notify::Error),
   /// Notification error.
   #[cfg(notification_all)]
   #[error("{0}")]
   Notification(#[from] notify_rust::error::Error),
+  /// Failed to close a notification.
+  #[cfg(notification_all)]
+  #[error("failed to close the notification: {0}")]
+  NotificationClose(String),
+  /// A notification option isn't supported on the current platform.
+  #[cfg(notification_all)]
+  #[error("the notification {0} option is not supported on this platform")]
+  NotificationUnsupported(&'static str),
   /// failed to detect the current platform.
   #[error("failed to detect platform: {0}")]
   FailedToDetectPlatform(String),
@@ -2,13 +2,19 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-License-Identifier: MIT
 
+mod archive;
 mod extract;
 mod file_move;
+mod metadata;
+mod write;
 
 use std::{fs, path::Path};
 
+pub use archive::*;
 pub use extract::*;
 pub use file_move::*;
+pub use metadata::*;
+pub use write::*;
 
 /// Reads a string file.
 pub fn read_string<P: AsRef<Path>>(file: P) -> crate::api::Result<String> {
@@ -0,0 +1,373 @@
+// Copyright 2019-2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use super::{
+  extract::{detect_archive_type, unsupported_compression},
+  ArchiveFormat, Compression,
+};
+use ignore::WalkBuilder;
+use serde::Serialize;
+
+use std::{
+  fs,
+  io::{self, Write},
+  path::{Path, PathBuf},
+};
+
+/// The progress of an archive creation, reported after each entry is added.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct ArchiveProgress {
+  /// The path of the last added entry in the archive.
+  pub entry: PathBuf,
+  /// The number of added entries.
+  pub entries: usize,
+  /// The total number of entries.
+  pub total_entries: usize,
+  /// The number of added bytes, before compression.
+  pub bytes: u64,
+  /// The total number of bytes, before compression.
+  pub total_bytes: u64,
+}
+
+// A file or directory to add to the archive
+struct Entry {
+  source: PathBuf,
+  name: PathBuf,
+  is_dir: bool,
+  size: u64,
+}
+
+// A writer applying the compression of a tar archive
+enum CompressedWriter {
+  Plain(fs::File),
+  Gz(flate2::write::GzEncoder<fs::File>),
+  #[cfg(feature = "compression-bz2")]
+  Bz2(bzip2::write::BzEncoder<fs::File>),
+  #[cfg(feature = "compression-xz")]
+  Xz(xz2::write::XzEncoder<fs::File>),
+  #[cfg(feature = "compression-zstd")]
+  Zstd(zstd::stream::write::Encoder<'static, fs::File>),
+}
+
+impl CompressedWriter {
+  fn new(file: fs::File, compression: Option<Compression>) -> crate::api::Result<Self> {
+    let writer = match compression {
+      None => Self::Plain(file),
+      Some(Compression::Gz) => Self::Gz(flate2::write::GzEncoder::new(
+        file,
+        flate2::Compression::default(),
+      )),
+      #[cfg(feature = "compression-bz2")]
+      Some(Compression::Bz2) => Self::Bz2(bzip2::write::BzEncoder::new(
+        file,
+        bzip2::Compression::default(),
+      )),
+      #[cfg(feature = "compression-xz")]
+      Some(Compression::Xz) => Self::Xz(xz2::write::XzEncoder::new(file, 6)),
+      #[cfg(feature = "compression-zstd")]
+      Some(Compression::Zstd) => Self::Zstd(zstd::stream::write::Encoder::new(file, 0)?),
+      #[allow(unreachable_patterns)]
+      Some(compression) => return Err(unsupported_compression(compression)),
+    };
+    Ok(writer)
+  }
+
+  fn finish(self) -> io::Result<()> {
+    match self {
+      Self::Plain(mut file) => file.flush(),
+      Self::Gz(encoder) => encoder.finish().map(|_| ()),
+      #[cfg(feature = "compression-bz2")]
+      Self::Bz2(encoder) => encoder.finish().map(|_| ()),
+      #[cfg(feature = "compression-xz")]
+      Self::Xz(encoder) => encoder.finish().map(|_| ()),
+      #[cfg(feature = "compression-zstd")]
+      Self::Zstd(encoder) => encoder.finish().map(|_| ()),
+    }
+  }
+}
+
+impl Write for CompressedWriter {
+  fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+    match self {
+      Self::Plain(file) => file.write(buf),
+      Self::Gz(encoder) => encoder.write(buf),
+      #[cfg(feature = "compression-bz2")]
+      Self::Bz2(encoder) => encoder.write(buf),
+      #[cfg(feature = "compression-xz")]
+      Self::Xz(encoder) => encoder.write(buf),
+      #[cfg(feature = "compression-zstd")]
+      Self::Zstd(encoder) => encoder.write(buf),
+    }
+  }
+
+  fn flush(&mut self) -> io::Result<()> {
+    match self {
+      Self::Plain(file) => file.flush(),
+      Self::Gz(encoder) => encoder.flush(),
+      #[cfg(feature = "compression-bz2")]
+      Self::Bz2(encoder) => encoder.flush(),
+      #[cfg(feature = "compression-xz")]
+      Self::Xz(encoder) => encoder.flush(),
+      #[cfg(feature = "compression-zstd")]
+      Self::Zstd(encoder) => encoder.flush(),
+    }
+  }
+}
+
+/// The archive builder, creating tar (optionally compressed) and zip archives.
+///
+/// Directories are added recursively and symlinks are followed.
+///
+/// # Examples
+///
+/// ```no_run
+/// use tauri::api::file::Archive;
+/// use std::path::Path;
+///
+/// Archive::to_destination("/home/user/export.tar.gz")
+///   .add(Path::new("/home/user/project/src"))
+///   .add_as(Path::new("/home/user/project/README.md"), "docs/README.md")
+///   .create_with_progress(|progress| {
+///     println!("{}/{} bytes", progress.bytes, progress.total_bytes);
+///   })
+///   .unwrap();
+/// ```
+#[derive(Debug)]
+pub struct Archive {
+  destination: PathBuf,
+  archive_format: Option<ArchiveFormat>,
+  paths: Vec<(PathBuf, PathBuf)>,
+}
+
+impl Archive {
+  /// Creates an archive builder writing to the destination path.
+  pub fn to_destination<P: Into<PathBuf>>(destination: P) -> Self {
+    Self {
+      destination: destination.into(),
+      archive_format: None,
+      paths: Vec::new(),
+    }
+  }
+
+  /// Specify the archive format. If not specified, the archive format will be determined from
+  /// the destination file extension.
+  pub fn archive_format(&mut self, format: ArchiveFormat) -> &mut Self {
+    self.archive_format = Some(format);
+    self
+  }
+
+  /// Adds a file or directory at the root of the archive, under its file name.
+  pub fn add<P: AsRef<Path>>(&mut self, path: P) -> &mut Self {
+    let name = path
+      .as_ref()
+      .file_name()
+      .map(PathBuf::from)
+      .unwrap_or_default();
+    self.add_as(path, name)
+  }
+
+  /// Adds a file or directory under the given path of the archive.
+  pub fn add_as<P: AsRef<Path>, N: AsRef<Path>>(&mut self, path: P, name: N) -> &mut Self {
+    self
+      .paths
+      .push((path.as_ref().to_path_buf(), name.as_ref().to_path_buf()));
+    self
+  }
+
+  /// Creates the archive.
+  pub fn create(&self) -> crate::api::Result<()> {
+    self.create_with_progress(|_| ())
+  }
+
+  /// Creates the archive, calling `on_progress` after each entry is added.
+  ///
+  /// The destination file is removed if the creation fails.
+  pub fn create_with_progress<F: FnMut(ArchiveProgress)>(
+    &self,
+    on_progress: F,
+  ) -> crate::api::Result<()> {
+    let format = self
+      .archive_format
+      .unwrap_or_else(|| detect_archive_type(&self.destination));
+    if let ArchiveFormat::Plain(_) = format {
+      return Err(crate::api::Error::Archive(format!(
+        "{} isn't a tar or zip archive",
+        self.destination.display()
+      )));
+    }
+
+    let file = fs::File::create(&self.destination)?;
+    let result = self.write(file, format, on_progress);
+    if result.is_err() {
+      let _ = fs::remove_file(&self.destination);
+    }
+    result
+  }
+
+  fn write<F: FnMut(ArchiveProgress)>(
+    &self,
+    file: fs::File,
+    format: ArchiveFormat,
+    mut on_progress: F,
+  ) -> crate::api::Result<()> {
+    let entries = self.entries()?;
+    let mut progress = ArchiveProgress {
+      total_entries: entries.len(),
+      total_bytes: entries.iter().map(|entry| entry.size).sum(),
+      ..Default::default()
+    };
+    let mut report = |entry: &Entry| {
+      progress.entry = entry.name.clone();
+      progress.entries += 1;
+      progress.bytes += entry.size;
+      on_progress(progress.clone());
+    };
+
+    match format {
+      ArchiveFormat::Tar(compression) => {
+        let mut builder = tar::Builder::new(CompressedWriter::new(file, compression)?);
+        for entry in &entries {
+          if entry.is_dir {
+            builder.append_dir(&entry.name, &entry.source)?;
+          } else {
+            builder.append_path_with_name(&entry.source, &entry.name)?;
+          }
+          report(entry);
+        }
+        builder.into_inner()?.finish()?;
+      }
+      ArchiveFormat::Zip => {
+        let mut writer = zip::ZipWriter::new(file);
+        for entry in &entries {
+          let options = zip::write::FileOptions::default();
+          #[cfg(unix)]
+          let options = {
+            use std::os::unix::fs::PermissionsExt;
+            options.unix_permissions(fs::metadata(&entry.source)?.permissions().mode())
+          };
+          let name = zip_name(&entry.name);
+          if entry.is_dir {
+            writer.add_directory(name, options)?;
+          } else {
+            writer.start_file(name, options)?;
+            io::copy(&mut fs::File::open(&entry.source)?, &mut writer)?;
+          }
+          report(entry);
+        }
+        writer.finish()?;
+      }
+      ArchiveFormat::Plain(_) => unreachable!(),
+    }
+    Ok(())
+  }
+
+  // Lists the files and directories to add, the archive itself is skipped
+  fn entries(&self) -> crate::api::Result<Vec<Entry>> {
+    let destination = self.destination.canonicalize()?;
+    let mut entries = Vec::new();
+    for (path, name) in &self.paths {
+      let walk = WalkBuilder::new(path)
+        .standard_filters(false)
+        .follow_links(true)
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .build();
+      for entry in walk {
+        let entry = entry?;
+        let relative = entry.path().strip_prefix(path)?;
+        let name = name.join(relative);
+        if name.as_os_str().is_empty() || entry.path().canonicalize()? == destination {
+          continue;
+        }
+        let metadata = entry.metadata()?;
+        entries.push(Entry {
+          source: entry.path().to_path_buf(),
+          name,
+          is_dir: metadata.is_dir(),
+          size: if metadata.is_dir() { 0 } else { metadata.len() },
+        });
+      }
+    }
+    Ok(entries)
+  }
+}
+
+// The zip entry names always use `/` as separator
+fn zip_name(name: &Path) -> String {
+  name
+    .components()
+    .map(|component| component.as_os_str().to_string_lossy())
+    .collect::<Vec<_>>()
+    .join("/")
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::api::file::Extract;
+
+  fn create_and_extract(file_name: &str) {
+    let source = tempfile::tempdir().unwrap();
+    fs::create_dir_all(source.path().join("project/src")).unwrap();
+    fs::write(source.path().join("project/src/main.rs"), "fn main() {}").unwrap();
+    fs::write(source.path().join("README.md"), "# Project").unwrap();
+
+    let out = tempfile::tempdir().unwrap();
+    let destination = out.path().join(file_name);
+    let mut reports = Vec::new();
+    Archive::to_destination(&destination)
+      .add(source.path().join("project"))
+      .add_as(source.path().join("README.md"), "docs/README.md")
+      .create_with_progress(|progress| reports.push(progress))
+      .unwrap();
+
+    // project, project/src, project/src/main.rs and docs/README.md
+    assert_eq!(reports.len(), 4);
+    let last = reports.last().unwrap();
+    assert_eq!(last.entries, last.total_entries);
+    assert_eq!(last.bytes, 21);
+    assert_eq!(last.bytes, last.total_bytes);
+
+    let extracted = tempfile::tempdir().unwrap();
+    Extract::from_source(&destination)
+      .extract_into(extracted.path())
+      .unwrap();
+    assert_eq!(
+      fs::read_to_string(extracted.path().join("project/src/main.rs")).unwrap(),
+      "fn main() {}"
+    );
+    assert_eq!(
+      fs::read_to_string(extracted.path().join("docs/README.md")).unwrap(),
+      "# Project"
+    );
+  }
+
+  #[test]
+  fn tar_gz_archive() {
+    create_and_extract("export.tar.gz");
+  }
+
+  #[test]
+  fn zip_archive() {
+    create_and_extract("export.zip");
+  }
+
+  #[cfg(feature = "compression-zstd")]
+  #[test]
+  fn tar_zst_archive() {
+    create_and_extract("export.tar.zst");
+  }
+
+  #[test]
+  fn plain_destination() {
+    let out = tempfile::tempdir().unwrap();
+    let destination = out.path().join("export.txt");
+    assert!(Archive::to_destination(&destination)
+      .add("test/api/test.txt")
+      .create()
+      .is_err());
+    assert!(!destination.exists());
+  }
+}
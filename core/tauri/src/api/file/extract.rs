@@ -2,9 +2,11 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-License-Identifier: MIT
 
-use either::{self, Either};
-
-use std::{fs, io, path};
+use std::{
+  fs,
+  io::{self, Read},
+  path::{self, Component},
+};
 
 /// The supported archive formats.
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -24,6 +26,12 @@ pub enum ArchiveFormat {
 pub enum Compression {
   /// Gz compression (e.g. `.tar.gz` archives)
   Gz,
+  /// Bz2 compression (e.g. `.tar.bz2` archives), requires the `compression-bz2` feature.
+  Bz2,
+  /// Xz compression (e.g. `.tar.xz` archives), requires the `compression-xz` feature.
+  Xz,
+  /// Zstd compression (e.g. `.tar.zst` archives), requires the `compression-zstd` feature.
+  Zstd,
 }
 
 /// The extract manager.
@@ -33,22 +41,75 @@ pub struct Extract<'a> {
   archive_format: Option<ArchiveFormat>,
 }
 
-fn detect_archive_type(path: &path::Path) -> ArchiveFormat {
-  match path.extension() {
-    Some(extension) if extension == std::ffi::OsStr::new("zip") => ArchiveFormat::Zip,
-    Some(extension) if extension == std::ffi::OsStr::new("tar") => ArchiveFormat::Tar(None),
-    Some(extension) if extension == std::ffi::OsStr::new("gz") => match path
-      .file_stem()
-      .map(|e| path::Path::new(e))
-      .and_then(|f| f.extension())
+pub(super) fn detect_archive_type(path: &path::Path) -> ArchiveFormat {
+  let extension = match path.extension().and_then(|e| e.to_str()) {
+    Some(extension) => extension,
+    None => return ArchiveFormat::Plain(None),
+  };
+  let compression = match extension {
+    "zip" => return ArchiveFormat::Zip,
+    "tar" => return ArchiveFormat::Tar(None),
+    "tgz" => return ArchiveFormat::Tar(Some(Compression::Gz)),
+    "tbz2" => return ArchiveFormat::Tar(Some(Compression::Bz2)),
+    "txz" => return ArchiveFormat::Tar(Some(Compression::Xz)),
+    "tzst" => return ArchiveFormat::Tar(Some(Compression::Zstd)),
+    "gz" => Compression::Gz,
+    "bz2" => Compression::Bz2,
+    "xz" => Compression::Xz,
+    "zst" => Compression::Zstd,
+    _ => return ArchiveFormat::Plain(None),
+  };
+  match path
+    .file_stem()
+    .map(|e| path::Path::new(e))
+    .and_then(|f| f.extension())
+  {
+    Some(extension) if extension == std::ffi::OsStr::new("tar") => {
+      ArchiveFormat::Tar(Some(compression))
+    }
+    _ => ArchiveFormat::Plain(Some(compression)),
+  }
+}
+
+// Joins the archive entry name to the destination, failing if it escapes it (e.g. `../../.bashrc`)
+fn enclosed_path(into_dir: &path::Path, name: &str) -> crate::api::Result<path::PathBuf> {
+  let name = path::Path::new(name);
+  if name
+    .components()
+    .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+  {
+    Ok(into_dir.join(name))
+  } else {
+    Err(crate::api::Error::Extract(format!(
+      "the archive entry {} is outside of the destination directory",
+      name.display()
+    )))
+  }
+}
+
+// Extracts a zip entry, creating its parent directories
+fn unpack_zip_file(
+  file: &mut zip::read::ZipFile<'_>,
+  into_dir: &path::Path,
+) -> crate::api::Result<()> {
+  let path = enclosed_path(into_dir, file.name())?;
+  if file.is_dir() {
+    fs::create_dir_all(&path)?;
+  } else {
+    if let Some(parent) = path.parent() {
+      fs::create_dir_all(parent)?;
+    }
+    let mut output = fs::File::create(&path)?;
+    io::copy(file, &mut output)?;
+    #[cfg(unix)]
     {
-      Some(extension) if extension == std::ffi::OsStr::new("tar") => {
-        ArchiveFormat::Tar(Some(Compression::Gz))
+      use std::os::unix::fs::PermissionsExt;
+      if let Some(mode) = file.unix_mode() {
+        fs::set_permissions(&path, fs::Permissions::from_mode(mode & 0o777))?;
       }
-      _ => ArchiveFormat::Plain(Some(Compression::Gz)),
-    },
-    _ => ArchiveFormat::Plain(None),
+    }
   }
+  Ok(())
 }
 
 impl<'a> Extract<'a> {
@@ -70,11 +131,20 @@ impl<'a> Extract<'a> {
   fn get_archive_reader(
     source: fs::File,
     compression: Option<Compression>,
-  ) -> Either<fs::File, flate2::read::GzDecoder<fs::File>> {
-    match compression {
-      Some(Compression::Gz) => Either::Right(flate2::read::GzDecoder::new(source)),
-      None => Either::Left(source),
-    }
+  ) -> crate::api::Result<Box<dyn Read>> {
+    let reader: Box<dyn Read> = match compression {
+      None => Box::new(source),
+      Some(Compression::Gz) => Box::new(flate2::read::GzDecoder::new(source)),
+      #[cfg(feature = "compression-bz2")]
+      Some(Compression::Bz2) => Box::new(bzip2::read::MultiBzDecoder::new(source)),
+      #[cfg(feature = "compression-xz")]
+      Some(Compression::Xz) => Box::new(xz2::read::XzDecoder::new_multi_decoder(source)),
+      #[cfg(feature = "compression-zstd")]
+      Some(Compression::Zstd) => Box::new(zstd::stream::read::Decoder::new(source)?),
+      #[allow(unreachable_patterns)]
+      Some(compression) => return Err(unsupported_compression(compression)),
+    };
+    Ok(reader)
   }
 
   /// Extract an entire source archive into a specified path. If the source is a single compressed
@@ -88,7 +158,7 @@ impl<'a> Extract<'a> {
 
     match archive {
       ArchiveFormat::Plain(compression) | ArchiveFormat::Tar(compression) => {
-        let mut reader = Self::get_archive_reader(source, compression);
+        let mut reader = Self::get_archive_reader(source, compression)?;
 
         match archive {
           ArchiveFormat::Plain(_) => {
@@ -119,9 +189,7 @@ impl<'a> Extract<'a> {
         let mut archive = zip::ZipArchive::new(source)?;
         for i in 0..archive.len() {
           let mut file = archive.by_index(i)?;
-          let path = into_dir.join(file.name());
-          let mut output = fs::File::create(path)?;
-          io::copy(&mut file, &mut output)?;
+          unpack_zip_file(&mut file, into_dir)?;
         }
       }
     };
@@ -144,7 +212,7 @@ impl<'a> Extract<'a> {
 
     match archive {
       ArchiveFormat::Plain(compression) | ArchiveFormat::Tar(compression) => {
-        let mut reader = Self::get_archive_reader(source, compression);
+        let mut reader = Self::get_archive_reader(source, compression)?;
 
         match archive {
           ArchiveFormat::Plain(_) => {
@@ -189,10 +257,64 @@ impl<'a> Extract<'a> {
             .to_str()
             .expect("Could not convert file to str"),
         )?;
-        let mut output = fs::File::create(into_dir.join(file.name()))?;
-        io::copy(&mut file, &mut output)?;
+        unpack_zip_file(&mut file, into_dir)?;
       }
     };
     Ok(())
   }
 }
+
+// The error of a compression disabled at compile time.
+pub(super) fn unsupported_compression(compression: Compression) -> crate::api::Error {
+  match compression {
+    Compression::Gz => unreachable!("gz compression is always enabled"),
+    Compression::Bz2 => crate::api::Error::CompressionUnsupported("bz2", "compression-bz2"),
+    Compression::Xz => crate::api::Error::CompressionUnsupported("xz", "compression-xz"),
+    Compression::Zstd => crate::api::Error::CompressionUnsupported("zstd", "compression-zstd"),
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use std::io::Write;
+
+  #[test]
+  fn archive_type() {
+    assert_eq!(
+      detect_archive_type(path::Path::new("app.tar.xz")),
+      ArchiveFormat::Tar(Some(Compression::Xz))
+    );
+    assert_eq!(
+      detect_archive_type(path::Path::new("app.tgz")),
+      ArchiveFormat::Tar(Some(Compression::Gz))
+    );
+    assert_eq!(
+      detect_archive_type(path::Path::new("app.zst")),
+      ArchiveFormat::Plain(Some(Compression::Zstd))
+    );
+    assert_eq!(
+      detect_archive_type(path::Path::new("app.zip")),
+      ArchiveFormat::Zip
+    );
+  }
+
+  #[test]
+  fn zip_slip() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = dir.path().join("evil.zip");
+    let mut writer = zip::ZipWriter::new(fs::File::create(&source).unwrap());
+    writer
+      .start_file("../evil.txt", zip::write::FileOptions::default())
+      .unwrap();
+    writer.write_all(b"evil").unwrap();
+    writer.finish().unwrap();
+
+    let into_dir = dir.path().join("out");
+    fs::create_dir(&into_dir).unwrap();
+    assert!(Extract::from_source(&source)
+      .extract_into(&into_dir)
+      .is_err());
+    assert!(!dir.path().join("evil.txt").exists());
+  }
+}
@@ -0,0 +1,182 @@
+// Copyright 2019-2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use serde::{Deserialize, Serialize};
+use std::{
+  fs,
+  path::Path,
+  time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The metadata of a file or directory.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct Metadata {
+  /// The size of the file in bytes.
+  pub size: u64,
+  /// The last modification time, in milliseconds since the Unix epoch.
+  pub modified_at: Option<u64>,
+  /// The last access time, in milliseconds since the Unix epoch.
+  pub accessed_at: Option<u64>,
+  /// The creation time, in milliseconds since the Unix epoch. Not available on every platform.
+  pub created_at: Option<u64>,
+  /// Whether the path is a file, following symlinks.
+  pub is_file: bool,
+  /// Whether the path is a directory, following symlinks.
+  pub is_dir: bool,
+  /// Whether the path itself is a symlink.
+  pub is_symlink: bool,
+  /// Whether the file is read-only.
+  pub readonly: bool,
+  /// The Unix permission bits, e.g. `0o644`. `None` on Windows.
+  pub mode: Option<u32>,
+}
+
+impl From<fs::Metadata> for Metadata {
+  fn from(metadata: fs::Metadata) -> Self {
+    #[cfg(unix)]
+    let mode = {
+      use std::os::unix::fs::PermissionsExt;
+      Some(metadata.permissions().mode() & 0o7777)
+    };
+    #[cfg(not(unix))]
+    let mode = None;
+
+    Self {
+      size: metadata.len(),
+      modified_at: metadata.modified().ok().and_then(millis_since_epoch),
+      accessed_at: metadata.accessed().ok().and_then(millis_since_epoch),
+      created_at: metadata.created().ok().and_then(millis_since_epoch),
+      is_file: metadata.is_file(),
+      is_dir: metadata.is_dir(),
+      is_symlink: metadata.file_type().is_symlink(),
+      readonly: metadata.permissions().readonly(),
+      mode,
+    }
+  }
+}
+
+fn millis_since_epoch(time: SystemTime) -> Option<u64> {
+  time
+    .duration_since(UNIX_EPOCH)
+    .ok()
+    .map(|duration| duration.as_millis() as u64)
+}
+
+/// The permissions to set on a file or directory.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Permissions {
+  /// Makes the file read-only or writable.
+  pub readonly: Option<bool>,
+  /// The Unix permission bits, e.g. `0o644`. Ignored on Windows.
+  pub mode: Option<u32>,
+}
+
+/// Reads the metadata of a file or directory.
+///
+/// Symlinks are followed, but [`Metadata::is_symlink`] tells whether the path itself is a symlink.
+/// The metadata of a broken symlink is the metadata of the link.
+pub fn metadata<P: AsRef<Path>>(path: P) -> crate::api::Result<Metadata> {
+  let link_metadata = fs::symlink_metadata(&path)?;
+  let is_symlink = link_metadata.file_type().is_symlink();
+  let mut metadata = if is_symlink {
+    fs::metadata(&path).unwrap_or(link_metadata).into()
+  } else {
+    Metadata::from(link_metadata)
+  };
+  metadata.is_symlink = is_symlink;
+  Ok(metadata)
+}
+
+/// Checks if the path exists. Broken symlinks don't exist.
+pub fn exists<P: AsRef<Path>>(path: P) -> bool {
+  path.as_ref().exists()
+}
+
+/// Sets the permissions of a file or directory.
+pub fn set_permissions<P: AsRef<Path>>(
+  path: P,
+  permissions: Permissions,
+) -> crate::api::Result<()> {
+  let mut current = fs::metadata(&path)?.permissions();
+  #[cfg(unix)]
+  {
+    if let Some(mode) = permissions.mode {
+      use std::os::unix::fs::PermissionsExt;
+      current.set_mode(mode);
+    }
+  }
+  if let Some(readonly) = permissions.readonly {
+    current.set_readonly(readonly);
+  }
+  fs::set_permissions(path, current)?;
+  Ok(())
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn file_metadata() {
+    let file = metadata("test/api/test.txt").unwrap();
+    assert!(file.is_file);
+    assert!(!file.is_dir);
+    assert!(!file.is_symlink);
+    assert!(file.size > 0);
+    assert!(file.modified_at.is_some());
+
+    assert!(metadata("test/api").unwrap().is_dir);
+    assert!(metadata("test/api/missing.txt").is_err());
+    assert!(exists("test/api/test.txt"));
+    assert!(!exists("test/api/missing.txt"));
+  }
+
+  #[test]
+  fn readonly_permissions() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("file.txt");
+    fs::write(&file, "contents").unwrap();
+
+    set_permissions(
+      &file,
+      Permissions {
+        readonly: Some(true),
+        mode: None,
+      },
+    )
+    .unwrap();
+    assert!(metadata(&file).unwrap().readonly);
+
+    set_permissions(
+      &file,
+      Permissions {
+        readonly: Some(false),
+        mode: None,
+      },
+    )
+    .unwrap();
+    assert!(!metadata(&file).unwrap().readonly);
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn unix_mode() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("script.sh");
+    fs::write(&file, "#!/bin/sh").unwrap();
+
+    set_permissions(
+      &file,
+      Permissions {
+        readonly: None,
+        mode: Some(0o750),
+      },
+    )
+    .unwrap();
+    assert_eq!(metadata(&file).unwrap().mode, Some(0o750));
+  }
+}
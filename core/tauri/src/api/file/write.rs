@@ -0,0 +1,217 @@
+// Copyright 2019-2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+  ffi::OsStr,
+  fs,
+  io::{self, Write},
+  path::{Path, PathBuf},
+};
+
+/// The options of the [`write_with_options`] function.
+///
+/// By default, the file is created or truncated.
+#[derive(Debug, Clone, Default)]
+pub struct WriteOptions {
+  append: bool,
+  create_new: bool,
+  atomic: bool,
+}
+
+impl WriteOptions {
+  /// Creates the default options.
+  pub fn new() -> Self {
+    Default::default()
+  }
+
+  /// Appends the contents to the file instead of replacing it.
+  pub fn append(mut self, append: bool) -> Self {
+    self.append = append;
+    self
+  }
+
+  /// Fails if the file already exists.
+  pub fn create_new(mut self, create_new: bool) -> Self {
+    self.create_new = create_new;
+    self
+  }
+
+  /// Writes the contents to a temporary file in the same directory, syncs it to the disk and renames it to the path,
+  /// so the file is either left untouched or fully written, even if the app crashes. Can't be combined with `append`.
+  ///
+  /// Like the other writes, the replaced file keeps its permissions and a new file gets the default permissions.
+  pub fn atomic(mut self, atomic: bool) -> Self {
+    self.atomic = atomic;
+    self
+  }
+}
+
+/// Writes the contents to a file, creating it if it doesn't exist and replacing it otherwise.
+pub fn write<P: AsRef<Path>, C: AsRef<[u8]>>(path: P, contents: C) -> crate::api::Result<()> {
+  write_with_options(path, contents, &WriteOptions::new())
+}
+
+/// Writes the contents to a file with the given options.
+///
+/// # Examples
+///
+/// ```no_run
+/// use tauri::api::file::{write_with_options, WriteOptions};
+///
+/// write_with_options(
+///   "/home/user/project/settings.json",
+///   r#"{ "theme": "dark" }"#,
+///   &WriteOptions::new().atomic(true),
+/// )
+/// .unwrap();
+/// ```
+pub fn write_with_options<P: AsRef<Path>, C: AsRef<[u8]>>(
+  path: P,
+  contents: C,
+  options: &WriteOptions,
+) -> crate::api::Result<()> {
+  let path = path.as_ref();
+  if options.atomic {
+    if options.append {
+      return Err(
+        io::Error::new(
+          io::ErrorKind::InvalidInput,
+          "atomic writes can't append to the file",
+        )
+        .into(),
+      );
+    }
+    write_atomic(path, contents.as_ref(), options)
+  } else {
+    let mut file = fs::OpenOptions::new()
+      .write(true)
+      .append(options.append)
+      .truncate(!options.append)
+      .create(!options.create_new)
+      .create_new(options.create_new)
+      .open(path)?;
+    file.write_all(contents.as_ref())?;
+    Ok(())
+  }
+}
+
+fn write_atomic(path: &Path, contents: &[u8], options: &WriteOptions) -> crate::api::Result<()> {
+  let file_name = path
+    .file_name()
+    .ok_or_else(|| crate::api::Error::Path(format!("{} isn't a file path", path.display())))?;
+  let dir = match path.parent() {
+    Some(parent) if !parent.as_os_str().is_empty() => parent,
+    _ => Path::new("."),
+  };
+
+  let (temp, mut file) = TempPath::create_in(dir, file_name)?;
+  file.write_all(contents)?;
+  if let Ok(metadata) = fs::metadata(path) {
+    fs::set_permissions(&temp.0, metadata.permissions())?;
+  }
+  file.sync_all()?;
+  // Windows can't rename an open file
+  drop(file);
+
+  if options.create_new {
+    // unlike the rename, the link fails if the path exists
+    fs::hard_link(&temp.0, path)?;
+  } else {
+    fs::rename(&temp.0, path)?;
+  }
+
+  // the rename is only durable once the directory is synced
+  #[cfg(unix)]
+  fs::File::open(dir)?.sync_all()?;
+  Ok(())
+}
+
+// The path of the temporary file of an atomic write, removed when it's dropped
+// so the file doesn't remain if anything fails before it's renamed
+struct TempPath(PathBuf);
+
+impl TempPath {
+  // The file is created like any other file, so it gets the default permissions
+  // (`0o666` minus the umask on Unix) instead of the owner-only permissions of a temporary file
+  fn create_in(dir: &Path, file_name: &OsStr) -> io::Result<(Self, fs::File)> {
+    loop {
+      let path = dir.join(format!(
+        ".{}.{:08x}.tmp",
+        file_name.to_string_lossy(),
+        rand::random::<u32>()
+      ));
+      match fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&path)
+      {
+        Ok(file) => return Ok((Self(path), file)),
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+        Err(e) => return Err(e),
+      }
+    }
+  }
+}
+
+impl Drop for TempPath {
+  fn drop(&mut self) {
+    let _ = fs::remove_file(&self.0);
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn write_modes() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("file.txt");
+
+    write(&file, "first").unwrap();
+    write_with_options(&file, " second", &WriteOptions::new().append(true)).unwrap();
+    assert_eq!(fs::read_to_string(&file).unwrap(), "first second");
+
+    assert!(write_with_options(&file, "new", &WriteOptions::new().create_new(true)).is_err());
+    assert!(write_with_options(
+      &file,
+      "new",
+      &WriteOptions::new().atomic(true).create_new(true)
+    )
+    .is_err());
+    assert!(
+      write_with_options(&file, "new", &WriteOptions::new().atomic(true).append(true)).is_err()
+    );
+
+    write_with_options(&file, "replaced", &WriteOptions::new().atomic(true)).unwrap();
+    assert_eq!(fs::read_to_string(&file).unwrap(), "replaced");
+    // the temporary file was renamed
+    assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn atomic_write_permissions() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("script.sh");
+    fs::write(&file, "#!/bin/sh").unwrap();
+    fs::set_permissions(&file, fs::Permissions::from_mode(0o750)).unwrap();
+
+    write_with_options(&file, "#!/bin/sh\necho", &WriteOptions::new().atomic(true)).unwrap();
+    let mode = fs::metadata(&file).unwrap().permissions().mode() & 0o777;
+    assert_eq!(mode, 0o750);
+
+    // a new file gets the same permissions as a regular write
+    let regular = dir.path().join("regular.txt");
+    let atomic = dir.path().join("atomic.txt");
+    write(&regular, "contents").unwrap();
+    write_with_options(&atomic, "contents", &WriteOptions::new().atomic(true)).unwrap();
+    assert_eq!(
+      fs::metadata(&atomic).unwrap().permissions().mode(),
+      fs::metadata(&regular).unwrap().permissions().mode()
+    );
+  }
+}
@@ -0,0 +1,136 @@
+// Copyright 2019-2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use crate::async_runtime::{channel, Receiver};
+use notify::{DebouncedEvent, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+
+use std::{
+  path::{Path, PathBuf},
+  sync::mpsc,
+  time::Duration,
+};
+
+/// A debounced file system event.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", content = "payload", rename_all = "camelCase")]
+#[non_exhaustive]
+pub enum WatchEvent {
+  /// A file or directory was created.
+  Create(PathBuf),
+  /// A file was written or its metadata changed.
+  Modify(PathBuf),
+  /// A file or directory was removed.
+  Remove(PathBuf),
+  /// A file or directory was renamed.
+  Rename {
+    /// The previous path.
+    from: PathBuf,
+    /// The new path.
+    to: PathBuf,
+  },
+  /// The watcher failed.
+  Error(String),
+}
+
+impl WatchEvent {
+  fn from_debounced(event: DebouncedEvent) -> Option<Self> {
+    let event = match event {
+      DebouncedEvent::Create(path) => Self::Create(path),
+      DebouncedEvent::Write(path) | DebouncedEvent::Chmod(path) => Self::Modify(path),
+      DebouncedEvent::Remove(path) => Self::Remove(path),
+      DebouncedEvent::Rename(from, to) => Self::Rename { from, to },
+      DebouncedEvent::Error(error, _) => Self::Error(error.to_string()),
+      // the notices are sent before the debounced events
+      _ => return None,
+    };
+    Some(event)
+  }
+}
+
+/// A file system watcher, the path is no longer watched when it's dropped.
+pub struct FsWatcher {
+  _watcher: RecommendedWatcher,
+}
+
+/// Watches the path for changes, the events of each path are debounced during the `debounce` delay.
+///
+/// Returns the receiver of the events and the watcher, which stops when dropped.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use tauri::api::fs::watch;
+///
+/// async fn run() {
+///   let (mut rx, _watcher) = watch("/var/log/app", true, Duration::from_millis(500)).unwrap();
+///   while let Some(event) = rx.recv().await {
+///     println!("{:?}", event);
+///   }
+/// }
+/// ```
+pub fn watch<P: AsRef<Path>>(
+  path: P,
+  recursive: bool,
+  debounce: Duration,
+) -> crate::api::Result<(Receiver<WatchEvent>, FsWatcher)> {
+  let (notify_tx, notify_rx) = mpsc::channel();
+  let mut watcher = notify::watcher(notify_tx, debounce)?;
+  watcher.watch(
+    path,
+    if recursive {
+      RecursiveMode::Recursive
+    } else {
+      RecursiveMode::NonRecursive
+    },
+  )?;
+
+  let (tx, rx) = channel(16);
+  // notify sends the events on a std channel, forward them until the watcher is dropped
+  std::thread::spawn(move || {
+    while let Ok(event) = notify_rx.recv() {
+      if let Some(event) = WatchEvent::from_debounced(event) {
+        if tx.blocking_send(event).is_err() {
+          break;
+        }
+      }
+    }
+  });
+
+  Ok((rx, FsWatcher { _watcher: watcher }))
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn debounced_events() {
+    let dir = tempfile::tempdir().unwrap();
+    let (mut rx, watcher) = watch(dir.path(), false, Duration::from_millis(100)).unwrap();
+
+    let file = dir.path().join("file.txt");
+    std::fs::write(&file, "first").unwrap();
+    std::fs::write(&file, "second").unwrap();
+
+    let event = rx.blocking_recv().unwrap();
+    // the writes following the creation are merged with it
+    assert!(matches!(event, WatchEvent::Create(path) if path.file_name() == file.file_name()));
+    drop(watcher);
+  }
+
+  #[test]
+  fn event_mapping() {
+    let path = PathBuf::from("file.txt");
+    assert_eq!(
+      WatchEvent::from_debounced(DebouncedEvent::Write(path.clone())),
+      Some(WatchEvent::Modify(path.clone()))
+    );
+    assert_eq!(
+      WatchEvent::from_debounced(DebouncedEvent::NoticeWrite(path)),
+      None
+    );
+  }
+}
@@ -7,9 +7,20 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use serde_repr::{Deserialize_repr, Serialize_repr};
 
-use std::{collections::HashMap, path::PathBuf, time::Duration};
+use std::{
+  collections::HashMap,
+  path::{Path, PathBuf},
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+  },
+  time::{Duration, Instant},
+};
 
 /// Client builder.
+///
+/// The TLS options and the cookie store are only supported by the `reqwest-client` backend,
+/// the default backend returns an error when they're set.
 #[derive(Clone, Default, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ClientBuilder {
@@ -17,6 +28,127 @@ pub struct ClientBuilder {
   pub max_redirections: Option<usize>,
   /// Connect timeout in seconds for the request
   pub connect_timeout: Option<u64>,
+  /// Proxy URL used for all requests, e.g. `http://proxy.example.com:8080`.
+  /// SOCKS proxies (`socks5://`) require the `reqwest-client` backend.
+  pub proxy: Option<String>,
+  /// PEM encoded root certificate files trusted in addition to the system certificates.
+  #[serde(default)]
+  pub root_certificates: Vec<PathBuf>,
+  /// The client certificate used for mutual TLS.
+  pub client_certificate: Option<ClientCertificate>,
+  /// The file persisting the cookies across restarts. Cookies are not stored if `None`.
+  #[serde(skip)]
+  pub cookie_store: Option<PathBuf>,
+  /// The retry policy of the requests. Requests are sent only once if `None`.
+  pub retry: Option<RetryPolicy>,
+}
+
+/// A client certificate used for mutual TLS.
+#[derive(Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientCertificate {
+  /// The PKCS#12 archive (`.p12` or `.pfx`) with the certificate chain and the private key.
+  pub path: PathBuf,
+  /// The password of the archive.
+  #[serde(default)]
+  pub password: String,
+}
+
+/// The retry policy of the HTTP requests.
+///
+/// A request is sent again when it fails to connect or when the response status is one of
+/// [`RetryPolicy::status_codes`], waiting an exponential backoff with jitter between the attempts.
+/// Only idempotent requests (`GET`, `HEAD`, `OPTIONS`, `PUT`, `DELETE` and `TRACE`) are retried by default.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct RetryPolicy {
+  /// The maximum number of attempts, including the first one. Defaults to 3.
+  pub max_attempts: u32,
+  /// The delay in milliseconds before the first retry, doubled on each attempt. Defaults to 200.
+  pub initial_delay: u64,
+  /// The maximum delay in milliseconds between two attempts. Defaults to 5000.
+  pub max_delay: u64,
+  /// The response status codes that are retried. Defaults to 408, 429, 500, 502, 503 and 504.
+  pub status_codes: Vec<u16>,
+  /// Whether connection errors and timeouts are retried. Defaults to true.
+  pub connect_errors: bool,
+  /// Whether non idempotent requests (e.g. `POST`) are also retried. Defaults to false.
+  pub non_idempotent: bool,
+}
+
+impl Default for RetryPolicy {
+  fn default() -> Self {
+    Self {
+      max_attempts: 3,
+      initial_delay: 200,
+      max_delay: 5000,
+      status_codes: vec![408, 429, 500, 502, 503, 504],
+      connect_errors: true,
+      non_idempotent: false,
+    }
+  }
+}
+
+impl RetryPolicy {
+  /// Creates the default retry policy.
+  pub fn new() -> Self {
+    Default::default()
+  }
+
+  /// Sets the maximum number of attempts, including the first one.
+  pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+    self.max_attempts = max_attempts;
+    self
+  }
+
+  /// Sets the delay in milliseconds before the first retry.
+  pub fn initial_delay(mut self, initial_delay: u64) -> Self {
+    self.initial_delay = initial_delay;
+    self
+  }
+
+  /// Sets the maximum delay in milliseconds between two attempts.
+  pub fn max_delay(mut self, max_delay: u64) -> Self {
+    self.max_delay = max_delay;
+    self
+  }
+
+  /// Sets the response status codes that are retried.
+  pub fn status_codes(mut self, status_codes: Vec<u16>) -> Self {
+    self.status_codes = status_codes;
+    self
+  }
+
+  /// Sets whether connection errors and timeouts are retried.
+  pub fn connect_errors(mut self, connect_errors: bool) -> Self {
+    self.connect_errors = connect_errors;
+    self
+  }
+
+  /// Sets whether non idempotent requests are also retried.
+  pub fn non_idempotent(mut self, non_idempotent: bool) -> Self {
+    self.non_idempotent = non_idempotent;
+    self
+  }
+
+  fn allows_method(&self, method: &str) -> bool {
+    self.non_idempotent
+      || matches!(
+        method.to_uppercase().as_str(),
+        "GET" | "HEAD" | "OPTIONS" | "PUT" | "DELETE" | "TRACE"
+      )
+  }
+
+  // The delay before the given retry: the exponential backoff with half of it randomized,
+  // so the clients failing at the same time don't retry at the same time
+  fn backoff(&self, retry: u32) -> Duration {
+    let delay = self
+      .initial_delay
+      .saturating_mul(2u64.saturating_pow(retry.saturating_sub(1)))
+      .min(self.max_delay);
+    let jitter = rand::random::<u64>() % (delay / 2 + 1);
+    Duration::from_millis(delay - delay / 2 + jitter)
+  }
 }
 
 impl ClientBuilder {
@@ -37,9 +169,63 @@ impl ClientBuilder {
     self
   }
 
+  /// Sets the proxy used for all requests.
+  pub fn proxy(mut self, proxy: impl Into<String>) -> Self {
+    self.proxy = Some(proxy.into());
+    self
+  }
+
+  /// Trusts the PEM encoded root certificate file in addition to the system certificates.
+  pub fn root_certificate(mut self, path: impl Into<PathBuf>) -> Self {
+    self.root_certificates.push(path.into());
+    self
+  }
+
+  /// Sets the client certificate used for mutual TLS.
+  pub fn client_certificate(mut self, certificate: ClientCertificate) -> Self {
+    self.client_certificate = Some(certificate);
+    self
+  }
+
+  /// Enables the cookie store, persisted on the given JSON file.
+  pub fn cookie_store(mut self, path: impl Into<PathBuf>) -> Self {
+    self.cookie_store = Some(path.into());
+    self
+  }
+
+  /// Sets the retry policy of the requests.
+  pub fn retry(mut self, retry: RetryPolicy) -> Self {
+    self.retry = Some(retry);
+    self
+  }
+
   /// Builds the Client.
   #[cfg(not(feature = "reqwest-client"))]
   pub fn build(self) -> crate::api::Result<Client> {
+    // validate the proxy URL early, it's applied to each request
+    if let Some(proxy) = &self.proxy {
+      let url = url::Url::parse(proxy)?;
+      if url.scheme().starts_with("socks") {
+        return Err(crate::api::Error::HttpClientOptionUnsupported(
+          "SOCKS proxy".into(),
+        ));
+      }
+    }
+    if !self.root_certificates.is_empty() {
+      return Err(crate::api::Error::HttpClientOptionUnsupported(
+        "rootCertificates".into(),
+      ));
+    }
+    if self.client_certificate.is_some() {
+      return Err(crate::api::Error::HttpClientOptionUnsupported(
+        "clientCertificate".into(),
+      ));
+    }
+    if self.cookie_store.is_some() {
+      return Err(crate::api::Error::HttpClientOptionUnsupported(
+        "cookie store".into(),
+      ));
+    }
     Ok(Client(self))
   }
 
@@ -56,15 +242,91 @@ impl ClientBuilder {
       client_builder = client_builder.connect_timeout(Duration::from_secs(connect_timeout));
     }
 
+    if let Some(proxy) = &self.proxy {
+      client_builder = client_builder.proxy(reqwest::Proxy::all(proxy.as_str())?);
+    }
+
+    for path in &self.root_certificates {
+      let certificate = reqwest::Certificate::from_pem(&std::fs::read(path)?)?;
+      client_builder = client_builder.add_root_certificate(certificate);
+    }
+
+    if let Some(certificate) = &self.client_certificate {
+      let identity = reqwest::Identity::from_pkcs12_der(
+        &std::fs::read(&certificate.path)?,
+        &certificate.password,
+      )?;
+      client_builder = client_builder.identity(identity);
+    }
+
+    let cookie_jar = match self.cookie_store {
+      Some(path) => {
+        let jar = CookieJar::load(path)?;
+        client_builder = client_builder.cookie_provider(jar.store.clone());
+        Some(jar)
+      }
+      None => None,
+    };
+
     let client = client_builder.build()?;
-    Ok(Client(client))
+    Ok(Client(client, cookie_jar, self.retry))
   }
 }
 
+/// A cookie store persisted on a JSON file.
+#[cfg(feature = "reqwest-client")]
+#[derive(Clone)]
+struct CookieJar {
+  store: Arc<reqwest_cookie_store::CookieStoreMutex>,
+  path: PathBuf,
+}
+
+#[cfg(feature = "reqwest-client")]
+impl CookieJar {
+  fn load(path: PathBuf) -> crate::api::Result<Self> {
+    let store = if path.exists() {
+      let file = std::io::BufReader::new(std::fs::File::open(&path)?);
+      reqwest_cookie_store::CookieStore::load_json(file)
+        .map_err(|e| crate::api::Error::CookieStore(e.to_string()))?
+    } else {
+      Default::default()
+    };
+    Ok(Self {
+      store: Arc::new(reqwest_cookie_store::CookieStoreMutex::new(store)),
+      path,
+    })
+  }
+
+  fn save(&self) -> crate::api::Result<()> {
+    let mut data = Vec::new();
+    self
+      .store
+      .lock()
+      .unwrap()
+      .save_json(&mut data)
+      .map_err(|e| crate::api::Error::CookieStore(e.to_string()))?;
+    if let Some(parent) = self.path.parent() {
+      std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&self.path, data)?;
+    Ok(())
+  }
+}
+
+#[cfg(feature = "reqwest-client")]
+fn is_connect_error(error: &crate::api::Error) -> bool {
+  matches!(error, crate::api::Error::Network(e) if e.is_connect() || e.is_timeout())
+}
+
+#[cfg(not(feature = "reqwest-client"))]
+fn is_connect_error(error: &crate::api::Error) -> bool {
+  matches!(error, crate::api::Error::Network(e) if matches!(e.kind(), attohttpc::ErrorKind::Io(_)))
+}
+
 /// The HTTP client.
 #[cfg(feature = "reqwest-client")]
 #[derive(Clone)]
-pub struct Client(reqwest::Client);
+pub struct Client(reqwest::Client, Option<CookieJar>, Option<RetryPolicy>);
 
 /// The HTTP client.
 #[cfg(not(feature = "reqwest-client"))]
@@ -73,11 +335,12 @@ pub struct Client(ClientBuilder);
 
 #[cfg(not(feature = "reqwest-client"))]
 impl Client {
-  /// Executes an HTTP request
-  ///
-  /// The response will be transformed to String,
-  /// If reading the response as binary, the byte array will be serialized using serde_json.
-  pub async fn send(&self, request: HttpRequestBuilder) -> crate::api::Result<Response> {
+  fn retry_policy(&self) -> Option<&RetryPolicy> {
+    self.0.retry.as_ref()
+  }
+
+  // Sends the request once, the error status is checked by `send`
+  async fn send_once(&self, request: HttpRequestBuilder) -> crate::api::Result<Response> {
     let method = Method::from_bytes(request.method.to_uppercase().as_bytes())?;
 
     let mut request_builder = attohttpc::RequestBuilder::try_new(method, &request.url)?;
@@ -97,43 +360,142 @@ impl Client {
       request_builder = request_builder.timeout(Duration::from_secs(timeout));
     }
 
+    if let Some(proxy) = &self.0.proxy {
+      let proxy = url::Url::parse(proxy)?;
+      request_builder = request_builder.proxy_settings(
+        attohttpc::ProxySettings::builder()
+          .http_proxy(proxy.clone())
+          .https_proxy(proxy)
+          .build(),
+      );
+    }
+
     let response = if let Some(body) = request.body {
       match body {
         Body::Bytes(data) => request_builder.body(attohttpc::body::Bytes(data)).send()?,
         Body::Text(text) => request_builder.body(attohttpc::body::Bytes(text)).send()?,
         Body::Json(json) => request_builder.json(&json)?.send()?,
         Body::Form(form_body) => {
-          let mut form = Vec::new();
+          // attohttpc multipart parts borrow byte slices, so the files are read in memory before building the form
+          let mut parts = Vec::new();
           for (name, part) in form_body.0 {
-            match part {
-              FormPart::Bytes(bytes) => form.push((name, serde_json::to_string(&bytes)?)),
-              FormPart::File(file_path) => form.push((name, serde_json::to_string(&file_path)?)),
-              FormPart::Text(text) => form.push((name, text)),
-            }
+            let part = match part {
+              FormPart::File {
+                file,
+                mime,
+                file_name,
+              } => {
+                let data = std::fs::read(&file)?;
+                MultipartPart::Binary {
+                  mime: mime.unwrap_or_else(|| guess_mime(&file)),
+                  file_name: file_name.or_else(|| file_name_of(&file)),
+                  data,
+                }
+              }
+              FormPart::Bytes(data) => MultipartPart::Binary {
+                data,
+                file_name: None,
+                mime: DEFAULT_MIME.into(),
+              },
+              FormPart::Text(text) => MultipartPart::Text(text),
+            };
+            parts.push((name, part));
+          }
+
+          let mut form = attohttpc::MultipartBuilder::new();
+          for (name, part) in &parts {
+            form = match part {
+              MultipartPart::Text(text) => form.with_text(name, text),
+              MultipartPart::Binary {
+                data,
+                file_name,
+                mime,
+              } => {
+                let mut file = attohttpc::MultipartFile::new(name, data).with_type(mime)?;
+                if let Some(file_name) = file_name {
+                  file = file.with_filename(file_name);
+                }
+                form.with_file(file)
+              }
+            };
           }
-          request_builder.form(&form)?.send()?
+          request_builder.body(form.build()?).send()?
         }
       }
     } else {
       request_builder.send()?
     };
 
-    let response = response.error_for_status()?;
     Ok(Response(
       request.response_type.unwrap_or(ResponseType::Json),
       response,
-      request.url,
     ))
   }
 }
 
-#[cfg(feature = "reqwest-client")]
 impl Client {
-  /// Executes an HTTP request
+  /// Executes an HTTP request, retrying it according to the retry policy of the request or the client.
   ///
   /// The response will be transformed to String,
   /// If reading the response as binary, the byte array will be serialized using serde_json.
-  pub async fn send(&self, request: HttpRequestBuilder) -> crate::api::Result<Response> {
+  pub async fn send(&self, mut request: HttpRequestBuilder) -> crate::api::Result<Response> {
+    let error_for_status = request.error_for_status.unwrap_or(true);
+    let policy = request
+      .retry
+      .take()
+      .or_else(|| self.retry_policy().cloned());
+
+    let response = match policy {
+      Some(policy) if policy.max_attempts > 1 && policy.allows_method(&request.method) => {
+        let mut attempt = 1;
+        loop {
+          if attempt >= policy.max_attempts {
+            break self.send_once(request).await?;
+          }
+          match self.send_once(request.clone()).await {
+            Ok(response) if !policy.status_codes.contains(&response.status()) => break response,
+            Err(e) if !(policy.connect_errors && is_connect_error(&e)) => return Err(e),
+            _ => {}
+          }
+          tokio::time::sleep(policy.backoff(attempt)).await;
+          attempt += 1;
+        }
+      }
+      _ => self.send_once(request).await?,
+    };
+
+    if error_for_status {
+      response.error_for_status()
+    } else {
+      Ok(response)
+    }
+  }
+
+  /// Executes an HTTP request and streams the response body to a file.
+  ///
+  /// See [`Response::download`].
+  pub async fn download<F: FnMut(DownloadProgress) + Send>(
+    &self,
+    request: HttpRequestBuilder,
+    download: DownloadBuilder,
+    on_progress: F,
+  ) -> crate::api::Result<()> {
+    if download.cancel.is_cancelled() {
+      return Err(crate::api::Error::DownloadCancelled);
+    }
+    let response = self.send(request).await?;
+    response.download(download, on_progress).await
+  }
+}
+
+#[cfg(feature = "reqwest-client")]
+impl Client {
+  fn retry_policy(&self) -> Option<&RetryPolicy> {
+    self.2.as_ref()
+  }
+
+  // Sends the request once, the error status is checked by `send`
+  async fn send_once(&self, request: HttpRequestBuilder) -> crate::api::Result<Response> {
     let method = Method::from_bytes(request.method.to_uppercase().as_bytes())?;
 
     let mut request_builder = self.0.request(method, &request.url);
@@ -169,22 +531,44 @@ impl Client {
         }
         Body::Json(json) => request_builder.json(&json).send().await?,
         Body::Form(form_body) => {
-          let mut form = Vec::new();
+          let mut form = reqwest::multipart::Form::new();
           for (name, part) in form_body.0 {
-            match part {
-              FormPart::Bytes(bytes) => form.push((name, serde_json::to_string(&bytes)?)),
-              FormPart::File(file_path) => form.push((name, serde_json::to_string(&file_path)?)),
-              FormPart::Text(text) => form.push((name, text)),
-            }
+            let part = match part {
+              FormPart::File {
+                file,
+                mime,
+                file_name,
+              } => {
+                // stream the file contents instead of reading it in memory
+                let handle = tokio::fs::File::open(&file).await?;
+                let length = handle.metadata().await?.len();
+                let mut part =
+                  reqwest::multipart::Part::stream_with_length(reqwest::Body::from(handle), length)
+                    .mime_str(&mime.unwrap_or_else(|| guess_mime(&file)))?;
+                if let Some(file_name) = file_name.or_else(|| file_name_of(&file)) {
+                  part = part.file_name(file_name);
+                }
+                part
+              }
+              FormPart::Bytes(data) => {
+                reqwest::multipart::Part::bytes(data).mime_str(DEFAULT_MIME)?
+              }
+              FormPart::Text(text) => reqwest::multipart::Part::text(text),
+            };
+            form = form.part(name, part);
           }
-          request_builder.form(&form).send().await?
+          request_builder.multipart(form).send().await?
         }
       }
     } else {
       request_builder.send().await?
     };
 
-    let response = response.error_for_status()?;
+    // persist the cookies set by the response
+    if let Some(cookie_jar) = &self.1 {
+      cookie_jar.save()?;
+    }
+
     Ok(Response(
       request.response_type.unwrap_or(ResponseType::Json),
       response,
@@ -206,20 +590,57 @@ pub enum ResponseType {
 }
 
 /// FormBody data types.
-#[derive(Deserialize)]
+#[derive(Clone, Deserialize)]
 #[serde(untagged)]
 #[non_exhaustive]
 pub enum FormPart {
-  /// A file path value.
-  File(PathBuf),
+  /// A file, its contents are uploaded.
+  ///
+  /// The `reqwest-client` feature streams the file. The default client reads it in memory,
+  /// since `attohttpc` multipart parts only borrow byte slices, so large uploads should use `reqwest-client`.
+  #[serde(rename_all = "camelCase")]
+  File {
+    /// The file path.
+    file: PathBuf,
+    /// The MIME type of the file. Guessed from the file extension by default.
+    mime: Option<String>,
+    /// The file name sent to the server. Defaults to the name of the file.
+    file_name: Option<String>,
+  },
   /// A string value.
   Text(String),
-  /// A byte array value.
+  /// A byte array value, sent as binary.
   Bytes(Vec<u8>),
 }
 
+const DEFAULT_MIME: &str = "application/octet-stream";
+
+// A form part with its file read, attohttpc needs the data to outlive the form
+#[cfg(not(feature = "reqwest-client"))]
+enum MultipartPart {
+  Text(String),
+  Binary {
+    data: Vec<u8>,
+    file_name: Option<String>,
+    mime: String,
+  },
+}
+
+fn guess_mime(path: &Path) -> String {
+  mime_guess::from_path(path)
+    .first_raw()
+    .unwrap_or(DEFAULT_MIME)
+    .to_string()
+}
+
+fn file_name_of(path: &Path) -> Option<String> {
+  path
+    .file_name()
+    .map(|name| name.to_string_lossy().into_owned())
+}
+
 /// Form body definition.
-#[derive(Deserialize)]
+#[derive(Clone, Deserialize)]
 pub struct FormBody(HashMap<String, FormPart>);
 
 impl FormBody {
@@ -227,14 +648,22 @@ impl FormBody {
   pub fn new(data: HashMap<String, FormPart>) -> Self {
     Self(data)
   }
+
+  /// The paths of the files uploaded with the form.
+  pub fn files(&self) -> impl Iterator<Item = &Path> + '_ {
+    self.0.values().filter_map(|part| match part {
+      FormPart::File { file, .. } => Some(file.as_path()),
+      _ => None,
+    })
+  }
 }
 
 /// A body for the request.
-#[derive(Deserialize)]
+#[derive(Clone, Deserialize)]
 #[serde(tag = "type", content = "payload")]
 #[non_exhaustive]
 pub enum Body {
-  /// A multipart formdata body.
+  /// A `multipart/form-data` body.
   Form(FormBody),
   /// A JSON body.
   Json(Value),
@@ -264,7 +693,7 @@ pub enum Body {
 ///   }
 /// }
 /// ```
-#[derive(Deserialize)]
+#[derive(Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct HttpRequestBuilder {
   /// The request method (GET, POST, PUT, DELETE, PATCH, HEAD, OPTIONS, CONNECT or TRACE)
@@ -281,6 +710,11 @@ pub struct HttpRequestBuilder {
   pub timeout: Option<u64>,
   /// The response type (defaults to Json)
   pub response_type: Option<ResponseType>,
+  /// Whether 4xx and 5xx responses are returned as errors (defaults to true).
+  /// Disable it to read the body of error responses.
+  pub error_for_status: Option<bool>,
+  /// The retry policy, overriding the policy of the client.
+  pub retry: Option<RetryPolicy>,
 }
 
 impl HttpRequestBuilder {
@@ -294,6 +728,8 @@ impl HttpRequestBuilder {
       body: None,
       timeout: None,
       response_type: None,
+      error_for_status: None,
+      retry: None,
     }
   }
 
@@ -326,6 +762,18 @@ impl HttpRequestBuilder {
     self.response_type = Some(response_type);
     self
   }
+
+  /// Sets whether 4xx and 5xx responses are returned as errors.
+  pub fn error_for_status(mut self, error_for_status: bool) -> Self {
+    self.error_for_status = Some(error_for_status);
+    self
+  }
+
+  /// Sets the retry policy, overriding the policy of the client.
+  pub fn retry(mut self, retry: RetryPolicy) -> Self {
+    self.retry = Some(retry);
+    self
+  }
 }
 
 /// The HTTP response.
@@ -333,60 +781,286 @@ impl HttpRequestBuilder {
 pub struct Response(ResponseType, reqwest::Response);
 /// The HTTP response.
 #[cfg(not(feature = "reqwest-client"))]
-pub struct Response(ResponseType, attohttpc::Response, String);
+pub struct Response(ResponseType, attohttpc::Response);
 
 impl Response {
+  // Turns 4xx and 5xx responses into errors
+  fn error_for_status(self) -> crate::api::Result<Self> {
+    Ok(Self(self.0, self.1.error_for_status()?))
+  }
+
+  /// The response status code.
+  pub fn status(&self) -> u16 {
+    self.1.status().as_u16()
+  }
+
+  /// The final URL of the response, after following the redirects.
+  pub fn url(&self) -> String {
+    self.1.url().to_string()
+  }
+
+  /// The response headers. The values of repeated headers are joined with a comma.
+  pub fn headers(&self) -> crate::api::Result<HashMap<String, String>> {
+    let mut headers: HashMap<String, String> = HashMap::new();
+    for (name, value) in self.1.headers() {
+      let value = value.to_str()?;
+      headers
+        .entry(name.as_str().to_string())
+        .and_modify(|values| {
+          values.push_str(", ");
+          values.push_str(value);
+        })
+        .or_insert_with(|| value.to_string());
+    }
+    Ok(headers)
+  }
+
   /// Reads the response as raw bytes.
   pub async fn bytes(self) -> crate::api::Result<RawResponse> {
-    let status = self.1.status().as_u16();
+    let status = self.status();
+    let url = self.url();
+    let headers = self.headers()?;
     #[cfg(feature = "reqwest-client")]
     let data = self.1.bytes().await?.to_vec();
     #[cfg(not(feature = "reqwest-client"))]
     let data = self.1.bytes()?;
-    Ok(RawResponse { status, data })
+    Ok(RawResponse {
+      url,
+      status,
+      headers,
+      data,
+    })
   }
 
   /// Reads the response and returns its info.
+  ///
+  /// When reading JSON, an error response whose body is not JSON is returned as text,
+  /// so the error payload is available when `error_for_status` is disabled.
   pub async fn read(self) -> crate::api::Result<ResponseData> {
-    #[cfg(feature = "reqwest-client")]
-    let url = self.1.url().to_string();
-    #[cfg(not(feature = "reqwest-client"))]
-    let url = self.2;
+    let response_type = self.0.clone();
+    let raw = self.bytes().await?;
+    let success = (200..300).contains(&raw.status);
 
-    let mut headers = HashMap::new();
-    for (name, value) in self.1.headers() {
-      headers.insert(name.as_str().to_string(), value.to_str()?.to_string());
+    let data = match response_type {
+      ResponseType::Json if raw.data.is_empty() => Value::Null,
+      ResponseType::Json => match serde_json::from_slice(&raw.data) {
+        Ok(json) => json,
+        Err(_) if !success => Value::String(String::from_utf8_lossy(&raw.data).into_owned()),
+        Err(e) => return Err(e.into()),
+      },
+      ResponseType::Text => Value::String(String::from_utf8_lossy(&raw.data).into_owned()),
+      ResponseType::Binary => Value::String(serde_json::to_string(&raw.data)?),
+    };
+
+    Ok(ResponseData {
+      url: raw.url,
+      status: raw.status,
+      headers: raw.headers,
+      data,
+    })
+  }
+}
+
+impl Response {
+  /// Streams the response body to a file, calling `on_progress` periodically.
+  ///
+  /// The body is written next to the destination with a `.download` suffix
+  /// and only moved to the destination once complete and verified.
+  pub async fn download<F: FnMut(DownloadProgress) + Send>(
+    self,
+    download: DownloadBuilder,
+    mut on_progress: F,
+  ) -> crate::api::Result<()> {
+    let mut partial_path = download.path.clone().into_os_string();
+    partial_path.push(".download");
+    let partial_path = PathBuf::from(partial_path);
+
+    match self
+      .write_body(&download, &partial_path, &mut on_progress)
+      .await
+    {
+      Ok(()) => {
+        std::fs::rename(&partial_path, &download.path)?;
+        Ok(())
+      }
+      Err(e) => {
+        let _ = std::fs::remove_file(&partial_path);
+        Err(e)
+      }
     }
-    let status = self.1.status().as_u16();
+  }
 
-    #[cfg(feature = "reqwest-client")]
-    let data = match self.0 {
-      ResponseType::Json => self.1.json().await?,
-      ResponseType::Text => Value::String(self.1.text().await?),
-      ResponseType::Binary => Value::String(serde_json::to_string(&self.1.bytes().await?)?),
+  async fn write_body<F: FnMut(DownloadProgress) + Send>(
+    self,
+    download: &DownloadBuilder,
+    path: &Path,
+    on_progress: &mut F,
+  ) -> crate::api::Result<()> {
+    use sha2::{Digest, Sha256};
+    use std::io::Write;
+
+    let total = self
+      .1
+      .headers()
+      .get(http::header::CONTENT_LENGTH)
+      .and_then(|length| length.to_str().ok())
+      .and_then(|length| length.parse().ok());
+
+    let mut file = std::fs::File::create(path)?;
+    let mut hasher = Sha256::new();
+    let mut progress = DownloadProgress {
+      downloaded: 0,
+      total,
     };
+    let mut last_progress = Instant::now();
+    on_progress(progress);
 
-    #[cfg(not(feature = "reqwest-client"))]
-    let data = match self.0 {
-      ResponseType::Json => self.1.json()?,
-      ResponseType::Text => Value::String(self.1.text()?),
-      ResponseType::Binary => Value::String(serde_json::to_string(&self.1.bytes()?)?),
+    let mut write_chunk = |chunk: &[u8]| -> crate::api::Result<()> {
+      if download.cancel.is_cancelled() {
+        return Err(crate::api::Error::DownloadCancelled);
+      }
+      file.write_all(chunk)?;
+      hasher.update(chunk);
+      progress.downloaded += chunk.len() as u64;
+      if last_progress.elapsed() >= DOWNLOAD_PROGRESS_INTERVAL {
+        last_progress = Instant::now();
+        on_progress(progress);
+      }
+      Ok(())
     };
 
-    Ok(ResponseData {
-      url,
-      status,
-      headers,
-      data,
-    })
+    #[cfg(feature = "reqwest-client")]
+    {
+      let mut response = self.1;
+      while let Some(chunk) = response.chunk().await? {
+        write_chunk(&chunk)?;
+      }
+    }
+
+    #[cfg(not(feature = "reqwest-client"))]
+    {
+      use std::io::Read;
+      let (_, _, mut reader) = self.1.split();
+      let mut buffer = vec![0; 64 * 1024];
+      loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+          break;
+        }
+        write_chunk(&buffer[..read])?;
+      }
+    }
+
+    file.sync_all()?;
+    on_progress(progress);
+
+    if let Some(expected) = &download.sha256 {
+      let actual = format!("{:x}", hasher.finalize());
+      if !actual.eq_ignore_ascii_case(expected) {
+        return Err(crate::api::Error::ChecksumMismatch {
+          expected: expected.clone(),
+          actual,
+        });
+      }
+    }
+
+    Ok(())
+  }
+}
+
+const DOWNLOAD_PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The progress of a download.
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct DownloadProgress {
+  /// Number of bytes written to the file.
+  pub downloaded: u64,
+  /// The size of the response body, if known.
+  pub total: Option<u64>,
+}
+
+/// A handle to cancel a download.
+#[derive(Debug, Clone, Default)]
+pub struct DownloadCancel(Arc<AtomicBool>);
+
+impl DownloadCancel {
+  /// Creates a new cancellation handle.
+  pub fn new() -> Self {
+    Default::default()
+  }
+
+  /// Cancels the download, the partially written file is removed.
+  pub fn cancel(&self) {
+    self.0.store(true, Ordering::Relaxed);
+  }
+
+  /// Whether the download was cancelled.
+  pub fn is_cancelled(&self) -> bool {
+    self.0.load(Ordering::Relaxed)
+  }
+}
+
+/// The builder of a download, writing a response body to a file.
+///
+/// # Examples
+/// ```no_run
+/// use tauri::api::http::{ClientBuilder, DownloadBuilder, HttpRequestBuilder};
+/// async fn run() {
+///   let client = ClientBuilder::new().build().unwrap();
+///   let request = HttpRequestBuilder::new("GET", "http://example.com/app.tar.gz");
+///   let download = DownloadBuilder::new("/tmp/app.tar.gz")
+///     .sha256("4fa0f3b5c1d1c6e0e8a8f5f3a2a4d9b0c6c4b2b7e7f5c0b0c1c8f2e5a3d7e9f1");
+///   let result = client
+///     .download(request, download, |progress| {
+///       println!("{} of {:?} bytes", progress.downloaded, progress.total)
+///     })
+///     .await;
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct DownloadBuilder {
+  /// The destination of the response body.
+  pub path: PathBuf,
+  /// The expected SHA-256 checksum (hex encoded) of the file.
+  pub sha256: Option<String>,
+  /// The handle to cancel the download.
+  pub cancel: DownloadCancel,
+}
+
+impl DownloadBuilder {
+  /// Initializes a new download to the given path.
+  pub fn new(path: impl Into<PathBuf>) -> Self {
+    Self {
+      path: path.into(),
+      sha256: None,
+      cancel: Default::default(),
+    }
+  }
+
+  /// Sets the expected SHA-256 checksum (hex encoded) of the file, verified once the download is complete.
+  pub fn sha256(mut self, checksum: impl Into<String>) -> Self {
+    self.sha256 = Some(checksum.into());
+    self
+  }
+
+  /// Sets the handle used to cancel the download.
+  pub fn cancel_handle(mut self, cancel: DownloadCancel) -> Self {
+    self.cancel = cancel;
+    self
   }
 }
 
 /// A response with raw bytes.
 #[non_exhaustive]
 pub struct RawResponse {
+  /// Response URL. Useful if it followed redirects.
+  pub url: String,
   /// Response status code.
   pub status: u16,
+  /// Response headers.
+  pub headers: HashMap<String, String>,
   /// Response bytes.
   pub data: Vec<u8>,
 }
@@ -405,3 +1079,220 @@ pub struct ResponseData {
   /// Response data.
   pub data: Value,
 }
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn form_parts() {
+    let form: FormBody = serde_json::from_str(
+      r#"{
+        "name": "tauri",
+        "data": [1, 2, 3],
+        "avatar": { "file": "./avatar.png", "fileName": "me.png" }
+      }"#,
+    )
+    .unwrap();
+    assert!(matches!(form.0.get("name"), Some(FormPart::Text(text)) if text == "tauri"));
+    assert!(matches!(form.0.get("data"), Some(FormPart::Bytes(data)) if data == &[1, 2, 3]));
+    assert!(matches!(
+      form.0.get("avatar"),
+      Some(FormPart::File { file, mime: None, file_name: Some(file_name) })
+        if file == Path::new("./avatar.png") && file_name == "me.png"
+    ));
+  }
+
+  #[test]
+  fn error_status_response() {
+    let _m = mockito::mock("GET", "/not-found")
+      .with_status(404)
+      .with_header("x-request-id", "42")
+      .with_body("resource not found")
+      .create();
+    let client = ClientBuilder::new().build().unwrap();
+    let url = format!("{}/not-found", mockito::server_url());
+
+    let result = tokio_test::block_on(client.send(HttpRequestBuilder::new("GET", &url)));
+    assert!(result.is_err());
+
+    let request = HttpRequestBuilder::new("GET", &url).error_for_status(false);
+    let response = tokio_test::block_on(client.send(request)).unwrap();
+    let data = tokio_test::block_on(response.read()).unwrap();
+    assert_eq!(data.status, 404);
+    assert_eq!(data.url, url);
+    assert_eq!(data.headers.get("x-request-id").unwrap(), "42");
+    assert_eq!(data.data, Value::String("resource not found".into()));
+  }
+
+  #[test]
+  fn raw_response() {
+    let _m = mockito::mock("GET", "/raw")
+      .with_status(200)
+      .with_header("content-type", "application/octet-stream")
+      .with_body(vec![1u8, 2, 3])
+      .create();
+    let client = ClientBuilder::new().build().unwrap();
+    let request = HttpRequestBuilder::new("GET", format!("{}/raw", mockito::server_url()));
+    let response = tokio_test::block_on(client.send(request)).unwrap();
+    let raw = tokio_test::block_on(response.bytes()).unwrap();
+    assert_eq!(raw.status, 200);
+    assert_eq!(
+      raw.headers.get("content-type").unwrap(),
+      "application/octet-stream"
+    );
+    assert_eq!(raw.data, vec![1, 2, 3]);
+  }
+
+  fn download(path: &str, sha256: Option<&str>) -> (tempfile::TempDir, crate::api::Result<()>) {
+    let _m = mockito::mock("GET", path)
+      .with_status(200)
+      .with_body("downloaded content")
+      .create();
+    let dir = tempfile::tempdir().unwrap();
+    let mut download = DownloadBuilder::new(dir.path().join("file.txt"));
+    if let Some(sha256) = sha256 {
+      download = download.sha256(sha256);
+    }
+    let client = ClientBuilder::new().build().unwrap();
+    let request = HttpRequestBuilder::new("GET", format!("{}{}", mockito::server_url(), path));
+    let mut last_progress = None;
+    let result = tokio_test::block_on(
+      client.download(request, download, |progress| last_progress = Some(progress)),
+    );
+    if result.is_ok() {
+      assert_eq!(last_progress.unwrap().downloaded, 18);
+    }
+    (dir, result)
+  }
+
+  #[test]
+  fn download_to_file() {
+    let (dir, result) = download(
+      "/download",
+      Some("F51BD38B46D76BBB6FA1B2236EDEA7997F6487777CB144497800A8D87F7DC1B8"),
+    );
+    assert!(result.is_ok());
+    assert_eq!(
+      std::fs::read_to_string(dir.path().join("file.txt")).unwrap(),
+      "downloaded content"
+    );
+    assert!(!dir.path().join("file.txt.download").exists());
+  }
+
+  #[test]
+  fn download_checksum_mismatch() {
+    let (dir, result) = download("/download-mismatch", Some("bad checksum"));
+    // the partially written file is removed
+    assert!(matches!(
+      result,
+      Err(crate::api::Error::ChecksumMismatch { .. })
+    ));
+    assert!(!dir.path().join("file.txt").exists());
+    assert!(!dir.path().join("file.txt.download").exists());
+  }
+
+  #[test]
+  fn cancelled_download() {
+    let _m = mockito::mock("GET", "/cancelled")
+      .with_status(200)
+      .with_body("downloaded content")
+      .create();
+    let dir = tempfile::tempdir().unwrap();
+    let cancel = DownloadCancel::new();
+    cancel.cancel();
+    let download = DownloadBuilder::new(dir.path().join("file.txt")).cancel_handle(cancel);
+    let client = ClientBuilder::new().build().unwrap();
+    let request = HttpRequestBuilder::new("GET", format!("{}/cancelled", mockito::server_url()));
+    let result = tokio_test::block_on(client.download(request, download, |_| {}));
+    assert!(matches!(result, Err(crate::api::Error::DownloadCancelled)));
+    assert!(!dir.path().join("file.txt").exists());
+  }
+
+  #[test]
+  fn guess_file_mime() {
+    assert_eq!(guess_mime(Path::new("avatar.png")), "image/png");
+    assert_eq!(guess_mime(Path::new("data")), DEFAULT_MIME);
+  }
+
+  #[cfg(not(feature = "reqwest-client"))]
+  #[test]
+  fn unsupported_client_options() {
+    assert!(ClientBuilder::new()
+      .proxy("http://localhost:8080")
+      .build()
+      .is_ok());
+    assert!(matches!(
+      ClientBuilder::new()
+        .proxy("socks5://localhost:1080")
+        .build(),
+      Err(crate::api::Error::HttpClientOptionUnsupported(_))
+    ));
+    assert!(matches!(
+      ClientBuilder::new().root_certificate("ca.pem").build(),
+      Err(crate::api::Error::HttpClientOptionUnsupported(_))
+    ));
+    assert!(matches!(
+      ClientBuilder::new().cookie_store("cookies.json").build(),
+      Err(crate::api::Error::HttpClientOptionUnsupported(_))
+    ));
+  }
+
+  #[test]
+  fn client_options() {
+    let builder: ClientBuilder = serde_json::from_str(
+      r#"{
+        "proxy": "http://localhost:8080",
+        "rootCertificates": ["ca.pem"],
+        "clientCertificate": { "path": "client.p12" }
+      }"#,
+    )
+    .unwrap();
+    assert_eq!(builder.root_certificates, vec![PathBuf::from("ca.pem")]);
+    let certificate = builder.client_certificate.unwrap();
+    assert_eq!(certificate.path, PathBuf::from("client.p12"));
+    assert!(certificate.password.is_empty());
+    assert!(builder.cookie_store.is_none());
+  }
+
+  #[test]
+  fn retry_status_codes() {
+    let get = mockito::mock("GET", "/unavailable")
+      .with_status(503)
+      .expect(3)
+      .create();
+    let post = mockito::mock("POST", "/unavailable")
+      .with_status(503)
+      .expect(1)
+      .create();
+    let client = ClientBuilder::new()
+      .retry(RetryPolicy::new().initial_delay(1))
+      .build()
+      .unwrap();
+    let url = format!("{}/unavailable", mockito::server_url());
+
+    let request = HttpRequestBuilder::new("GET", &url).error_for_status(false);
+    let response = tokio_test::block_on(client.send(request)).unwrap();
+    assert_eq!(response.status(), 503);
+    get.assert();
+
+    // non idempotent requests are only sent once
+    let result = tokio_test::block_on(client.send(HttpRequestBuilder::new("POST", &url)));
+    assert!(result.is_err());
+    post.assert();
+  }
+
+  #[test]
+  fn retry_backoff() {
+    let policy = RetryPolicy::new().initial_delay(100).max_delay(300);
+    for _ in 0..10 {
+      let first = policy.backoff(1);
+      assert!(first >= Duration::from_millis(50) && first <= Duration::from_millis(100));
+      let capped = policy.backoff(5);
+      assert!(capped >= Duration::from_millis(150) && capped <= Duration::from_millis(300));
+    }
+    assert!(policy.allows_method("get"));
+    assert!(!policy.allows_method("POST"));
+    assert!(policy.non_idempotent(true).allows_method("POST"));
+  }
+}
@@ -12,6 +12,9 @@ pub mod dialog;
 pub mod dir;
 /// The File API module contains helpers to perform file operations.
 pub mod file;
+/// The file system watcher API.
+#[cfg(fs_watch)]
+pub mod fs;
 /// The HTTP request API.
 pub mod http;
 /// The file system path operations API.
@@ -20,9 +23,14 @@ pub mod path;
 pub mod process;
 /// The RPC module includes utilities to send messages to the JS layer of the webview.
 pub mod rpc;
+/// The filesystem and URL access scopes.
+pub mod scope;
 /// The shell api.
 #[cfg(shell_open)]
 pub mod shell;
+/// The trash API, to move files to the trash, list and restore them.
+#[cfg(fs_trash)]
+pub mod trash;
 /// The semver API.
 pub mod version;
 
@@ -44,6 +52,10 @@ pub mod shortcuts;
 #[cfg(notification_all)]
 pub mod notification;
 
+/// The WebSocket client API.
+#[cfg(websocket_all)]
+pub mod websocket;
+
 pub use tauri_utils::*;
 
 mod error;
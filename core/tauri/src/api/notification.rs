@@ -2,9 +2,63 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-License-Identifier: MIT
 
+//! Actions, replacing or closing notifications by ID, urgency, timeouts and events
+//! are only supported on Linux, where the notifications follow the
+//! [Desktop Notifications Specification](https://specifications.freedesktop.org/notification-spec/latest/).
+//! On Windows and macOS, [`Notification::show`] fails if any of them is set.
+
+use serde::Serialize;
+
 #[cfg(windows)]
 use std::path::MAIN_SEPARATOR;
 
+// the action invoked when the notification itself is clicked
+#[cfg(all(unix, not(target_os = "macos")))]
+const DEFAULT_ACTION: &str = "default";
+
+/// An action button of a notification.
+#[derive(Debug, Clone)]
+pub struct NotificationAction {
+  /// The identifier of the action, reported by [`NotificationEvent::Action`].
+  pub id: String,
+  /// The label of the action button.
+  pub title: String,
+}
+
+/// The urgency level of a notification.
+#[derive(Debug, Clone, Copy)]
+pub enum Urgency {
+  /// Low urgency.
+  Low,
+  /// Normal urgency.
+  Normal,
+  /// Critical urgency, the notification usually doesn't expire.
+  Critical,
+}
+
+/// The time after which a notification expires.
+#[derive(Debug, Clone, Copy)]
+pub enum Timeout {
+  /// The default timeout of the notification server.
+  Default,
+  /// The notification never expires.
+  Never,
+  /// The notification expires after the given number of milliseconds.
+  Milliseconds(u32),
+}
+
+/// An event of a notification.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", content = "payload", rename_all = "camelCase")]
+pub enum NotificationEvent {
+  /// The notification was clicked.
+  Clicked,
+  /// An action button was clicked, with the identifier of the action.
+  Action(String),
+  /// The notification was closed or expired.
+  Closed,
+}
+
 /// The Notification definition.
 /// Allows you to construct a Notification data and send it.
 ///
@@ -28,6 +82,18 @@ pub struct Notification {
   icon: Option<String>,
   /// The notification identifier
   identifier: String,
+  /// The ID of the notification to replace.
+  id: Option<u32>,
+  /// The action buttons.
+  actions: Vec<NotificationAction>,
+  /// The urgency level.
+  urgency: Option<Urgency>,
+  /// The expiration timeout.
+  timeout: Option<Timeout>,
+  /// The sound to play.
+  sound: Option<String>,
+  /// The event handler.
+  on_event: Option<Box<dyn FnOnce(NotificationEvent) + Send>>,
 }
 
 impl Notification {
@@ -57,8 +123,60 @@ impl Notification {
     self
   }
 
+  /// Replaces the notification with the given ID, see [`NotificationHandle::id`].
+  ///
+  /// Only supported on Linux.
+  pub fn id(mut self, id: u32) -> Self {
+    self.id = Some(id);
+    self
+  }
+
+  /// Adds an action button.
+  ///
+  /// Only supported on Linux.
+  pub fn action(mut self, id: impl Into<String>, title: impl Into<String>) -> Self {
+    self.actions.push(NotificationAction {
+      id: id.into(),
+      title: title.into(),
+    });
+    self
+  }
+
+  /// Sets the urgency level.
+  ///
+  /// Only supported on Linux.
+  pub fn urgency(mut self, urgency: Urgency) -> Self {
+    self.urgency = Some(urgency);
+    self
+  }
+
+  /// Sets the expiration timeout.
+  ///
+  /// Only supported on Linux.
+  pub fn timeout(mut self, timeout: Timeout) -> Self {
+    self.timeout = Some(timeout);
+    self
+  }
+
+  /// Sets the sound to play, e.g. `message-new-instant` on Linux or `Ping` on macOS.
+  pub fn sound(mut self, sound: impl Into<String>) -> Self {
+    self.sound = Some(sound.into());
+    self
+  }
+
+  /// Sets the handler of the first event of the notification: a click, an action or its closing.
+  ///
+  /// Only supported on Linux. A thread waits for the event until the notification is closed,
+  /// so notifications that never expire keep their thread until the user dismisses them.
+  pub fn on_event<F: FnOnce(NotificationEvent) + Send + 'static>(mut self, f: F) -> Self {
+    self.on_event = Some(Box::new(f));
+    self
+  }
+
   /// Shows the notification.
-  pub fn show(self) -> crate::api::Result<()> {
+  ///
+  /// Fails on Windows and macOS if an ID, action, urgency, timeout or event handler is set.
+  pub fn show(self) -> crate::api::Result<NotificationHandle> {
     let mut notification = notify_rust::Notification::new();
     if let Some(body) = self.body {
       notification.body(&body);
@@ -69,6 +187,9 @@ impl Notification {
     if let Some(icon) = self.icon {
       notification.icon(&icon);
     }
+    if let Some(sound) = self.sound {
+      notification.sound_name(&sound);
+    }
     #[cfg(windows)]
     {
       let exe = std::env::current_exe()?;
@@ -81,7 +202,285 @@ impl Notification {
         notification.app_id(&self.identifier);
       }
     }
-    notification.show()?;
-    Ok(())
+    #[cfg(all(unix, not(target_os = "macos")))]
+    {
+      if let Some(id) = self.id {
+        notification.id(id);
+      }
+      if let Some(urgency) = self.urgency {
+        notification.urgency(match urgency {
+          Urgency::Low => notify_rust::Urgency::Low,
+          Urgency::Normal => notify_rust::Urgency::Normal,
+          Urgency::Critical => notify_rust::Urgency::Critical,
+        });
+      }
+      if let Some(timeout) = self.timeout {
+        notification.timeout(match timeout {
+          Timeout::Default => notify_rust::Timeout::Default,
+          Timeout::Never => notify_rust::Timeout::Never,
+          Timeout::Milliseconds(ms) => notify_rust::Timeout::Milliseconds(ms),
+        });
+      }
+      if self.on_event.is_some() {
+        notification.action(DEFAULT_ACTION, DEFAULT_ACTION);
+      }
+      for action in &self.actions {
+        notification.action(&action.id, &action.title);
+      }
+      show_and_wait(notification, self.on_event)
+    }
+    #[cfg(not(all(unix, not(target_os = "macos"))))]
+    {
+      let unsupported = if self.id.is_some() {
+        Some("id")
+      } else if !self.actions.is_empty() {
+        Some("actions")
+      } else if self.urgency.is_some() {
+        Some("urgency")
+      } else if self.timeout.is_some() {
+        Some("timeout")
+      } else if self.on_event.is_some() {
+        Some("event handler")
+      } else {
+        None
+      };
+      if let Some(option) = unsupported {
+        return Err(crate::api::Error::NotificationUnsupported(option));
+      }
+      notification.show()?;
+      Ok(NotificationHandle { id: None })
+    }
+  }
+}
+
+// The D-Bus connection of the notification can't be sent to another thread,
+// so it's shown on the thread waiting for its event
+#[cfg(all(unix, not(target_os = "macos")))]
+fn show_and_wait(
+  notification: notify_rust::Notification,
+  on_event: Option<Box<dyn FnOnce(NotificationEvent) + Send>>,
+) -> crate::api::Result<NotificationHandle> {
+  let on_event = match on_event {
+    Some(on_event) => on_event,
+    None => {
+      let handle = notification.show()?;
+      return Ok(NotificationHandle {
+        id: Some(handle.id()),
+      });
+    }
+  };
+
+  let (tx, rx) = std::sync::mpsc::channel();
+  std::thread::spawn(move || match notification.show() {
+    Ok(handle) => {
+      let _ = tx.send(Ok(handle.id()));
+      handle.wait_for_action(|action| {
+        on_event(match action {
+          "__closed" => NotificationEvent::Closed,
+          DEFAULT_ACTION => NotificationEvent::Clicked,
+          action => NotificationEvent::Action(action.to_string()),
+        })
+      });
+    }
+    Err(e) => {
+      let _ = tx.send(Err(e));
+    }
+  });
+  let id = rx.recv().expect("the notification thread panicked")?;
+  Ok(NotificationHandle { id: Some(id) })
+}
+
+/// A shown notification.
+#[derive(Debug, Clone)]
+pub struct NotificationHandle {
+  id: Option<u32>,
+}
+
+impl NotificationHandle {
+  /// The ID of the notification, used to replace or close it. `None` on Windows and macOS.
+  pub fn id(&self) -> Option<u32> {
+    self.id
+  }
+
+  /// Closes the notification. Does nothing on Windows and macOS.
+  pub fn close(&self) -> crate::api::Result<()> {
+    match self.id {
+      Some(id) => close(id),
+      None => Ok(()),
+    }
+  }
+}
+
+/// Closes the notification with the given ID. Does nothing on Windows and macOS.
+#[allow(unused_variables)]
+pub fn close(id: u32) -> crate::api::Result<()> {
+  #[cfg(all(unix, not(target_os = "macos")))]
+  {
+    let close_error = |e: dbus::Error| crate::api::Error::NotificationClose(e.to_string());
+    let connection = dbus::blocking::Connection::new_session().map_err(close_error)?;
+    connection
+      .with_proxy(
+        "org.freedesktop.Notifications",
+        "/org/freedesktop/Notifications",
+        std::time::Duration::from_secs(5),
+      )
+      .method_call::<(), _, _, _>("org.freedesktop.Notifications", "CloseNotification", (id,))
+      .map_err(close_error)?;
+  }
+  Ok(())
+}
+
+#[cfg(all(test, unix, not(target_os = "macos")))]
+mod test {
+  use super::*;
+  use dbus::{
+    blocking::{stdintf::org_freedesktop_dbus::RequestNameReply, Connection},
+    channel::{Channel, Sender},
+    message::MatchRule,
+    Message,
+  };
+
+  use std::{
+    io::{BufRead, BufReader},
+    process::{Command, Stdio},
+    sync::{
+      atomic::{AtomicU32, Ordering},
+      mpsc, Arc,
+    },
+    time::Duration,
+  };
+
+  const PATH: &str = "/org/freedesktop/Notifications";
+  const INTERFACE: &str = "org.freedesktop.Notifications";
+  // set on the process running the client side of the test
+  const CLIENT: &str = "TAURI_TEST_NOTIFICATION_CLIENT";
+
+  // A stand-in notification server, reporting the closed notifications
+  // and invoking the "reply" action of the first notification until the stop channel is closed
+  fn serve(connection: Connection, closed: mpsc::Sender<u32>, stop: mpsc::Receiver<()>) {
+    let first_id = Arc::new(AtomicU32::new(0));
+    let notified = first_id.clone();
+    let mut next_id = 0;
+    connection.start_receive(
+      MatchRule::new_method_call(),
+      Box::new(move |message: Message, connection: &Connection| {
+        let reply = match message.member().as_deref() {
+          Some("Notify") => {
+            let (_, replaces_id): (String, u32) = message.read2().unwrap();
+            let id = if replaces_id == 0 {
+              next_id += 1;
+              next_id
+            } else {
+              replaces_id
+            };
+            let _ = notified.compare_exchange(0, id, Ordering::SeqCst, Ordering::SeqCst);
+            message.method_return().append1(id)
+          }
+          Some("CloseNotification") => {
+            let id: u32 = message.read1().unwrap();
+            let _ = closed.send(id);
+            let _ = connection.send(
+              Message::new_signal(PATH, INTERFACE, "NotificationClosed")
+                .unwrap()
+                .append2(id, 3u32),
+            );
+            message.method_return()
+          }
+          Some("GetCapabilities") => message.method_return().append1(vec!["actions", "body"]),
+          _ => message.method_return(),
+        };
+        let _ = connection.send(reply);
+        true
+      }),
+    );
+
+    while let Err(mpsc::TryRecvError::Empty) = stop.try_recv() {
+      connection.process(Duration::from_millis(50)).unwrap();
+      // the client only receives the signal once it waits for the action, so it's repeated
+      let id = first_id.load(Ordering::SeqCst);
+      if id != 0 {
+        let _ = connection.send(
+          Message::new_signal(PATH, INTERFACE, "ActionInvoked")
+            .unwrap()
+            .append2(id, "reply"),
+        );
+      }
+    }
+  }
+
+  #[test]
+  fn actions_replace_and_close() {
+    // a private session bus, skipped where D-Bus isn't installed
+    let mut daemon = match Command::new("dbus-daemon")
+      .args(&["--session", "--nofork", "--print-address"])
+      .stdout(Stdio::piped())
+      .spawn()
+    {
+      Ok(daemon) => daemon,
+      Err(_) => return,
+    };
+    let mut address = String::new();
+    BufReader::new(daemon.stdout.take().unwrap())
+      .read_line(&mut address)
+      .unwrap();
+    let address = address.trim();
+
+    let mut channel = Channel::open_private(address).unwrap();
+    channel.register().unwrap();
+    let connection = Connection::from(channel);
+    // the name is owned once the request returns, before any notification is sent
+    let reply = connection
+      .request_name(INTERFACE, false, true, true)
+      .unwrap();
+    assert!(matches!(reply, RequestNameReply::PrimaryOwner));
+
+    let (closed_tx, closed_rx) = mpsc::channel();
+    let (stop_tx, stop_rx) = mpsc::channel();
+    let server = std::thread::spawn(move || serve(connection, closed_tx, stop_rx));
+
+    // the notifications use the session bus, so the client runs in a process connected to the private bus
+    let status = Command::new(std::env::current_exe().unwrap())
+      .args(&["--exact", "api::notification::test::client"])
+      .env("DBUS_SESSION_BUS_ADDRESS", address)
+      .env(CLIENT, "1")
+      .status()
+      .unwrap();
+    assert!(status.success());
+    assert_eq!(closed_rx.recv_timeout(Duration::from_secs(5)).unwrap(), 1);
+
+    drop(stop_tx);
+    server.join().unwrap();
+    let _ = daemon.kill();
+  }
+
+  // The client side of `actions_replace_and_close`, does nothing unless spawned by it
+  #[test]
+  fn client() {
+    if std::env::var_os(CLIENT).is_none() {
+      return;
+    }
+
+    let (event_tx, event_rx) = mpsc::channel();
+    let handle = Notification::new("studio.tauri.test")
+      .title("New message")
+      .action("reply", "Reply")
+      .urgency(Urgency::Critical)
+      .timeout(Timeout::Never)
+      .on_event(move |event| event_tx.send(event).unwrap())
+      .show()
+      .unwrap();
+    let id = handle.id().unwrap();
+    assert_eq!(
+      event_rx.recv_timeout(Duration::from_secs(5)).unwrap(),
+      NotificationEvent::Action("reply".into())
+    );
+
+    let replaced = Notification::new("studio.tauri.test")
+      .title("New messages")
+      .id(id)
+      .show()
+      .unwrap();
+    assert_eq!(replaced.id(), Some(id));
+    replaced.close().unwrap();
   }
 }
@@ -59,6 +59,18 @@ pub enum BaseDirectory {
   App,
   /// The current working directory.
   Current,
+  /// The default App log directory.
+  /// Resolves to `${BaseDirectory::Home}/Library/Logs/${config.tauri.bundle.identifier}` on macOS
+  /// and `${BaseDirectory::LocalData}/${config.tauri.bundle.identifier}/logs` on Linux and Windows.
+  Log,
+  /// The temporary directory.
+  Temp,
+  /// The default App cache directory.
+  /// Resolves to ${BaseDirectory::Cache}/${config.tauri.bundle.identifier}
+  AppCache,
+  /// The default App local data directory.
+  /// Resolves to ${BaseDirectory::LocalData}/${config.tauri.bundle.identifier}
+  AppLocalData,
 }
 
 /// Resolves the path with the optional base directory.
@@ -106,6 +118,10 @@ pub fn resolve_path<P: AsRef<Path>>(
       BaseDirectory::Resource => resource_dir(package_info),
       BaseDirectory::App => app_dir(config),
       BaseDirectory::Current => Some(env::current_dir()?),
+      BaseDirectory::Log => app_log_dir(config),
+      BaseDirectory::Temp => Some(temp_dir()),
+      BaseDirectory::AppCache => app_cache_dir(config),
+      BaseDirectory::AppLocalData => app_local_data_dir(config),
     };
     if let Some(mut base_dir_path_value) = base_dir_path {
       // use the same path resolution mechanism as the bundler's resource injection algorithm
@@ -217,6 +233,11 @@ pub fn video_dir() -> Option<PathBuf> {
   dirs_next::video_dir()
 }
 
+/// Returns the path to the temporary directory.
+pub fn temp_dir() -> PathBuf {
+  env::temp_dir()
+}
+
 /// Returns the path to the resource directory of this app.
 pub fn resource_dir(package_info: &PackageInfo) -> Option<PathBuf> {
   crate::api::platform::resource_dir(package_info).ok()
@@ -226,3 +247,160 @@ pub fn resource_dir(package_info: &PackageInfo) -> Option<PathBuf> {
 pub fn app_dir(config: &Config) -> Option<PathBuf> {
   dirs_next::config_dir().map(|dir| dir.join(&config.tauri.bundle.identifier))
 }
+
+/// Returns the path to the suggested directory for your app cache files.
+pub fn app_cache_dir(config: &Config) -> Option<PathBuf> {
+  dirs_next::cache_dir().map(|dir| dir.join(&config.tauri.bundle.identifier))
+}
+
+/// Returns the path to the suggested directory for your app local data files.
+pub fn app_local_data_dir(config: &Config) -> Option<PathBuf> {
+  dirs_next::data_local_dir().map(|dir| dir.join(&config.tauri.bundle.identifier))
+}
+
+/// Returns the path to the suggested directory for your app log files.
+pub fn app_log_dir(config: &Config) -> Option<PathBuf> {
+  #[cfg(target_os = "macos")]
+  let dir = dirs_next::home_dir().map(|dir| {
+    dir
+      .join("Library/Logs")
+      .join(&config.tauri.bundle.identifier)
+  });
+  #[cfg(not(target_os = "macos"))]
+  let dir =
+    dirs_next::data_local_dir().map(|dir| dir.join(&config.tauri.bundle.identifier).join("logs"));
+  dir
+}
+
+/// Normalizes the path, removing the `.` components and resolving the `..` components lexically.
+///
+/// The file system isn't accessed, so symlinks aren't resolved. The leading `..` components of relative paths are kept
+/// and the `..` components after the root are dropped. An empty path is normalized to `.`.
+///
+/// # Example
+/// ```
+/// use tauri::api::path::normalize;
+/// use std::path::Path;
+///
+/// assert_eq!(normalize("a/./b/../c"), Path::new("a").join("c"));
+/// ```
+pub fn normalize<P: AsRef<Path>>(path: P) -> PathBuf {
+  let mut normalized = PathBuf::new();
+  // the number of components that can be removed by a `..`
+  let mut depth = 0;
+  for component in path.as_ref().components() {
+    match component {
+      Component::Prefix(_) | Component::RootDir => normalized.push(component.as_os_str()),
+      Component::CurDir => {}
+      Component::ParentDir => {
+        if depth > 0 {
+          normalized.pop();
+          depth -= 1;
+        } else if !normalized.has_root() {
+          normalized.push("..");
+        }
+      }
+      Component::Normal(name) => {
+        normalized.push(name);
+        depth += 1;
+      }
+    }
+  }
+  if normalized.as_os_str().is_empty() {
+    normalized.push(".");
+  }
+  normalized
+}
+
+/// Returns the relative path from `from` to `to`, after normalizing both.
+///
+/// Fails if one path is absolute and the other isn't, if they are on different Windows drives
+/// or if `from` goes above the current directory.
+///
+/// # Example
+/// ```
+/// use tauri::api::path::relative;
+/// use std::path::Path;
+///
+/// assert_eq!(
+///   relative("/data/app/logs", "/data/app/cache/images").unwrap(),
+///   Path::new("..").join("cache").join("images")
+/// );
+/// ```
+pub fn relative<F: AsRef<Path>, T: AsRef<Path>>(from: F, to: T) -> crate::api::Result<PathBuf> {
+  let from = normalize(from);
+  let to = normalize(to);
+  let error = || {
+    crate::api::Error::Path(format!(
+      "unable to compute the path from {} to {}",
+      from.display(),
+      to.display()
+    ))
+  };
+
+  let mut from_components = from
+    .components()
+    .filter(|c| *c != Component::CurDir)
+    .peekable();
+  let mut to_components = to
+    .components()
+    .filter(|c| *c != Component::CurDir)
+    .peekable();
+  while let (Some(a), Some(b)) = (from_components.peek(), to_components.peek()) {
+    if a != b {
+      break;
+    }
+    from_components.next();
+    to_components.next();
+  }
+
+  let mut relative = PathBuf::new();
+  for component in from_components {
+    match component {
+      Component::Normal(_) => relative.push(".."),
+      // a different root, drive or an unknown parent directory
+      _ => return Err(error()),
+    }
+  }
+  for component in to_components {
+    match component {
+      Component::Prefix(_) | Component::RootDir => return Err(error()),
+      component => relative.push(component.as_os_str()),
+    }
+  }
+  if relative.as_os_str().is_empty() {
+    relative.push(".");
+  }
+  Ok(relative)
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn normalize_paths() {
+    assert_eq!(normalize("a/./b/../c"), Path::new("a/c"));
+    assert_eq!(normalize("../a/../../b"), Path::new("../../b"));
+    assert_eq!(normalize("a/.."), Path::new("."));
+    assert_eq!(normalize(""), Path::new("."));
+    #[cfg(unix)]
+    assert_eq!(normalize("/../a//b/"), Path::new("/a/b"));
+    #[cfg(windows)]
+    assert_eq!(normalize(r"C:\..\a\.\b"), Path::new(r"C:\a\b"));
+  }
+
+  #[test]
+  fn relative_paths() {
+    assert_eq!(relative("a/b", "a/c/d").unwrap(), Path::new("../c/d"));
+    assert_eq!(relative("a/b", "a/b").unwrap(), Path::new("."));
+    assert_eq!(relative(".", "../a").unwrap(), Path::new("../a"));
+    assert!(relative("../a", "b").is_err());
+    #[cfg(unix)]
+    {
+      assert_eq!(relative("/a/b/c", "/a").unwrap(), Path::new("../.."));
+      assert!(relative("/a", "a").is_err());
+      assert!(relative("a", "/a").is_err());
+    }
+  }
+}
@@ -0,0 +1,503 @@
+// Copyright 2019-2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use crate::{
+  api::path::{resolve_path, BaseDirectory},
+  Config, PackageInfo,
+};
+
+use glob::{MatchOptions, Pattern};
+
+use std::{
+  collections::HashMap,
+  env,
+  path::{is_separator, Component, Path, PathBuf},
+  sync::Mutex,
+};
+
+/// The paths the scoped filesystem APIs can access.
+///
+/// Configured on `tauri.conf.json > tauri > allowlist > fs > scope` as a list of glob patterns.
+/// A pattern can start with a base directory variable (`$APP`, `$AUDIO`, `$CACHE`, `$CONFIG`, `$DATA`, `$LOCALDATA`,
+/// `$DESKTOP`, `$DOCUMENT`, `$DOWNLOAD`, `$EXE`, `$FONT`, `$HOME`, `$PICTURE`, `$PUBLIC`, `$RUNTIME`,
+/// `$TEMPLATE`, `$VIDEO`, `$RESOURCE`, `$LOG`, `$TEMP`, `$APPCACHE` or `$APPLOCALDATA`), e.g. `$APP/**` allows everything in the app directory.
+///
+/// The scope is built once and managed by the app, a window can also access the paths allowed for it at runtime,
+/// e.g. the paths the user picked on a dialog.
+///
+/// All paths are allowed if the scope isn't configured.
+#[derive(Debug)]
+pub struct FsScope {
+  patterns: Option<Vec<Pattern>>,
+  // the paths allowed at runtime for each window label
+  allowed: Mutex<HashMap<String, Vec<AllowedPath>>>,
+}
+
+#[derive(Debug)]
+enum AllowedPath {
+  File(PathBuf),
+  Directory(PathBuf),
+}
+
+impl FsScope {
+  /// Creates the scope of the filesystem APIs from the configuration.
+  pub fn for_fs_api(config: &Config, package_info: &PackageInfo) -> crate::api::Result<Self> {
+    let patterns = match &config.tauri.allowlist.fs.scope {
+      Some(scope) => Some(
+        scope
+          .iter()
+          .map(|pattern| parse_pattern(config, package_info, pattern))
+          .collect::<crate::api::Result<Vec<Pattern>>>()?,
+      ),
+      None => None,
+    };
+    Ok(Self {
+      patterns,
+      allowed: Default::default(),
+    })
+  }
+
+  /// Allows the window to access the file, e.g. after the user picked it on a dialog.
+  pub fn allow_file<P: AsRef<Path>>(&self, window: &str, path: P) {
+    self.allow(window, AllowedPath::File(normalize(path.as_ref())));
+  }
+
+  /// Allows the window to access the directory and its descendants.
+  pub fn allow_directory<P: AsRef<Path>>(&self, window: &str, path: P) {
+    self.allow(window, AllowedPath::Directory(normalize(path.as_ref())));
+  }
+
+  fn allow(&self, window: &str, path: AllowedPath) {
+    self
+      .allowed
+      .lock()
+      .unwrap()
+      .entry(window.into())
+      .or_default()
+      .push(path);
+  }
+
+  /// Forgets the paths allowed for the window, e.g. when it's destroyed.
+  pub fn forget_window(&self, window: &str) {
+    self.allowed.lock().unwrap().remove(window);
+  }
+
+  /// Whether the path can be accessed by the window.
+  pub fn is_allowed<P: AsRef<Path>>(&self, window: &str, path: P) -> bool {
+    let patterns = match &self.patterns {
+      Some(patterns) => patterns,
+      None => return true,
+    };
+    let path = normalize(path.as_ref());
+
+    let options = MatchOptions {
+      // `*` doesn't match the path separator, use `**` to match nested paths
+      require_literal_separator: true,
+      ..Default::default()
+    };
+    patterns
+      .iter()
+      .any(|pattern| pattern.matches_path_with(&path, options))
+      || self
+        .allowed
+        .lock()
+        .unwrap()
+        .get(window)
+        .map_or(false, |allowed| {
+          allowed.iter().any(|allowed| match allowed {
+            AllowedPath::File(file) => &path == file,
+            AllowedPath::Directory(directory) => path.starts_with(directory),
+          })
+        })
+  }
+
+  /// Returns an error if the path can't be accessed by the window.
+  pub fn check<P: AsRef<Path>>(&self, window: &str, path: P) -> crate::api::Result<()> {
+    if self.is_allowed(window, &path) {
+      Ok(())
+    } else {
+      Err(crate::api::Error::PathNotAllowed(
+        path.as_ref().to_path_buf(),
+      ))
+    }
+  }
+}
+
+/// The URLs the WebSocket API can connect to.
+///
+/// Configured on `tauri.conf.json > tauri > allowlist > websocket > scope` as a list of glob patterns,
+/// e.g. `wss://dashboard.example.com/*` or `wss://*.example.com/*`.
+/// The scheme, host, port and path of the URL are matched separately, so a `*` on the host never matches the path.
+/// A pattern without a port only matches the default port of its scheme.
+///
+/// All URLs are allowed if the scope isn't configured.
+#[derive(Debug, Clone)]
+pub struct UrlScope {
+  patterns: Option<Vec<UrlPattern>>,
+}
+
+#[derive(Debug, Clone)]
+struct UrlPattern {
+  scheme: Pattern,
+  host: Pattern,
+  port: Option<Pattern>,
+  // matches the path and the query
+  path: Pattern,
+}
+
+impl UrlPattern {
+  fn parse(pattern: &str) -> crate::api::Result<Self> {
+    let scheme_end = pattern.find("://").ok_or(glob::PatternError {
+      pos: 0,
+      msg: "the URL pattern must start with a scheme, e.g. `wss://`",
+    })?;
+    let rest = &pattern[scheme_end + 3..];
+    let (authority, path) = match rest.find('/') {
+      Some(path_start) => rest.split_at(path_start),
+      None => (rest, "/"),
+    };
+    let (host, port) = match authority.rfind(':') {
+      // the brackets of IPv6 hosts contain colons
+      Some(port_start) if !authority[port_start..].contains(']') => (
+        &authority[..port_start],
+        Some(Pattern::new(&authority[port_start + 1..])?),
+      ),
+      _ => (authority, None),
+    };
+    Ok(Self {
+      scheme: Pattern::new(&pattern[..scheme_end].to_lowercase())?,
+      host: Pattern::new(&host.to_lowercase())?,
+      port,
+      path: Pattern::new(path)?,
+    })
+  }
+
+  fn matches(&self, url: &url::Url) -> bool {
+    let host = match url.host_str() {
+      Some(host) => host,
+      None => return false,
+    };
+    let port_matches = match (&self.port, url.port()) {
+      (Some(pattern), Some(port)) => pattern.matches(&port.to_string()),
+      (None, None) => true,
+      _ => false,
+    };
+    let path = match url.query() {
+      Some(query) => format!("{}?{}", url.path(), query),
+      None => url.path().to_string(),
+    };
+    self.scheme.matches(url.scheme())
+      && self.host.matches(host)
+      && port_matches
+      && self.path.matches(&path)
+  }
+}
+
+impl UrlScope {
+  /// Creates the scope of the WebSocket API from the configuration.
+  pub fn for_websocket_api(config: &Config) -> crate::api::Result<Self> {
+    let patterns = match &config.tauri.allowlist.websocket.scope {
+      Some(scope) => Some(
+        scope
+          .iter()
+          .map(|pattern| UrlPattern::parse(pattern))
+          .collect::<crate::api::Result<Vec<UrlPattern>>>()?,
+      ),
+      None => None,
+    };
+    Ok(Self { patterns })
+  }
+
+  /// Whether the URL can be accessed.
+  pub fn is_allowed(&self, url: &str) -> bool {
+    match &self.patterns {
+      Some(patterns) => match url::Url::parse(url) {
+        // match the normalized URL so the host casing or default port can't bypass the scope
+        Ok(url) => patterns.iter().any(|pattern| pattern.matches(&url)),
+        Err(_) => false,
+      },
+      None => true,
+    }
+  }
+
+  /// Returns an error if the URL can't be accessed.
+  pub fn check(&self, url: &str) -> crate::api::Result<()> {
+    if self.is_allowed(url) {
+      Ok(())
+    } else {
+      Err(crate::api::Error::UrlNotAllowed(url.into()))
+    }
+  }
+}
+
+// Replace the base directory variable and parse the glob pattern,
+// the literal prefix of the pattern is normalized like the checked paths
+fn parse_pattern(
+  config: &Config,
+  package_info: &PackageInfo,
+  pattern: &str,
+) -> crate::api::Result<Pattern> {
+  let (prefix, glob) = if let Some(variable) = pattern.strip_prefix('$') {
+    let end = variable.find(is_separator).unwrap_or(variable.len());
+    let base_dir = base_directory(&variable[..end]).ok_or_else(|| {
+      crate::api::Error::Path(format!("unknown scope variable `${}`", &variable[..end]))
+    })?;
+    let base_dir_path = resolve_path(config, package_info, "", Some(base_dir))?;
+    let (literal, glob) = split_literal_prefix(&variable[end..]);
+    (
+      format!(
+        "{}{}",
+        base_dir_path
+          .to_string_lossy()
+          .trim_end_matches(is_separator),
+        literal
+      ),
+      glob,
+    )
+  } else {
+    let (literal, glob) = split_literal_prefix(pattern);
+    (literal.to_string(), glob)
+  };
+
+  let pattern = if prefix.is_empty() {
+    glob.to_string()
+  } else {
+    let prefix = normalize(Path::new(&prefix));
+    format!(
+      "{}{}",
+      Pattern::escape(prefix.to_string_lossy().trim_end_matches(is_separator)),
+      glob
+    )
+  };
+  Pattern::new(&pattern).map_err(|e| crate::api::Error::Path(e.to_string()))
+}
+
+// Split the components without glob characters from the rest of the pattern,
+// e.g. `/home/user/*.txt` is split into `/home/user` and `/*.txt`
+fn split_literal_prefix(pattern: &str) -> (&str, &str) {
+  match pattern.find(|c| c == '*' || c == '?' || c == '[') {
+    Some(glob_start) => {
+      let end = pattern[..glob_start].rfind(is_separator).unwrap_or(0);
+      pattern.split_at(end)
+    }
+    None => (pattern, ""),
+  }
+}
+
+fn base_directory(variable: &str) -> Option<BaseDirectory> {
+  let dir = match variable {
+    "APP" => BaseDirectory::App,
+    "AUDIO" => BaseDirectory::Audio,
+    "CACHE" => BaseDirectory::Cache,
+    "CONFIG" => BaseDirectory::Config,
+    "DATA" => BaseDirectory::Data,
+    "LOCALDATA" => BaseDirectory::LocalData,
+    "DESKTOP" => BaseDirectory::Desktop,
+    "DOCUMENT" => BaseDirectory::Document,
+    "DOWNLOAD" => BaseDirectory::Download,
+    "EXE" => BaseDirectory::Executable,
+    "FONT" => BaseDirectory::Font,
+    "HOME" => BaseDirectory::Home,
+    "PICTURE" => BaseDirectory::Picture,
+    "PUBLIC" => BaseDirectory::Public,
+    "RUNTIME" => BaseDirectory::Runtime,
+    "TEMPLATE" => BaseDirectory::Template,
+    "VIDEO" => BaseDirectory::Video,
+    "RESOURCE" => BaseDirectory::Resource,
+    "LOG" => BaseDirectory::Log,
+    "TEMP" => BaseDirectory::Temp,
+    "APPCACHE" => BaseDirectory::AppCache,
+    "APPLOCALDATA" => BaseDirectory::AppLocalData,
+    _ => return None,
+  };
+  Some(dir)
+}
+
+// Make the path absolute, resolve the symlinks and remove the `.` and `..` components,
+// so neither `$APP/../secret` nor a symlink in the scope can escape it.
+// The path might not exist yet (e.g. a file to write), so only its existing ancestors are canonicalized.
+fn normalize(path: &Path) -> PathBuf {
+  let path = if path.is_absolute() {
+    path.to_path_buf()
+  } else {
+    env::current_dir()
+      .map(|cwd| cwd.join(path))
+      .unwrap_or_else(|_| path.to_path_buf())
+  };
+
+  let components: Vec<Component<'_>> = path.components().collect();
+  let (mut normalized, rest) = (1..=components.len())
+    .rev()
+    .find_map(|existing| {
+      components[..existing]
+        .iter()
+        .collect::<PathBuf>()
+        .canonicalize()
+        .ok()
+        .map(|canonical| (canonical, &components[existing..]))
+    })
+    .unwrap_or_else(|| (PathBuf::new(), &components[..]));
+
+  // the rest doesn't exist, so it can't be a symlink
+  for component in rest {
+    match component {
+      Component::CurDir => {}
+      Component::ParentDir => {
+        normalized.pop();
+      }
+      component => normalized.push(component),
+    }
+  }
+  normalized
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  fn scope(patterns: &[&str]) -> FsScope {
+    FsScope {
+      patterns: Some(
+        patterns
+          .iter()
+          .map(|pattern| Pattern::new(pattern).unwrap())
+          .collect(),
+      ),
+      allowed: Default::default(),
+    }
+  }
+
+  #[test]
+  fn unrestricted_without_scope() {
+    let scope = FsScope {
+      patterns: None,
+      allowed: Default::default(),
+    };
+    assert!(scope.is_allowed("main", "/etc/passwd"));
+  }
+
+  #[cfg(not(windows))]
+  #[test]
+  fn glob_patterns() {
+    let scope = scope(&["/home/tauri/app/*", "/home/tauri/data/**"]);
+    assert!(scope.is_allowed("main", "/home/tauri/app/file.txt"));
+    assert!(!scope.is_allowed("main", "/home/tauri/app/nested/file.txt"));
+    assert!(scope.is_allowed("main", "/home/tauri/data/nested/file.txt"));
+    assert!(!scope.is_allowed("main", "/home/tauri/other.txt"));
+    assert!(!scope.is_allowed("main", "/home/tauri/app/../other.txt"));
+    assert!(scope.check("main", "/home/tauri/other.txt").is_err());
+  }
+
+  #[cfg(not(windows))]
+  #[test]
+  fn window_allowed_paths() {
+    let scope = scope(&["/home/tauri/app/*"]);
+    scope.allow_file("main", "/home/tauri/picked/file.txt");
+    scope.allow_directory("main", "/home/tauri/folder");
+
+    assert!(scope.is_allowed("main", "/home/tauri/picked/file.txt"));
+    assert!(!scope.is_allowed("main", "/home/tauri/picked/other.txt"));
+    assert!(scope.is_allowed("main", "/home/tauri/folder/nested/file.txt"));
+    assert!(!scope.is_allowed("other", "/home/tauri/picked/file.txt"));
+    assert!(!scope.is_allowed("other", "/home/tauri/folder/file.txt"));
+
+    scope.forget_window("main");
+    assert!(!scope.is_allowed("main", "/home/tauri/picked/file.txt"));
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn symlinks_cannot_escape() {
+    let dir = tempfile::tempdir().unwrap();
+    let app_dir = dir.path().join("app");
+    let outside = dir.path().join("outside");
+    std::fs::create_dir(&app_dir).unwrap();
+    std::fs::create_dir(&outside).unwrap();
+    std::os::unix::fs::symlink(&outside, app_dir.join("link")).unwrap();
+
+    let mut config: Config = Default::default();
+    config.tauri.allowlist.fs.scope = Some(vec![format!("{}/**", app_dir.display())]);
+    let package_info = PackageInfo {
+      name: "app".into(),
+      version: "1.0.0".into(),
+    };
+    let scope = FsScope::for_fs_api(&config, &package_info).unwrap();
+
+    assert!(scope.is_allowed("main", app_dir.join("file.txt")));
+    assert!(scope.is_allowed("main", app_dir.join("new").join("file.txt")));
+    assert!(!scope.is_allowed("main", app_dir.join("link").join("secret")));
+    assert!(!scope.is_allowed(
+      "main",
+      app_dir.join("new").join("..").join("..").join("secret")
+    ));
+  }
+
+  #[test]
+  fn literal_prefix() {
+    assert_eq!(
+      split_literal_prefix("/home/tauri/*.txt"),
+      ("/home/tauri", "/*.txt")
+    );
+    assert_eq!(
+      split_literal_prefix("/home/tauri/file.txt"),
+      ("/home/tauri/file.txt", "")
+    );
+    assert_eq!(split_literal_prefix("**/*.txt"), ("", "**/*.txt"));
+  }
+
+  #[test]
+  fn scope_variables() {
+    let mut config: Config = Default::default();
+    config.tauri.allowlist.fs.scope = Some(vec!["$HOME/*".into()]);
+    let package_info = PackageInfo {
+      name: "app".into(),
+      version: "1.0.0".into(),
+    };
+    let scope = FsScope::for_fs_api(&config, &package_info).unwrap();
+    if let Some(home) = crate::api::path::home_dir() {
+      assert!(scope.is_allowed("main", home.join("file.txt")));
+      assert!(!scope.is_allowed("main", home.join("nested").join("file.txt")));
+    }
+
+    config.tauri.allowlist.fs.scope = Some(vec!["$UNKNOWN/*".into()]);
+    assert!(FsScope::for_fs_api(&config, &package_info).is_err());
+  }
+
+  #[test]
+  fn url_scope() {
+    let mut config: Config = Default::default();
+    let scope = UrlScope::for_websocket_api(&config).unwrap();
+    assert!(scope.is_allowed("ws://localhost:8080"));
+
+    config.tauri.allowlist.websocket.scope = Some(vec!["wss://dashboard.example.com/*".into()]);
+    let scope = UrlScope::for_websocket_api(&config).unwrap();
+    assert!(scope.is_allowed("wss://dashboard.example.com/live"));
+    assert!(scope.is_allowed("wss://Dashboard.example.com:443/live"));
+    assert!(!scope.is_allowed("wss://dashboard.example.com.evil.com/live"));
+    assert!(!scope.is_allowed("ws://dashboard.example.com/live"));
+    assert!(!scope.is_allowed("wss://dashboard.example.com:8443/live"));
+    assert!(scope.check("not a url").is_err());
+  }
+
+  #[test]
+  fn url_scope_wildcard_host() {
+    let mut config: Config = Default::default();
+    config.tauri.allowlist.websocket.scope = Some(vec![
+      "wss://*.example.com/*".into(),
+      "ws://localhost:*/*".into(),
+    ]);
+    let scope = UrlScope::for_websocket_api(&config).unwrap();
+    assert!(scope.is_allowed("wss://live.example.com/feed?token=1"));
+    assert!(scope.is_allowed("ws://localhost:8080/"));
+    assert!(!scope.is_allowed("ws://localhost/"));
+    // the wildcard can't match the path or the credentials
+    assert!(!scope.is_allowed("wss://evil.com/x.example.com/"));
+    assert!(!scope.is_allowed("wss://evil.com/.example.com/"));
+    assert!(!scope.is_allowed("wss://live.example.com@evil.com/"));
+    assert!(!scope.is_allowed("wss://example.com.evil.com/"));
+
+    config.tauri.allowlist.websocket.scope = Some(vec!["dashboard.example.com/*".into()]);
+    assert!(UrlScope::for_websocket_api(&config).is_err());
+  }
+}
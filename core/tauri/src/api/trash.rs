@@ -0,0 +1,383 @@
+// Copyright 2019-2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! On Linux and the BSDs, the trash follows the [freedesktop.org Trash specification](https://specifications.freedesktop.org/trash-spec/trashspec-latest.html),
+//! using the home trash (`$XDG_DATA_HOME/Trash`). On Windows, the Recycle Bin is used.
+//! On macOS, the files are moved to the Trash but it can't be listed or restored.
+
+use serde::Serialize;
+
+use std::path::{Path, PathBuf};
+
+/// An item of the trash.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct TrashItem {
+  /// The identifier of the item, used to restore it.
+  pub id: String,
+  /// The name of the item.
+  pub name: String,
+  /// The path of the item before it was moved to the trash.
+  pub original_path: PathBuf,
+  /// The deletion time, in milliseconds since the Unix epoch.
+  pub deleted_at: Option<u64>,
+}
+
+/// Moves a file or directory to the trash.
+///
+/// The returned item can be restored with [`restore`], except on macOS where its `id` is empty.
+///
+/// # Examples
+///
+/// ```no_run
+/// use tauri::api::trash;
+///
+/// let item = trash::delete("/home/user/notes.txt").unwrap();
+/// // undo
+/// trash::restore(&item.id).unwrap();
+/// ```
+pub fn delete<P: AsRef<Path>>(path: P) -> crate::api::Result<TrashItem> {
+  let path = path.as_ref();
+  // the parent is resolved but the file itself isn't, so symlinks are trashed instead of their target
+  let path = if path.is_absolute() {
+    path.to_path_buf()
+  } else {
+    std::env::current_dir()?.join(path)
+  };
+  // fail with the usual error if the path doesn't exist
+  path.symlink_metadata()?;
+  imp::delete(&path)
+}
+
+/// Lists the items of the trash.
+pub fn list() -> crate::api::Result<Vec<TrashItem>> {
+  imp::list()
+}
+
+/// Restores the trash item to its original path, which is returned.
+///
+/// Fails if the original path already exists.
+pub fn restore(id: &str) -> crate::api::Result<PathBuf> {
+  imp::restore(id)
+}
+
+fn trash_error(message: impl Into<String>) -> crate::api::Error {
+  crate::api::Error::Trash(message.into())
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+mod imp {
+  use super::{trash_error, TrashItem};
+  use chrono::{Local, NaiveDateTime, TimeZone};
+
+  use std::{
+    fs, io,
+    io::Write,
+    path::{Path, PathBuf},
+  };
+
+  const DATE_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+  // the error of a rename across file systems
+  const EXDEV: i32 = 18;
+
+  fn trash_dir() -> crate::api::Result<PathBuf> {
+    crate::api::path::data_dir()
+      .map(|dir| dir.join("Trash"))
+      .ok_or_else(|| trash_error("failed to find the data directory"))
+  }
+
+  fn create_trash(trash: &Path) -> io::Result<()> {
+    fs::create_dir_all(trash.join("files"))?;
+    fs::create_dir_all(trash.join("info"))
+  }
+
+  fn info_path(trash: &Path, id: &str) -> PathBuf {
+    trash.join("info").join(format!("{}.trashinfo", id))
+  }
+
+  pub fn delete(path: &Path) -> crate::api::Result<TrashItem> {
+    delete_in(&trash_dir()?, path)
+  }
+
+  pub fn list() -> crate::api::Result<Vec<TrashItem>> {
+    list_in(&trash_dir()?)
+  }
+
+  pub fn restore(id: &str) -> crate::api::Result<PathBuf> {
+    restore_in(&trash_dir()?, id)
+  }
+
+  pub(super) fn delete_in(trash: &Path, path: &Path) -> crate::api::Result<TrashItem> {
+    create_trash(trash)?;
+    let name = path
+      .file_name()
+      .ok_or_else(|| trash_error(format!("{} can't be moved to the trash", path.display())))?
+      .to_string_lossy()
+      .into_owned();
+    let encoded_path = url::Url::from_file_path(path)
+      .map_err(|_| trash_error(format!("invalid path {}", path.display())))?
+      .path()
+      .to_string();
+    let deleted_at = Local::now();
+
+    // the name is reserved by creating the info file, as required by the specification
+    let mut index = 1;
+    let (id, mut info) = loop {
+      let id = if index == 1 {
+        name.clone()
+      } else {
+        format!("{}.{}", name, index)
+      };
+      index += 1;
+      if trash.join("files").join(&id).symlink_metadata().is_ok() {
+        continue;
+      }
+      match fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(info_path(&trash, &id))
+      {
+        Ok(info) => break (id, info),
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+        Err(e) => return Err(e.into()),
+      }
+    };
+
+    let result = write!(
+      info,
+      "[Trash Info]\nPath={}\nDeletionDate={}\n",
+      encoded_path,
+      deleted_at.format(DATE_FORMAT)
+    )
+    .and_then(|_| move_path(path, &trash.join("files").join(&id)));
+    if let Err(e) = result {
+      let _ = fs::remove_file(info_path(&trash, &id));
+      return Err(e.into());
+    }
+
+    Ok(TrashItem {
+      id,
+      name,
+      original_path: path.to_path_buf(),
+      // the deletion date is stored with a second precision
+      deleted_at: Some(deleted_at.timestamp() as u64 * 1000),
+    })
+  }
+
+  pub(super) fn list_in(trash: &Path) -> crate::api::Result<Vec<TrashItem>> {
+    create_trash(trash)?;
+    let mut items = Vec::new();
+    for entry in fs::read_dir(trash.join("info"))? {
+      let path = entry?.path();
+      if path.extension().map_or(false, |e| e == "trashinfo") {
+        // the info files written by other applications may be invalid
+        if let Ok(item) = read_info(&path) {
+          items.push(item);
+        }
+      }
+    }
+    Ok(items)
+  }
+
+  pub(super) fn restore_in(trash: &Path, id: &str) -> crate::api::Result<PathBuf> {
+    if id.is_empty() || id.contains('/') || id == "." || id == ".." {
+      return Err(trash_error(format!("invalid trash item id {}", id)));
+    }
+    create_trash(trash)?;
+    let info = info_path(&trash, id);
+    let item = read_info(&info)?;
+    if item.original_path.symlink_metadata().is_ok() {
+      return Err(trash_error(format!(
+        "{} already exists",
+        item.original_path.display()
+      )));
+    }
+    if let Some(parent) = item.original_path.parent() {
+      fs::create_dir_all(parent)?;
+    }
+    move_path(&trash.join("files").join(id), &item.original_path)?;
+    fs::remove_file(info)?;
+    Ok(item.original_path)
+  }
+
+  fn read_info(info: &Path) -> crate::api::Result<TrashItem> {
+    let id = info
+      .file_stem()
+      .map(|stem| stem.to_string_lossy().into_owned())
+      .unwrap_or_default();
+    let contents = fs::read_to_string(info)?;
+    let mut original_path = None;
+    let mut deleted_at = None;
+    for line in contents.lines() {
+      if let Some(path) = line.strip_prefix("Path=") {
+        original_path = url::Url::parse(&format!("file://{}", path))
+          .ok()
+          .and_then(|url| url.to_file_path().ok());
+      } else if let Some(date) = line.strip_prefix("DeletionDate=") {
+        deleted_at = NaiveDateTime::parse_from_str(date, DATE_FORMAT)
+          .ok()
+          .and_then(|date| Local.from_local_datetime(&date).earliest())
+          .map(|date| date.timestamp_millis() as u64);
+      }
+    }
+    let original_path =
+      original_path.ok_or_else(|| trash_error(format!("invalid trash info {}", info.display())))?;
+
+    Ok(TrashItem {
+      id,
+      name: original_path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default(),
+      original_path,
+      deleted_at,
+    })
+  }
+
+  // Renames the path, copying it if the destination is on another file system
+  fn move_path(from: &Path, to: &Path) -> io::Result<()> {
+    match fs::rename(from, to) {
+      Err(e) if e.raw_os_error() == Some(EXDEV) => {
+        copy_all(from, to)?;
+        if from.symlink_metadata()?.is_dir() {
+          fs::remove_dir_all(from)
+        } else {
+          fs::remove_file(from)
+        }
+      }
+      result => result,
+    }
+  }
+
+  fn copy_all(from: &Path, to: &Path) -> io::Result<()> {
+    let metadata = from.symlink_metadata()?;
+    if metadata.file_type().is_symlink() {
+      std::os::unix::fs::symlink(fs::read_link(from)?, to)
+    } else if metadata.is_dir() {
+      fs::create_dir(to)?;
+      for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        copy_all(&entry.path(), &to.join(entry.file_name()))?;
+      }
+      fs::set_permissions(to, metadata.permissions())
+    } else {
+      fs::copy(from, to).map(|_| ())
+    }
+  }
+}
+
+#[cfg(windows)]
+mod imp {
+  use super::{trash_error, TrashItem};
+
+  use std::path::{Path, PathBuf};
+
+  fn items() -> crate::api::Result<Vec<trash::TrashItem>> {
+    trash::os_limited::list().map_err(|e| trash_error(e.to_string()))
+  }
+
+  fn from_item(item: trash::TrashItem) -> TrashItem {
+    TrashItem {
+      id: item.id.to_string_lossy().into_owned(),
+      original_path: item.original_parent.join(&item.name),
+      name: item.name,
+      deleted_at: Some(item.time_deleted.max(0) as u64 * 1000),
+    }
+  }
+
+  pub fn delete(path: &Path) -> crate::api::Result<TrashItem> {
+    trash::delete(path).map_err(|e| trash_error(e.to_string()))?;
+    items()?
+      .into_iter()
+      .map(from_item)
+      .filter(|item| item.original_path == path)
+      .max_by_key(|item| item.deleted_at)
+      .ok_or_else(|| trash_error(format!("{} wasn't found in the trash", path.display())))
+  }
+
+  pub fn list() -> crate::api::Result<Vec<TrashItem>> {
+    Ok(items()?.into_iter().map(from_item).collect())
+  }
+
+  pub fn restore(id: &str) -> crate::api::Result<PathBuf> {
+    let item = items()?
+      .into_iter()
+      .find(|item| item.id.to_string_lossy() == id)
+      .ok_or_else(|| trash_error(format!("trash item {} not found", id)))?;
+    let original_path = item.original_parent.join(&item.name);
+    trash::os_limited::restore_all(vec![item]).map_err(|e| trash_error(e.to_string()))?;
+    Ok(original_path)
+  }
+}
+
+#[cfg(target_os = "macos")]
+mod imp {
+  use super::{trash_error, TrashItem};
+
+  use std::path::{Path, PathBuf};
+
+  pub fn delete(path: &Path) -> crate::api::Result<TrashItem> {
+    trash::delete(path).map_err(|e| trash_error(e.to_string()))?;
+    Ok(TrashItem {
+      id: String::new(),
+      name: path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default(),
+      original_path: path.to_path_buf(),
+      deleted_at: std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .ok()
+        .map(|duration| duration.as_millis() as u64),
+    })
+  }
+
+  pub fn list() -> crate::api::Result<Vec<TrashItem>> {
+    Err(trash_error("listing the trash isn't supported on macOS"))
+  }
+
+  pub fn restore(_id: &str) -> crate::api::Result<PathBuf> {
+    Err(trash_error(
+      "restoring from the trash isn't supported on macOS",
+    ))
+  }
+}
+
+#[cfg(all(test, unix, not(target_os = "macos")))]
+mod test {
+  use super::imp::{delete_in, list_in, restore_in};
+
+  #[test]
+  fn delete_and_restore() {
+    let trash = tempfile::tempdir().unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("notes.txt");
+    std::fs::write(&file, "notes").unwrap();
+
+    let item = delete_in(trash.path(), &file).unwrap();
+    assert!(!file.exists());
+    assert_eq!(item.original_path, file);
+    let info = std::fs::read_to_string(
+      trash
+        .path()
+        .join("info")
+        .join(format!("{}.trashinfo", item.id)),
+    )
+    .unwrap();
+    assert!(info.starts_with("[Trash Info]\nPath="));
+    assert!(list_in(trash.path()).unwrap().contains(&item));
+
+    // a file with the same name gets another id
+    std::fs::write(&file, "other notes").unwrap();
+    let other = delete_in(trash.path(), &file).unwrap();
+    assert_ne!(other.id, item.id);
+
+    assert_eq!(restore_in(trash.path(), &item.id).unwrap(), file);
+    assert_eq!(std::fs::read_to_string(&file).unwrap(), "notes");
+    assert!(restore_in(trash.path(), &other.id).is_err());
+    assert!(restore_in(trash.path(), "../info").is_err());
+  }
+}
@@ -0,0 +1,235 @@
+// Copyright 2019-2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use futures::{stream::SplitSink, SinkExt, StreamExt};
+use http::header::{HeaderName, HeaderValue};
+use serde::{Deserialize, Serialize};
+use tokio::{
+  net::TcpStream,
+  sync::{
+    mpsc::{channel, Receiver},
+    Mutex,
+  },
+};
+use tokio_tungstenite::{
+  tungstenite::{self, client::IntoClientRequest},
+  MaybeTlsStream, WebSocketStream,
+};
+
+use std::{collections::HashMap, sync::Arc};
+
+type Sink = SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, tungstenite::Message>;
+
+/// A WebSocket message.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data")]
+#[non_exhaustive]
+pub enum Message {
+  /// A text message.
+  Text(String),
+  /// A binary message.
+  Binary(Vec<u8>),
+}
+
+/// The close frame sent by the server.
+#[derive(Debug, Clone, Serialize)]
+pub struct CloseFrame {
+  /// The close code.
+  pub code: u16,
+  /// The close reason.
+  pub reason: String,
+}
+
+/// An event of the WebSocket connection.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", content = "payload")]
+#[non_exhaustive]
+pub enum WebSocketEvent {
+  /// A message was received.
+  Message(Message),
+  /// An error happened, the connection is closed.
+  Error(String),
+  /// The connection was closed.
+  Closed(Option<CloseFrame>),
+}
+
+/// The builder of a WebSocket connection.
+///
+/// # Examples
+///
+/// ```no_run
+/// use tauri::api::websocket::{Message, WebSocketBuilder, WebSocketEvent};
+///
+/// async fn run() {
+///   let (mut rx, socket) = WebSocketBuilder::new("wss://dashboard.example.com/live")
+///     .header("Authorization", "Bearer token")
+///     .connect()
+///     .await
+///     .unwrap();
+///   socket.send(Message::Text("subscribe".into())).await.unwrap();
+///   while let Some(event) = rx.recv().await {
+///     if let WebSocketEvent::Message(message) = event {
+///       println!("{:?}", message);
+///     }
+///   }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct WebSocketBuilder {
+  url: String,
+  headers: HashMap<String, String>,
+}
+
+impl WebSocketBuilder {
+  /// Creates a new connection builder to the given `ws://` or `wss://` URL.
+  pub fn new(url: impl Into<String>) -> Self {
+    Self {
+      url: url.into(),
+      headers: Default::default(),
+    }
+  }
+
+  /// Adds a header to the handshake request.
+  pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+    self.headers.insert(name.into(), value.into());
+    self
+  }
+
+  /// Adds the headers to the handshake request.
+  pub fn headers(mut self, headers: HashMap<String, String>) -> Self {
+    self.headers.extend(headers);
+    self
+  }
+
+  /// Connects to the server.
+  ///
+  /// Returns the receiver of the connection events and the handle to send messages.
+  pub async fn connect(self) -> crate::api::Result<(Receiver<WebSocketEvent>, WebSocket)> {
+    let mut request = self.url.into_client_request()?;
+    for (name, value) in self.headers {
+      request.headers_mut().insert(
+        HeaderName::from_bytes(name.as_bytes())?,
+        HeaderValue::from_str(&value)?,
+      );
+    }
+
+    let (stream, _) = tokio_tungstenite::connect_async(request).await?;
+    let (sink, mut stream) = stream.split();
+
+    let (tx, rx) = channel(16);
+    crate::async_runtime::spawn(async move {
+      let mut close_frame = None;
+      while let Some(message) = stream.next().await {
+        let event = match message {
+          Ok(tungstenite::Message::Text(text)) => WebSocketEvent::Message(Message::Text(text)),
+          Ok(tungstenite::Message::Binary(data)) => WebSocketEvent::Message(Message::Binary(data)),
+          Ok(tungstenite::Message::Close(frame)) => {
+            close_frame = frame.map(|frame| CloseFrame {
+              code: frame.code.into(),
+              reason: frame.reason.into_owned(),
+            });
+            break;
+          }
+          // pings are answered by tungstenite
+          Ok(_) => continue,
+          Err(e) => WebSocketEvent::Error(e.to_string()),
+        };
+        let is_error = matches!(event, WebSocketEvent::Error(_));
+        if tx.send(event).await.is_err() || is_error {
+          break;
+        }
+      }
+      let _ = tx.send(WebSocketEvent::Closed(close_frame)).await;
+    });
+
+    Ok((rx, WebSocket(Arc::new(Mutex::new(sink)))))
+  }
+}
+
+/// The handle of a WebSocket connection, used to send messages.
+#[derive(Clone)]
+pub struct WebSocket(Arc<Mutex<Sink>>);
+
+impl WebSocket {
+  /// Sends a message to the server.
+  pub async fn send(&self, message: Message) -> crate::api::Result<()> {
+    let message = match message {
+      Message::Text(text) => tungstenite::Message::Text(text),
+      Message::Binary(data) => tungstenite::Message::Binary(data),
+    };
+    self.0.lock().await.send(message).await?;
+    Ok(())
+  }
+
+  /// Closes the connection. The [`WebSocketEvent::Closed`] event is sent when the server acknowledges it.
+  pub async fn close(&self) -> crate::api::Result<()> {
+    self.0.lock().await.close().await?;
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use tokio::net::TcpListener;
+
+  // Echoes the messages back and replies to the `auth` message with the authorization header
+  async fn echo_server() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    crate::async_runtime::spawn(async move {
+      let (stream, _) = listener.accept().await.unwrap();
+      let mut authorization = None;
+      let mut socket = tokio_tungstenite::accept_hdr_async(
+        stream,
+        |request: &tungstenite::handshake::server::Request, response| {
+          authorization = request
+            .headers()
+            .get("Authorization")
+            .map(|value| value.to_str().unwrap().to_string());
+          Ok(response)
+        },
+      )
+      .await
+      .unwrap();
+      while let Some(Ok(message)) = socket.next().await {
+        let reply = match &message {
+          tungstenite::Message::Text(text) if text == "auth" => {
+            tungstenite::Message::Text(authorization.clone().unwrap_or_default())
+          }
+          tungstenite::Message::Close(_) => break,
+          _ => message,
+        };
+        socket.send(reply).await.unwrap();
+      }
+    });
+    format!("ws://{}", address)
+  }
+
+  #[test]
+  fn echo() {
+    crate::async_runtime::block_on(async {
+      let url = echo_server().await;
+      let (mut rx, socket) = WebSocketBuilder::new(url)
+        .header("Authorization", "Bearer token")
+        .connect()
+        .await
+        .unwrap();
+
+      socket.send(Message::Text("auth".into())).await.unwrap();
+      socket.send(Message::Binary(vec![1, 2, 3])).await.unwrap();
+      assert!(matches!(
+        rx.recv().await,
+        Some(WebSocketEvent::Message(Message::Text(text))) if text == "Bearer token"
+      ));
+      assert!(matches!(
+        rx.recv().await,
+        Some(WebSocketEvent::Message(Message::Binary(data))) if data == vec![1, 2, 3]
+      ));
+
+      socket.close().await.unwrap();
+      assert!(matches!(rx.recv().await, Some(WebSocketEvent::Closed(_))));
+    });
+  }
+}
@@ -5,6 +5,7 @@
 use crate::{
   api::assets::Assets,
   api::config::WindowUrl,
+  api::scope::FsScope,
   hooks::{InvokeHandler, OnPageLoad, PageLoadPayload, SetupHook},
   manager::{Args, WindowManager},
   plugin::{Plugin, PluginStore},
@@ -31,6 +32,10 @@ use crate::updater;
 #[cfg(feature = "menu")]
 pub(crate) type GlobalMenuEventListener<P> = Box<dyn Fn(WindowMenuEvent<P>) + Send + Sync>;
 pub(crate) type GlobalWindowEventListener<P> = Box<dyn Fn(GlobalWindowEvent<P>) + Send + Sync>;
+#[cfg(feature = "updater")]
+type ShouldCheckUpdate<P> = Box<dyn Fn(&AppHandle<P>) -> bool + Send + Sync>;
+#[cfg(feature = "updater")]
+type UpdaterHeaders<P> = Box<dyn Fn(&AppHandle<P>) -> HashMap<String, String> + Send + Sync>;
 #[cfg(feature = "system-tray")]
 type SystemTrayEventListener<P> =
   Box<dyn Fn(&AppHandle<P>, SystemTrayEvent<<P as Params>::SystemTrayMenuId>) + Send + Sync>;
@@ -222,28 +227,98 @@ impl<P: Params> App<P> {
 #[cfg(feature = "updater")]
 impl<P: Params> App<P> {
   /// Runs the updater hook with built-in dialog.
-  fn run_updater_dialog(&self, window: Window<P>) {
+  fn run_updater_dialog(
+    &self,
+    window: Window<P>,
+    pending: updater::PendingUpdate,
+    headers: Option<updater::HeadersHook>,
+  ) {
     let updater_config = self.manager.config().tauri.updater.clone();
     let package_info = self.manager.package_info().clone();
     crate::async_runtime::spawn(async move {
-      updater::check_update_with_dialog(updater_config, package_info, window).await
+      updater::check_update_with_dialog(updater_config, package_info, window, pending, headers)
+        .await
     });
   }
 
   /// Listen updater events when dialog are disabled.
-  fn listen_updater_events(&self, window: Window<P>) {
+  fn listen_updater_events(
+    &self,
+    window: Window<P>,
+    pending: updater::PendingUpdate,
+    headers: Option<updater::HeadersHook>,
+  ) {
     let updater_config = self.manager.config().tauri.updater.clone();
-    updater::listener(updater_config, self.manager.package_info().clone(), &window);
+    updater::listener(
+      updater_config,
+      self.manager.package_info().clone(),
+      &window,
+      pending,
+      headers,
+    );
+  }
+
+  /// Checks for updates periodically if a schedule is configured.
+  fn schedule_updater(
+    &self,
+    window: Window<P>,
+    pending: updater::PendingUpdate,
+    should_check_update: Option<ShouldCheckUpdate<P>>,
+  ) {
+    if let Some(schedule) = self.manager.config().tauri.updater.schedule.clone() {
+      let app_handle = self.handle();
+      let scheduled = updater::schedule(schedule, window.clone(), pending, move || {
+        should_check_update
+          .as_ref()
+          .map(|should_check| should_check(&app_handle))
+          .unwrap_or(true)
+      });
+      // the checks are triggered on the window, so they stop with it
+      if let Some(scheduled) = scheduled {
+        window.on_window_event(move |event| {
+          if let WindowEvent::Destroyed = event {
+            scheduled.abort();
+          }
+        });
+      }
+    }
+  }
+
+  /// Listen the launch confirmation of the installed update.
+  fn listen_confirm_launch(&self, window: &Window<P>) {
+    let event_window = window.clone();
+    window.listen(
+      updater::EVENT_CONFIRM_LAUNCH
+        .parse::<P::Event>()
+        .unwrap_or_else(|_| panic!("bad label")),
+      move |_msg| {
+        let _ = updater::confirm_launch(&event_window);
+      },
+    );
   }
 
-  fn run_updater(&self, main_window: Option<Window<P>>) {
+  fn run_updater(
+    &self,
+    main_window: Option<Window<P>>,
+    should_check_update: Option<ShouldCheckUpdate<P>>,
+    updater_headers: Option<UpdaterHeaders<P>>,
+  ) {
     if let Some(main_window) = main_window {
       let event_window = main_window.clone();
       let updater_config = self.manager.config().tauri.updater.clone();
+      let pending = updater::PendingUpdate::default();
+      let headers = updater_headers.map(|updater_headers| {
+        let app_handle = self.handle();
+        Arc::new(move || updater_headers(&app_handle)) as updater::HeadersHook
+      });
+      if updater_config.active {
+        self.listen_confirm_launch(&main_window);
+        self.schedule_updater(main_window.clone(), pending.clone(), should_check_update);
+      }
       // check if updater is active or not
       if updater_config.dialog && updater_config.active {
         // if updater dialog is enabled spawn a new task
-        self.run_updater_dialog(main_window.clone());
+        self.run_updater_dialog(main_window.clone(), pending.clone(), headers.clone());
         let config = self.manager.config().tauri.updater.clone();
         let package_info = self.manager.package_info().clone();
         // When dialog is enabled, if user want to recheck
@@ -257,12 +332,15 @@ impl<P: Params> App<P> {
             let window = event_window.clone();
             let package_info = package_info.clone();
             let config = config.clone();
+            let pending = pending.clone();
+            let headers = headers.clone();
             // re-spawn task inside tokyo to launch the download
             // we don't need to emit anything as everything is handled
             // by the process (user is asked to restart at the end)
             // and it's handled by the updater
             crate::async_runtime::spawn(async move {
-              updater::check_update_with_dialog(config, package_info, window).await
+              updater::check_update_with_dialog(config, package_info, window, pending, headers)
+                .await
             });
           },
         );
@@ -272,7 +350,7 @@ impl<P: Params> App<P> {
         // if there is a new update we emit `tauri://update-available` with details
         // this is the user responsabilities to display dialog and ask if user want to install
         // to install the update you need to invoke the Event `tauri://update-install`
-        self.listen_updater_events(main_window);
+        self.listen_updater_events(main_window, pending, headers);
       }
     }
   }
@@ -329,6 +407,14 @@ where
   #[cfg(feature = "system-tray")]
   system_tray_event_listeners: Vec<SystemTrayEventListener<Args<E, L, MID, TID, A, R>>>,
 
+  /// Decides if a scheduled update check should run.
+  #[cfg(feature = "updater")]
+  should_check_update: Option<ShouldCheckUpdate<Args<E, L, MID, TID, A, R>>>,
+
+  /// Computes the headers added to the update requests.
+  #[cfg(feature = "updater")]
+  updater_headers: Option<UpdaterHeaders<Args<E, L, MID, TID, A, R>>>,
+
   #[cfg(shell_execute)]
   cleanup_on_drop: bool,
 }
@@ -361,6 +447,10 @@ where
       system_tray: Vec::new(),
       #[cfg(feature = "system-tray")]
       system_tray_event_listeners: Vec::new(),
+      #[cfg(feature = "updater")]
+      should_check_update: None,
+      #[cfg(feature = "updater")]
+      updater_headers: None,
       #[cfg(shell_execute)]
       cleanup_on_drop: true,
     }
@@ -564,6 +654,34 @@ where
     self
   }
 
+  /// Decides if a scheduled update check should run, e.g. only on unmetered networks.
+  /// See the `schedule` option of the updater configuration.
+  #[cfg(feature = "updater")]
+  #[cfg_attr(doc_cfg, doc(cfg(feature = "updater")))]
+  pub fn should_check_update<
+    F: Fn(&AppHandle<Args<E, L, MID, TID, A, R>>) -> bool + Send + Sync + 'static,
+  >(
+    mut self,
+    should_check: F,
+  ) -> Self {
+    self.should_check_update = Some(Box::new(should_check));
+    self
+  }
+
+  /// Computes headers added to the update manifest and download requests on each check,
+  /// e.g. an `Authorization` header with a license key from the app state.
+  #[cfg(feature = "updater")]
+  #[cfg_attr(doc_cfg, doc(cfg(feature = "updater")))]
+  pub fn updater_headers<
+    F: Fn(&AppHandle<Args<E, L, MID, TID, A, R>>) -> HashMap<String, String> + Send + Sync + 'static,
+  >(
+    mut self,
+    headers: F,
+  ) -> Self {
+    self.updater_headers = Some(Box::new(headers));
+    self
+  }
+
   /// Registers a URI scheme protocol available to all webviews.
   /// Leverages [setURLSchemeHandler](https://developer.apple.com/documentation/webkit/wkwebviewconfiguration/2875766-seturlschemehandler) on macOS,
   /// [AddWebResourceRequestedFilter](https://docs.microsoft.com/en-us/dotnet/api/microsoft.web.webview2.core.corewebview2.addwebresourcerequestedfilter?view=webview2-dotnet-1.0.774.44) on Windows
@@ -629,6 +747,17 @@ where
       icon
     };
 
+    // the fs and websocket scopes are compiled once and shared by the windows
+    self
+      .state
+      .set(FsScope::for_fs_api(&context.config, &context.package_info)?);
+    #[cfg(websocket_all)]
+    self
+      .state
+      .set(crate::api::scope::UrlScope::for_websocket_api(
+        &context.config,
+      )?);
+
     let manager = WindowManager::with_handlers(
       context,
       self.plugins,
@@ -656,6 +785,10 @@ where
       ));
     }
 
+    // restore the previous version if the installed update didn't confirm its launch
+    #[cfg(feature = "updater")]
+    updater::check_rollback(&manager.config(), manager.package_info());
+
     let mut app = App {
       runtime: Some(R::new()?),
       manager,
@@ -685,7 +818,7 @@ where
     }
 
     #[cfg(feature = "updater")]
-    app.run_updater(main_window);
+    app.run_updater(main_window, self.should_check_update, self.updater_headers);
 
     (self.setup)(&mut app).map_err(|e| crate::Error::Setup(e))?;
 
@@ -8,11 +8,11 @@
 //! For command handlers, it's recommended to use a plain `async fn` command.
 
 use once_cell::sync::OnceCell;
-use tokio::runtime::Runtime;
 pub use tokio::sync::{
   mpsc::{channel, Receiver, Sender},
   Mutex, RwLock,
 };
+use tokio::{runtime::Runtime, task::JoinHandle};
 
 use std::future::Future;
 
@@ -26,10 +26,19 @@ pub fn block_on<F: Future>(task: F) -> F::Output {
 
 /// Spawn a future onto the runtime.
 pub fn spawn<F>(task: F)
+where
+  F: Future + Send + 'static,
+  F::Output: Send + 'static,
+{
+  spawn_task(task);
+}
+
+/// Spawn a future onto the runtime, returning the handle used to await or abort it.
+pub(crate) fn spawn_task<F>(task: F) -> JoinHandle<F::Output>
 where
   F: Future + Send + 'static,
   F::Output: Send + 'static,
 {
   let runtime = RUNTIME.get_or_init(|| Runtime::new().unwrap());
-  runtime.spawn(task);
+  runtime.spawn(task)
 }
@@ -22,8 +22,11 @@ mod global_shortcut;
 mod http;
 mod internal;
 mod notification;
+mod path;
 mod process;
+mod resources;
 mod shell;
+mod websocket;
 mod window;
 
 /// The response for a JS `invoke` call.
@@ -45,6 +48,7 @@ enum Module {
   App(app::Cmd),
   Process(process::Cmd),
   Fs(file_system::Cmd),
+  Path(path::Cmd),
   Window(Box<window::Cmd>),
   Shell(shell::Cmd),
   Event(event::Cmd),
@@ -54,6 +58,7 @@ enum Module {
   Notification(notification::Cmd),
   Http(http::Cmd),
   GlobalShortcut(global_shortcut::Cmd),
+  Websocket(websocket::Cmd),
 }
 
 impl Module {
@@ -75,10 +80,12 @@ impl Module {
         .respond_async(async move { cmd.run().and_then(|r| r.json).map_err(InvokeError::from) }),
       Self::Fs(cmd) => resolver.respond_async(async move {
         cmd
-          .run(config, &package_info)
+          .run(window, config, &package_info)
           .and_then(|r| r.json)
           .map_err(InvokeError::from)
       }),
+      Self::Path(cmd) => resolver
+        .respond_async(async move { cmd.run().and_then(|r| r.json).map_err(InvokeError::from) }),
       Self::Window(cmd) => resolver.respond_async(async move {
         cmd
           .run(window)
@@ -104,28 +111,14 @@ impl Module {
           .and_then(|r| r.json)
           .map_err(InvokeError::from)
       }),
-      // on macOS, the dialog must run on another thread: https://github.com/rust-windowing/winit/issues/1779
-      // we do the same on Windows just to stay consistent with `tao` (and it also improves UX because of the event loop)
-      #[cfg(not(target_os = "linux"))]
+      // the dialogs run on their own thread, so they don't block the event loop
       Self::Dialog(cmd) => resolver.respond_async(async move {
         cmd
           .run(window)
+          .await
           .and_then(|r| r.json)
           .map_err(InvokeError::from)
       }),
-      // on Linux, the dialog must run on the main thread.
-      #[cfg(target_os = "linux")]
-      Self::Dialog(cmd) => {
-        let window_ = window.clone();
-        let _ = window.run_on_main_thread(move || {
-          resolver.respond_closure(move || {
-            cmd
-              .run(window_)
-              .and_then(|r| r.json)
-              .map_err(InvokeError::from)
-          })
-        });
-      }
       Self::Cli(cmd) => {
         if let Some(cli_config) = config.tauri.cli.clone() {
           resolver.respond_async(async move {
@@ -136,15 +129,16 @@ impl Module {
           })
         }
       }
-      Self::Notification(cmd) => resolver.respond_closure(move || {
+      Self::Notification(cmd) => resolver.respond_async(async move {
         cmd
-          .run(config, &package_info)
+          .run(window, config, &package_info)
+          .await
           .and_then(|r| r.json)
           .map_err(InvokeError::from)
       }),
       Self::Http(cmd) => resolver.respond_async(async move {
         cmd
-          .run()
+          .run(window, config, package_info)
           .await
           .and_then(|r| r.json)
           .map_err(InvokeError::from)
@@ -155,6 +149,13 @@ impl Module {
           .and_then(|r| r.json)
           .map_err(InvokeError::from)
       }),
+      Self::Websocket(cmd) => resolver.respond_async(async move {
+        cmd
+          .run(window, config)
+          .await
+          .and_then(|r| r.json)
+          .map_err(InvokeError::from)
+      }),
     }
   }
 }
@@ -3,12 +3,17 @@
 // SPDX-License-Identifier: MIT
 
 use super::InvokeResponse;
-#[cfg(any(dialog_open, dialog_save))]
-use crate::api::dialog::FileDialogBuilder;
 use crate::{
-  api::dialog::{ask as ask_dialog, message as message_dialog, AskResponse},
+  api::dialog::{
+    MessageDialogBuilder, MessageDialogButtons, MessageDialogKind, MessageDialogResult,
+  },
   Params, Window,
 };
+#[cfg(any(dialog_open, dialog_save))]
+use crate::{
+  api::{dialog::FileDialogBuilder, scope::FsScope},
+  Manager,
+};
 use serde::Deserialize;
 
 use std::path::PathBuf;
@@ -34,8 +39,17 @@ pub struct OpenDialogOptions {
   /// Whether the dialog is a directory selection (`true` value) or file selection (`false` value).
   #[serde(default)]
   pub directory: bool,
-  /// The initial path of the dialog.
+  /// The initial directory or file path of the dialog.
   pub default_path: Option<PathBuf>,
+  /// The title of the dialog. Currently ignored, see [`FileDialogBuilder::set_title`].
+  pub title: Option<String>,
+  /// Whether the user can create directories from the dialog.
+  /// Currently ignored, see [`FileDialogBuilder::set_can_create_directories`].
+  #[serde(default)]
+  pub can_create_directories: bool,
+  /// Whether the dialog shows hidden files. Currently ignored, see [`FileDialogBuilder::set_show_hidden_files`].
+  #[serde(default)]
+  pub show_hidden_files: bool,
 }
 
 /// The options for the save dialog API.
@@ -45,8 +59,73 @@ pub struct SaveDialogOptions {
   /// The filters of the dialog.
   #[serde(default)]
   pub filters: Vec<DialogFilter>,
-  /// The initial path of the dialog.
+  /// The initial directory or file path of the dialog. A file path doesn't need to exist if its directory does.
   pub default_path: Option<PathBuf>,
+  /// The initial file name of the dialog, overriding the file name of `default_path`.
+  pub default_file_name: Option<String>,
+  /// The title of the dialog. Currently ignored, see [`FileDialogBuilder::set_title`].
+  pub title: Option<String>,
+  /// Whether the user can create directories from the dialog.
+  /// Currently ignored, see [`FileDialogBuilder::set_can_create_directories`].
+  #[serde(default)]
+  pub can_create_directories: bool,
+  /// Whether the dialog shows hidden files. Currently ignored, see [`FileDialogBuilder::set_show_hidden_files`].
+  #[serde(default)]
+  pub show_hidden_files: bool,
+}
+
+/// The kind of a message dialog.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DialogKind {
+  /// An information dialog.
+  Info,
+  /// A warning dialog.
+  Warning,
+  /// An error dialog.
+  Error,
+}
+
+impl From<DialogKind> for MessageDialogKind {
+  fn from(kind: DialogKind) -> Self {
+    match kind {
+      DialogKind::Info => Self::Info,
+      DialogKind::Warning => Self::Warning,
+      DialogKind::Error => Self::Error,
+    }
+  }
+}
+
+/// The buttons of a message dialog.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DialogButtons {
+  /// An "Ok" button.
+  Ok,
+  /// "Ok" and "Cancel" buttons.
+  OkCancel,
+  /// "Yes" and "No" buttons.
+  YesNo,
+}
+
+impl From<DialogButtons> for MessageDialogButtons {
+  fn from(buttons: DialogButtons) -> Self {
+    match buttons {
+      DialogButtons::Ok => Self::Ok,
+      DialogButtons::OkCancel => Self::OkCancel,
+      DialogButtons::YesNo => Self::YesNo,
+    }
+  }
+}
+
+/// The options for the message dialog API.
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageDialogOptions {
+  /// The kind of the dialog, defaults to `info`.
+  pub kind: Option<DialogKind>,
+  /// The buttons of the dialog, defaults to `ok`.
+  pub buttons: Option<DialogButtons>,
 }
 
 /// The API descriptor.
@@ -54,15 +133,14 @@ pub struct SaveDialogOptions {
 #[serde(tag = "cmd", rename_all = "camelCase")]
 pub enum Cmd {
   /// The open dialog API.
-  OpenDialog {
-    options: OpenDialogOptions,
-  },
+  OpenDialog { options: OpenDialogOptions },
   /// The save dialog API.
-  SaveDialog {
-    options: SaveDialogOptions,
-  },
+  SaveDialog { options: SaveDialogOptions },
+  /// The message dialog API, returning the pressed button.
   MessageDialog {
+    title: Option<String>,
     message: String,
+    options: Option<MessageDialogOptions>,
   },
   AskDialog {
     title: Option<String>,
@@ -72,83 +150,66 @@ pub enum Cmd {
 
 impl Cmd {
   #[allow(unused_variables)]
-  pub fn run<P: Params>(self, window: Window<P>) -> crate::Result<InvokeResponse> {
+  pub async fn run<P: Params>(self, window: Window<P>) -> crate::Result<InvokeResponse> {
     match self {
       #[cfg(dialog_open)]
-      Self::OpenDialog { options } => open(window, options),
+      Self::OpenDialog { options } => open(window, options).await,
       #[cfg(not(dialog_open))]
       Self::OpenDialog { .. } => Err(crate::Error::ApiNotAllowlisted("dialog > open".to_string())),
 
       #[cfg(dialog_save)]
-      Self::SaveDialog { options } => save(window, options),
+      Self::SaveDialog { options } => save(window, options).await,
       #[cfg(not(dialog_save))]
       Self::SaveDialog { .. } => Err(crate::Error::ApiNotAllowlisted("dialog > save".to_string())),
 
-      Self::MessageDialog { message } => {
-        let exe = std::env::current_exe()?;
-        let app_name = exe
-          .file_stem()
-          .expect("failed to get binary filename")
-          .to_string_lossy()
-          .to_string();
-        message_dialog(app_name, message);
-        Ok(().into())
+      Self::MessageDialog {
+        title,
+        message,
+        options,
+      } => {
+        let title = match title {
+          Some(title) => title,
+          None => default_title()?,
+        };
+        show_message(window, title, message, options.unwrap_or_default()).await
       }
       Self::AskDialog { title, message } => {
-        let exe = std::env::current_exe()?;
-        let answer = ask(
-          title.unwrap_or_else(|| {
-            exe
-              .file_stem()
-              .expect("failed to get binary filename")
-              .to_string_lossy()
-              .to_string()
-          }),
-          message,
-        )?;
-        Ok(answer)
+        let title = match title {
+          Some(title) => title,
+          None => default_title()?,
+        };
+        ask(window, title, message).await
       }
     }
   }
 }
 
-#[cfg(all(target_os = "linux", any(dialog_open, dialog_save)))]
-fn set_default_path(dialog_builder: FileDialogBuilder, default_path: PathBuf) -> FileDialogBuilder {
-  if default_path.is_file() {
-    dialog_builder.set_file_name(&default_path.to_string_lossy().to_string())
-  } else {
-    dialog_builder.set_directory(default_path)
-  }
-}
-
-#[cfg(all(any(windows, target_os = "macos"), any(dialog_open, dialog_save)))]
+// Starts the dialog on the default path: a directory is opened and a file is selected in its directory.
+#[cfg(any(dialog_open, dialog_save))]
 fn set_default_path(
   mut dialog_builder: FileDialogBuilder,
   default_path: PathBuf,
 ) -> FileDialogBuilder {
-  if default_path.is_file() {
-    if let Some(parent) = default_path.parent() {
+  if default_path.is_dir() {
+    return dialog_builder.set_directory(default_path);
+  }
+  if let Some(parent) = default_path.parent() {
+    if !parent.as_os_str().is_empty() {
       dialog_builder = dialog_builder.set_directory(parent);
     }
-    dialog_builder = dialog_builder.set_file_name(
-      &default_path
-        .file_name()
-        .unwrap()
-        .to_string_lossy()
-        .to_string(),
-    );
-    dialog_builder
-  } else {
-    dialog_builder.set_directory(default_path)
   }
+  if let Some(file_name) = default_path.file_name() {
+    dialog_builder = dialog_builder.set_file_name(&file_name.to_string_lossy());
+  }
+  dialog_builder
 }
 
-#[cfg(windows)]
+#[cfg(all(windows, any(dialog_open, dialog_save)))]
 struct WindowParent {
   hwnd: *mut std::ffi::c_void,
 }
 
-#[cfg(windows)]
+#[cfg(all(windows, any(dialog_open, dialog_save)))]
 unsafe impl raw_window_handle::HasRawWindowHandle for WindowParent {
   fn raw_window_handle(&self) -> raw_window_handle::RawWindowHandle {
     let mut handle = raw_window_handle::windows::WindowsHandle::empty();
@@ -157,8 +218,8 @@ unsafe impl raw_window_handle::HasRawWindowHandle for WindowParent {
   }
 }
 
-#[cfg(windows)]
-fn parent<P: Params>(window: Window<P>) -> crate::Result<WindowParent> {
+#[cfg(all(windows, any(dialog_open, dialog_save)))]
+fn parent<P: Params>(window: &Window<P>) -> crate::Result<WindowParent> {
   Ok(WindowParent {
     hwnd: window.hwnd()?,
   })
@@ -167,14 +228,14 @@ fn parent<P: Params>(window: Window<P>) -> crate::Result<WindowParent> {
 /// Shows an open dialog.
 #[cfg(dialog_open)]
 #[allow(unused_variables)]
-pub fn open<P: Params>(
+pub async fn open<P: Params>(
   window: Window<P>,
   options: OpenDialogOptions,
 ) -> crate::Result<InvokeResponse> {
   let mut dialog_builder = FileDialogBuilder::new();
   #[cfg(windows)]
   {
-    dialog_builder = dialog_builder.set_parent(&parent(window)?);
+    dialog_builder = dialog_builder.set_parent(&parent(&window)?);
   }
   if let Some(default_path) = options.default_path {
     if !default_path.exists() {
@@ -182,16 +243,37 @@ pub fn open<P: Params>(
     }
     dialog_builder = set_default_path(dialog_builder, default_path);
   }
+  if let Some(title) = options.title {
+    dialog_builder = dialog_builder.set_title(title);
+  }
+  dialog_builder = dialog_builder
+    .set_can_create_directories(options.can_create_directories)
+    .set_show_hidden_files(options.show_hidden_files);
   for filter in options.filters {
     let extensions: Vec<&str> = filter.extensions.iter().map(|s| &**s).collect();
     dialog_builder = dialog_builder.add_filter(filter.name, &extensions);
   }
+  // the picked paths can be accessed by the window's fs APIs until it's closed
+  let scope = window.state::<FsScope>();
+  let label = window.label().to_string();
   let response = if options.directory {
-    dialog_builder.pick_folder().into()
+    let folder = dialog_builder.pick_folder_async().await?;
+    for path in &folder {
+      scope.allow_directory(&label, path);
+    }
+    folder.into()
   } else if options.multiple {
-    dialog_builder.pick_files().into()
+    let files = dialog_builder.pick_files_async().await?;
+    for path in files.iter().flatten() {
+      scope.allow_file(&label, path);
+    }
+    files.into()
   } else {
-    dialog_builder.pick_file().into()
+    let file = dialog_builder.pick_file_async().await?;
+    for path in &file {
+      scope.allow_file(&label, path);
+    }
+    file.into()
   };
   Ok(response)
 }
@@ -199,32 +281,124 @@ pub fn open<P: Params>(
 /// Shows a save dialog.
 #[cfg(dialog_save)]
 #[allow(unused_variables)]
-pub fn save<P: Params>(
+pub async fn save<P: Params>(
   window: Window<P>,
   options: SaveDialogOptions,
 ) -> crate::Result<InvokeResponse> {
   let mut dialog_builder = FileDialogBuilder::new();
   #[cfg(windows)]
   {
-    dialog_builder = dialog_builder.set_parent(&parent(window)?);
+    dialog_builder = dialog_builder.set_parent(&parent(&window)?);
   }
   if let Some(default_path) = options.default_path {
-    if !default_path.exists() {
+    // the file to save usually doesn't exist yet, but its directory must
+    let directory_exists = default_path.exists()
+      || default_path.parent().map_or(false, |parent| {
+        parent.as_os_str().is_empty() || parent.is_dir()
+      });
+    if !directory_exists {
       return Err(crate::Error::DialogDefaultPathNotExists(default_path));
     }
     dialog_builder = set_default_path(dialog_builder, default_path);
   }
+  if let Some(file_name) = options.default_file_name {
+    dialog_builder = dialog_builder.set_file_name(&file_name);
+  }
+  if let Some(title) = options.title {
+    dialog_builder = dialog_builder.set_title(title);
+  }
+  dialog_builder = dialog_builder
+    .set_can_create_directories(options.can_create_directories)
+    .set_show_hidden_files(options.show_hidden_files);
   for filter in options.filters {
     let extensions: Vec<&str> = filter.extensions.iter().map(|s| &**s).collect();
     dialog_builder = dialog_builder.add_filter(filter.name, &extensions);
   }
-  Ok(dialog_builder.save_file().into())
+  let path = dialog_builder.save_file_async().await?;
+  if let Some(path) = &path {
+    window
+      .state::<FsScope>()
+      .allow_file(&window.label().to_string(), path);
+  }
+  Ok(path.into())
+}
+
+// The dialog title when none is given, the binary name
+fn default_title() -> crate::Result<String> {
+  let exe = std::env::current_exe()?;
+  Ok(
+    exe
+      .file_stem()
+      .expect("failed to get binary filename")
+      .to_string_lossy()
+      .to_string(),
+  )
+}
+
+// Shows the message dialog without blocking the event loop
+#[cfg(dialog_async)]
+async fn show<P: Params>(
+  _window: &Window<P>,
+  dialog_builder: MessageDialogBuilder,
+) -> crate::Result<MessageDialogResult> {
+  dialog_builder.show_async().await.map_err(Into::into)
+}
+
+// Without the non-blocking dialogs, the dialog is shown on the main thread, where GTK runs on Linux
+#[cfg(not(dialog_async))]
+async fn show<P: Params>(
+  window: &Window<P>,
+  dialog_builder: MessageDialogBuilder,
+) -> crate::Result<MessageDialogResult> {
+  let (tx, rx) = tokio::sync::oneshot::channel();
+  window.run_on_main_thread(move || {
+    let _ = tx.send(dialog_builder.show());
+  })?;
+  rx.await
+    .map_err(|_| crate::api::Error::Dialog("the dialog failed to return a result".into()).into())
+}
+
+/// Shows a message dialog, returning the pressed button: `ok`, `cancel`, `yes` or `no`.
+#[allow(unused_variables)]
+pub async fn show_message<P: Params>(
+  window: Window<P>,
+  title: String,
+  message: String,
+  options: MessageDialogOptions,
+) -> crate::Result<InvokeResponse> {
+  let mut dialog_builder = MessageDialogBuilder::new(title, message);
+  #[cfg(all(windows, any(dialog_open, dialog_save)))]
+  {
+    dialog_builder = dialog_builder.parent(&parent(&window)?);
+  }
+  if let Some(kind) = options.kind {
+    dialog_builder = dialog_builder.kind(kind.into());
+  }
+  if let Some(buttons) = options.buttons {
+    dialog_builder = dialog_builder.buttons(buttons.into());
+  }
+  let button = match show(&window, dialog_builder).await? {
+    MessageDialogResult::Ok => "ok",
+    MessageDialogResult::Cancel => "cancel",
+    MessageDialogResult::Yes => "yes",
+    MessageDialogResult::No => "no",
+  };
+  Ok(button.into())
 }
 
 /// Shows a dialog with a yes/no question.
-pub fn ask(title: String, message: String) -> crate::Result<InvokeResponse> {
-  match ask_dialog(title, message) {
-    AskResponse::Yes => Ok(true.into()),
-    _ => Ok(false.into()),
+#[allow(unused_variables)]
+pub async fn ask<P: Params>(
+  window: Window<P>,
+  title: String,
+  message: String,
+) -> crate::Result<InvokeResponse> {
+  let mut dialog_builder =
+    MessageDialogBuilder::new(title, message).buttons(MessageDialogButtons::YesNo);
+  #[cfg(all(windows, any(dialog_open, dialog_save)))]
+  {
+    dialog_builder = dialog_builder.parent(&parent(&window)?);
   }
+  let answer = show(&window, dialog_builder).await?;
+  Ok((answer == MessageDialogResult::Yes).into())
 }
@@ -2,25 +2,78 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-License-Identifier: MIT
 
-use super::InvokeResponse;
+use super::{
+  resources::{ResourceId, ResourceTable},
+  InvokeResponse,
+};
 use crate::{
   api::{
     dir, file,
     path::{resolve_path, BaseDirectory},
+    scope::FsScope,
   },
-  Config, PackageInfo,
+  Config, Manager, PackageInfo, Params, Window,
 };
 
+use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 
-use std::{fs, fs::File, io::Write, path::PathBuf, sync::Arc};
+use std::{
+  fs,
+  fs::File,
+  io::{Read, Seek, SeekFrom, Write},
+  path::PathBuf,
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+  },
+};
+
+// the files opened with the `open` API
+static FILE_HANDLES: Lazy<ResourceTable<File>> = Lazy::new(Default::default);
+
+// the directory streams created with the `streamDir` API
+#[cfg(fs_read_dir)]
+static DIR_STREAMS: Lazy<ResourceTable<DirStream>> = Lazy::new(Default::default);
+
+// serializes the updates of the trash record
+#[cfg(fs_trash)]
+static TRASH_RECORD: Lazy<std::sync::Mutex<()>> = Lazy::new(Default::default);
+
+// the watchers created with the `watch` API
+#[cfg(fs_watch)]
+static WATCHERS: Lazy<ResourceTable<crate::api::fs::FsWatcher>> = Lazy::new(Default::default);
 
 /// The options for the directory functions on the file system API.
 #[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct DirOperationOptions {
   /// Whether the API should recursively perform the operation on the directory.
   #[serde(default)]
   pub recursive: bool,
+  /// Whether the `readDir` API should include the metadata of the entries.
+  #[serde(default)]
+  pub metadata: bool,
+  /// The maximum depth of a recursive `readDir`.
+  pub max_depth: Option<usize>,
+  /// The globs of the files listed by `readDir`.
+  #[serde(default)]
+  pub include: Vec<String>,
+  /// The globs of the files and directories skipped by `readDir`.
+  #[serde(default)]
+  pub exclude: Vec<String>,
+  /// Whether `readDir` skips the files ignored by the `.gitignore` files.
+  #[serde(default)]
+  pub gitignore: bool,
+  /// Whether `readDir` lists the hidden files.
+  #[serde(default = "default_true")]
+  pub hidden: bool,
+  /// How `readDir` handles the symlinks.
+  #[serde(default)]
+  pub symlinks: dir::SymlinkPolicy,
+  /// Whether the `removeDir` API moves the directory to the trash.
+  #[serde(default)]
+  pub trash: bool,
   /// The base directory of the operation.
   /// The directory path of the BaseDirectory will be the prefix of the defined directory path.
   pub dir: Option<BaseDirectory>,
@@ -32,6 +85,137 @@ pub struct FileOperationOptions {
   /// The base directory of the operation.
   /// The directory path of the BaseDirectory will be the prefix of the defined file path.
   pub dir: Option<BaseDirectory>,
+  /// Whether the `removeFile` API moves the file to the trash.
+  #[serde(default)]
+  pub trash: bool,
+}
+
+/// The options of the write file APIs.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WriteFileOptions {
+  /// The base directory of the operation.
+  /// The directory path of the BaseDirectory will be the prefix of the defined file path.
+  pub dir: Option<BaseDirectory>,
+  /// Appends the contents to the file.
+  #[serde(default)]
+  pub append: bool,
+  /// Fails if the file already exists.
+  #[serde(default)]
+  pub create_new: bool,
+  /// Writes to a temporary file, syncs it and renames it to the path.
+  #[serde(default)]
+  pub atomic: bool,
+}
+
+#[cfg(any(fs_write_file, fs_write_binary_file))]
+impl WriteFileOptions {
+  fn write_options(&self) -> file::WriteOptions {
+    file::WriteOptions::new()
+      .append(self.append)
+      .create_new(self.create_new)
+      .atomic(self.atomic)
+  }
+}
+
+/// The options of the open API.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenOptions {
+  /// The base directory of the file.
+  pub dir: Option<BaseDirectory>,
+  /// Open the file for reading. Defaults to true.
+  #[serde(default = "default_true")]
+  pub read: bool,
+  /// Open the file for writing.
+  #[serde(default)]
+  pub write: bool,
+  /// Open the file for appending, all writes go to the end of the file.
+  #[serde(default)]
+  pub append: bool,
+  /// Truncate the file when it's opened.
+  #[serde(default)]
+  pub truncate: bool,
+  /// Create the file if it doesn't exist.
+  #[serde(default)]
+  pub create: bool,
+  /// Create the file, failing if it already exists.
+  #[serde(default)]
+  pub create_new: bool,
+}
+
+impl Default for OpenOptions {
+  fn default() -> Self {
+    Self {
+      dir: None,
+      read: true,
+      write: false,
+      append: false,
+      truncate: false,
+      create: false,
+      create_new: false,
+    }
+  }
+}
+
+fn default_true() -> bool {
+  true
+}
+
+/// The options of the watch API.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchOptions {
+  /// The base directory of the watched path.
+  pub dir: Option<BaseDirectory>,
+  /// Whether the subdirectories are also watched.
+  #[serde(default)]
+  pub recursive: bool,
+  /// The debounce delay in milliseconds. Defaults to 500.
+  pub debounce: Option<u64>,
+}
+
+/// A directory stream created with the `streamDir` API, the walk stops when it's dropped.
+#[derive(Default)]
+pub struct DirStream(Arc<AtomicBool>);
+
+impl Drop for DirStream {
+  fn drop(&mut self) {
+    self.0.store(true, Ordering::Relaxed);
+  }
+}
+
+/// An event of a directory stream.
+#[derive(Serialize)]
+#[serde(tag = "type", content = "payload", rename_all = "camelCase")]
+pub enum DirStreamEvent {
+  /// The next entries of the directory, a directory is followed by its entries.
+  Page(Vec<dir::DiskEntry>),
+  /// Reading the directory failed, the stream is closed.
+  Error(String),
+  /// All the entries were sent.
+  Done,
+}
+
+/// The origin of a seek.
+#[derive(Deserialize)]
+pub enum SeekMode {
+  /// Seek from the start of the file.
+  Start,
+  /// Seek from the current position.
+  Current,
+  /// Seek from the end of the file.
+  End,
+}
+
+/// A buffer written to a file handle.
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub enum Buffer {
+  /// A UTF-8 string.
+  Text(String),
+  /// A byte array.
+  Raw(Vec<u8>),
 }
 
 /// The API descriptor.
@@ -52,13 +236,13 @@ pub enum Cmd {
   WriteFile {
     path: PathBuf,
     contents: String,
-    options: Option<FileOperationOptions>,
+    options: Option<WriteFileOptions>,
   },
   /// The write binary file API.
   WriteBinaryFile {
     path: PathBuf,
     contents: String,
-    options: Option<FileOperationOptions>,
+    options: Option<WriteFileOptions>,
   },
   /// The read dir API.
   ReadDir {
@@ -98,19 +282,92 @@ pub enum Cmd {
     path: String,
     directory: Option<BaseDirectory>,
   },
+  /// Opens a file handle.
+  Open {
+    path: PathBuf,
+    options: Option<OpenOptions>,
+  },
+  /// Reads up to `len` bytes from a file handle.
+  Read { rid: ResourceId, len: usize },
+  /// Writes to a file handle.
+  Write { rid: ResourceId, data: Buffer },
+  /// Moves the cursor of a file handle.
+  Seek {
+    rid: ResourceId,
+    offset: i64,
+    whence: SeekMode,
+  },
+  /// Truncates or extends the file of a file handle.
+  Truncate { rid: ResourceId, len: u64 },
+  /// Closes a file handle.
+  Close { rid: ResourceId },
+  /// Watches a path, the events are sent to `on_event_fn`.
+  #[serde(rename_all = "camelCase")]
+  Watch {
+    path: PathBuf,
+    options: Option<WatchOptions>,
+    on_event_fn: String,
+  },
+  /// Stops a watcher.
+  Unwatch { rid: ResourceId },
+  /// Streams the entries of a directory to `on_event_fn`.
+  #[serde(rename_all = "camelCase")]
+  StreamDir {
+    path: PathBuf,
+    options: Option<DirOperationOptions>,
+    page_size: Option<usize>,
+    on_event_fn: String,
+  },
+  /// Stops a directory stream.
+  CancelStreamDir { rid: ResourceId },
+  /// Lists the trash items created by the app.
+  ListTrash,
+  /// Restores a trash item created by the app.
+  RestoreTrash { id: String },
+  /// The extract archive API.
+  ExtractArchive {
+    source: PathBuf,
+    destination: PathBuf,
+    options: Option<FileOperationOptions>,
+  },
+  /// The create archive API, the progress is sent to `on_progress_fn`.
+  #[serde(rename_all = "camelCase")]
+  CreateArchive {
+    destination: PathBuf,
+    paths: Vec<PathBuf>,
+    options: Option<FileOperationOptions>,
+    on_progress_fn: Option<String>,
+  },
+  /// The metadata API.
+  Metadata {
+    path: PathBuf,
+    options: Option<FileOperationOptions>,
+  },
+  /// The exists API.
+  Exists {
+    path: PathBuf,
+    options: Option<FileOperationOptions>,
+  },
+  /// The set permissions API.
+  SetPermissions {
+    path: PathBuf,
+    permissions: file::Permissions,
+    options: Option<FileOperationOptions>,
+  },
 }
 
 impl Cmd {
   #[allow(unused_variables)]
-  pub fn run(
+  pub fn run<P: Params>(
     self,
+    window: Window<P>,
     config: Arc<Config>,
     package_info: &PackageInfo,
   ) -> crate::Result<InvokeResponse> {
     match self {
       #[cfg(fs_read_text_file)]
       Self::ReadTextFile { path, options } => {
-        read_text_file(&config, package_info, path, options).map(Into::into)
+        read_text_file(&window, &config, package_info, path, options).map(Into::into)
       }
       #[cfg(not(fs_read_text_file))]
       Self::ReadTextFile { .. } => Err(crate::Error::ApiNotAllowlisted(
@@ -119,7 +376,7 @@ impl Cmd {
 
       #[cfg(fs_read_binary_file)]
       Self::ReadBinaryFile { path, options } => {
-        read_binary_file(&config, package_info, path, options).map(Into::into)
+        read_binary_file(&window, &config, package_info, path, options).map(Into::into)
       }
       #[cfg(not(fs_read_binary_file))]
       Self::ReadBinaryFile { .. } => Err(crate::Error::ApiNotAllowlisted(
@@ -131,7 +388,7 @@ impl Cmd {
         path,
         contents,
         options,
-      } => write_file(&config, package_info, path, contents, options).map(Into::into),
+      } => write_file(&window, &config, package_info, path, contents, options).map(Into::into),
       #[cfg(not(fs_write_file))]
       Self::WriteFile { .. } => Err(crate::Error::ApiNotAllowlisted(
         "fs > writeFile".to_string(),
@@ -142,7 +399,9 @@ impl Cmd {
         path,
         contents,
         options,
-      } => write_binary_file(&config, package_info, path, contents, options).map(Into::into),
+      } => {
+        write_binary_file(&window, &config, package_info, path, contents, options).map(Into::into)
+      }
       #[cfg(not(fs_write_binary_file))]
       Self::WriteBinaryFile { .. } => Err(crate::Error::ApiNotAllowlisted(
         "writeBinaryFile".to_string(),
@@ -150,23 +409,46 @@ impl Cmd {
 
       #[cfg(fs_read_dir)]
       Self::ReadDir { path, options } => {
-        read_dir(&config, package_info, path, options).map(Into::into)
+        read_dir(&window, &config, package_info, path, options).map(Into::into)
+      }
+      #[cfg(fs_read_dir)]
+      Self::StreamDir {
+        path,
+        options,
+        page_size,
+        on_event_fn,
+      } => stream_dir(
+        window,
+        &config,
+        package_info,
+        path,
+        options,
+        page_size,
+        on_event_fn,
+      )
+      .map(Into::into),
+      #[cfg(fs_read_dir)]
+      Self::CancelStreamDir { rid } => {
+        DIR_STREAMS.remove(&window.label().to_string(), rid);
+        Ok(().into())
       }
       #[cfg(not(fs_read_dir))]
-      Self::ReadDir { .. } => Err(crate::Error::ApiNotAllowlisted("fs > readDir".to_string())),
+      Self::ReadDir { .. } | Self::StreamDir { .. } | Self::CancelStreamDir { .. } => {
+        Err(crate::Error::ApiNotAllowlisted("fs > readDir".to_string()))
+      }
 
       #[cfg(fs_copy_file)]
       Self::CopyFile {
         source,
         destination,
         options,
-      } => copy_file(&config, package_info, source, destination, options).map(Into::into),
+      } => copy_file(&window, &config, package_info, source, destination, options).map(Into::into),
       #[cfg(not(fs_copy_file))]
       Self::CopyFile { .. } => Err(crate::Error::ApiNotAllowlisted("fs > copyFile".to_string())),
 
       #[cfg(fs_create_dir)]
       Self::CreateDir { path, options } => {
-        create_dir(&config, package_info, path, options).map(Into::into)
+        create_dir(&window, &config, package_info, path, options).map(Into::into)
       }
       #[cfg(not(fs_create_dir))]
       Self::CreateDir { .. } => Err(crate::Error::ApiNotAllowlisted(
@@ -175,7 +457,7 @@ impl Cmd {
 
       #[cfg(fs_remove_dir)]
       Self::RemoveDir { path, options } => {
-        remove_dir(&config, package_info, path, options).map(Into::into)
+        remove_dir(&window, &config, package_info, path, options).map(Into::into)
       }
       #[cfg(not(fs_remove_dir))]
       Self::RemoveDir { .. } => Err(crate::Error::ApiNotAllowlisted(
@@ -184,7 +466,7 @@ impl Cmd {
 
       #[cfg(fs_remove_file)]
       Self::RemoveFile { path, options } => {
-        remove_file(&config, package_info, path, options).map(Into::into)
+        remove_file(&window, &config, package_info, path, options).map(Into::into)
       }
       #[cfg(not(fs_remove_file))]
       Self::RemoveFile { .. } => Err(crate::Error::ApiNotAllowlisted(
@@ -196,7 +478,7 @@ impl Cmd {
         old_path,
         new_path,
         options,
-      } => rename_file(&config, package_info, old_path, new_path, options).map(Into::into),
+      } => rename_file(&window, &config, package_info, old_path, new_path, options).map(Into::into),
       #[cfg(not(fs_rename_file))]
       Self::RenameFile { .. } => Err(crate::Error::ApiNotAllowlisted(
         "fs > renameFile".to_string(),
@@ -208,50 +490,345 @@ impl Cmd {
       }
       #[cfg(not(fs_path))]
       Self::ResolvePath { .. } => Err(crate::Error::ApiNotAllowlisted("fs > pathApi".to_string())),
+
+      Self::Open { path, options } => open(
+        &window,
+        &config,
+        package_info,
+        path,
+        options.unwrap_or_default(),
+      )
+      .map(Into::into),
+      Self::Read { rid, len } => FILE_HANDLES
+        .with(&window.label().to_string(), rid, |file| {
+          read_chunk(file, len)
+        })
+        .map(Into::into),
+      Self::Write { rid, data } => {
+        let data = match data {
+          Buffer::Text(text) => text.into_bytes(),
+          Buffer::Raw(data) => data,
+        };
+        FILE_HANDLES
+          .with(&window.label().to_string(), rid, |file| {
+            file.write_all(&data).map_err(Into::into)
+          })
+          .map(Into::into)
+      }
+      Self::Seek {
+        rid,
+        offset,
+        whence,
+      } => {
+        let position = match whence {
+          SeekMode::Start if offset < 0 => {
+            return Err(crate::Error::Io(std::io::Error::new(
+              std::io::ErrorKind::InvalidInput,
+              "cannot seek before the start of the file",
+            )))
+          }
+          SeekMode::Start => SeekFrom::Start(offset as u64),
+          SeekMode::Current => SeekFrom::Current(offset),
+          SeekMode::End => SeekFrom::End(offset),
+        };
+        FILE_HANDLES
+          .with(&window.label().to_string(), rid, |file| {
+            file.seek(position).map_err(Into::into)
+          })
+          .map(Into::into)
+      }
+      Self::Truncate { rid, len } => FILE_HANDLES
+        .with(&window.label().to_string(), rid, |file| {
+          file.set_len(len).map_err(Into::into)
+        })
+        .map(Into::into),
+      Self::Close { rid } => {
+        FILE_HANDLES.remove(&window.label().to_string(), rid);
+        Ok(().into())
+      }
+
+      #[cfg(fs_watch)]
+      Self::Watch {
+        path,
+        options,
+        on_event_fn,
+      } => watch(window, &config, package_info, path, options, on_event_fn).map(Into::into),
+      #[cfg(fs_watch)]
+      Self::Unwatch { rid } => {
+        WATCHERS.remove(&window.label().to_string(), rid);
+        Ok(().into())
+      }
+      #[cfg(not(fs_watch))]
+      Self::Watch { .. } | Self::Unwatch { .. } => {
+        Err(crate::Error::ApiNotAllowlisted("fs > watch".to_string()))
+      }
+
+      #[cfg(fs_metadata)]
+      Self::Metadata { path, options } => {
+        metadata(&window, &config, package_info, path, options).map(Into::into)
+      }
+      #[cfg(fs_metadata)]
+      Self::Exists { path, options } => {
+        exists(&window, &config, package_info, path, options).map(Into::into)
+      }
+      #[cfg(not(fs_metadata))]
+      Self::Metadata { .. } | Self::Exists { .. } => {
+        Err(crate::Error::ApiNotAllowlisted("fs > metadata".to_string()))
+      }
+      #[cfg(fs_trash)]
+      Self::ListTrash => list_trash(&window, &config).map(Into::into),
+      #[cfg(fs_trash)]
+      Self::RestoreTrash { id } => restore_trash(&window, &config, id).map(Into::into),
+      #[cfg(not(fs_trash))]
+      Self::ListTrash | Self::RestoreTrash { .. } => {
+        Err(crate::Error::ApiNotAllowlisted("fs > trash".to_string()))
+      }
+
+      #[cfg(fs_archive)]
+      Self::ExtractArchive {
+        source,
+        destination,
+        options,
+      } => extract_archive(&window, &config, package_info, source, destination, options)
+        .map(Into::into),
+      #[cfg(fs_archive)]
+      Self::CreateArchive {
+        destination,
+        paths,
+        options,
+        on_progress_fn,
+      } => create_archive(
+        window,
+        &config,
+        package_info,
+        destination,
+        paths,
+        options,
+        on_progress_fn,
+      )
+      .map(Into::into),
+      #[cfg(not(fs_archive))]
+      Self::ExtractArchive { .. } | Self::CreateArchive { .. } => {
+        Err(crate::Error::ApiNotAllowlisted("fs > archive".to_string()))
+      }
+
+      #[cfg(fs_set_permissions)]
+      Self::SetPermissions {
+        path,
+        permissions,
+        options,
+      } => {
+        set_permissions(&window, &config, package_info, path, permissions, options).map(Into::into)
+      }
+      #[cfg(not(fs_set_permissions))]
+      Self::SetPermissions { .. } => Err(crate::Error::ApiNotAllowlisted(
+        "fs > setPermissions".to_string(),
+      )),
     }
   }
 }
 
+/// Opens a file handle owned by the window, reading requires the `readBinaryFile` API
+/// and writing requires the `writeBinaryFile` API.
+fn open<P: Params>(
+  window: &Window<P>,
+  config: &Config,
+  package_info: &PackageInfo,
+  path: PathBuf,
+  options: OpenOptions,
+) -> crate::Result<ResourceId> {
+  if options.read && !cfg!(fs_read_binary_file) {
+    return Err(crate::Error::ApiNotAllowlisted(
+      "fs > readBinaryFile".to_string(),
+    ));
+  }
+  let writes =
+    options.write || options.append || options.truncate || options.create || options.create_new;
+  if writes && !cfg!(fs_write_binary_file) {
+    return Err(crate::Error::ApiNotAllowlisted(
+      "fs > writeBinaryFile".to_string(),
+    ));
+  }
+
+  let path = resolve_scoped_path(window, config, package_info, path, options.dir)?;
+  let file = fs::OpenOptions::new()
+    .read(options.read)
+    .write(options.write)
+    .append(options.append)
+    .truncate(options.truncate)
+    .create(options.create)
+    .create_new(options.create_new)
+    .open(path)?;
+  Ok(FILE_HANDLES.add(window, file))
+}
+
+/// Watches a path allowed by the fs scope, sending the debounced events to the window.
+#[cfg(fs_watch)]
+fn watch<P: Params>(
+  window: Window<P>,
+  config: &Config,
+  package_info: &PackageInfo,
+  path: PathBuf,
+  options: Option<WatchOptions>,
+  on_event_fn: String,
+) -> crate::Result<ResourceId> {
+  let (dir, recursive, debounce) = match options {
+    Some(options) => (options.dir, options.recursive, options.debounce),
+    None => (None, false, None),
+  };
+  let path = resolve_scoped_path(&window, config, package_info, path, dir)?;
+  let (mut rx, watcher) = crate::api::fs::watch(
+    path,
+    recursive,
+    std::time::Duration::from_millis(debounce.unwrap_or(500)),
+  )?;
+  let rid = WATCHERS.add(&window, watcher);
+
+  // the channel is closed when the watcher is removed
+  crate::async_runtime::spawn(async move {
+    while let Some(event) = rx.recv().await {
+      let js = crate::api::rpc::format_callback(on_event_fn.clone(), &event)
+        .expect("unable to serialize WatchEvent");
+      let _ = window.eval(js.as_str());
+    }
+  });
+
+  Ok(rid)
+}
+
+// Reads up to `len` bytes, an empty chunk means the end of the file was reached
+fn read_chunk<R: Read>(reader: &mut R, len: usize) -> crate::Result<Vec<u8>> {
+  let mut buffer = Vec::with_capacity(len.min(64 * 1024 * 1024));
+  reader.take(len as u64).read_to_end(&mut buffer)?;
+  Ok(buffer)
+}
+
 /// Reads a directory.
 #[cfg(fs_read_dir)]
-pub fn read_dir(
+pub fn read_dir<P: Params>(
+  window: &Window<P>,
   config: &Config,
   package_info: &PackageInfo,
   path: PathBuf,
   options: Option<DirOperationOptions>,
 ) -> crate::Result<Vec<dir::DiskEntry>> {
-  let (recursive, dir) = if let Some(options_value) = options {
-    (options_value.recursive, options_value.dir)
-  } else {
-    (false, None)
+  let (dir, options) = read_dir_options(options);
+  dir::read_dir_with_options(
+    resolve_scoped_path(window, config, package_info, path, dir)?,
+    &options,
+  )
+  .map_err(crate::Error::FailedToExecuteApi)
+}
+
+/// Streams the entries of a directory to the window, in pages of `page_size` entries.
+#[cfg(fs_read_dir)]
+fn stream_dir<P: Params>(
+  window: Window<P>,
+  config: &Config,
+  package_info: &PackageInfo,
+  path: PathBuf,
+  options: Option<DirOperationOptions>,
+  page_size: Option<usize>,
+  on_event_fn: String,
+) -> crate::Result<ResourceId> {
+  let (dir, options) = read_dir_options(options);
+  let walker = dir::walk_dir(
+    resolve_scoped_path(&window, config, package_info, path, dir)?,
+    &options,
+  )
+  .map_err(crate::Error::FailedToExecuteApi)?;
+  let page_size = page_size.unwrap_or(256).max(1);
+
+  let stream = DirStream::default();
+  let cancelled = stream.0.clone();
+  let rid = DIR_STREAMS.add(&window, stream);
+  let label = window.label().to_string();
+
+  let send = move |event: DirStreamEvent| {
+    let js = crate::api::rpc::format_callback(on_event_fn.clone(), &event)
+      .expect("unable to serialize DirStreamEvent");
+    let _ = window.eval(js.as_str());
   };
-  dir::read_dir(resolve_path(config, package_info, path, dir)?, recursive)
-    .map_err(crate::Error::FailedToExecuteApi)
+  // the walk blocks on the file system, so it runs on its own thread
+  std::thread::spawn(move || {
+    let mut page = Vec::with_capacity(page_size);
+    for entry in walker {
+      if cancelled.load(Ordering::Relaxed) {
+        return;
+      }
+      match entry {
+        Ok(entry) => page.push(entry),
+        Err(e) => {
+          DIR_STREAMS.remove(&label, rid);
+          send(DirStreamEvent::Error(e.to_string()));
+          return;
+        }
+      }
+      if page.len() == page_size {
+        send(DirStreamEvent::Page(std::mem::replace(
+          &mut page,
+          Vec::with_capacity(page_size),
+        )));
+      }
+    }
+    if !page.is_empty() {
+      send(DirStreamEvent::Page(page));
+    }
+    DIR_STREAMS.remove(&label, rid);
+    send(DirStreamEvent::Done);
+  });
+
+  Ok(rid)
+}
+
+#[cfg(fs_read_dir)]
+fn read_dir_options(
+  options: Option<DirOperationOptions>,
+) -> (Option<BaseDirectory>, dir::ReadDirOptions) {
+  match options {
+    Some(options) => {
+      let mut read_dir_options = dir::ReadDirOptions::new()
+        .recursive(options.recursive)
+        .metadata(options.metadata)
+        .gitignore(options.gitignore)
+        .hidden(options.hidden)
+        .symlinks(options.symlinks);
+      if let Some(max_depth) = options.max_depth {
+        read_dir_options = read_dir_options.max_depth(max_depth);
+      }
+      for glob in options.include {
+        read_dir_options = read_dir_options.include(glob);
+      }
+      for glob in options.exclude {
+        read_dir_options = read_dir_options.exclude(glob);
+      }
+      (options.dir, read_dir_options)
+    }
+    None => (None, dir::ReadDirOptions::new()),
+  }
 }
 
 /// Copies a file.
 #[cfg(fs_copy_file)]
-pub fn copy_file(
+pub fn copy_file<P: Params>(
+  window: &Window<P>,
   config: &Config,
   package_info: &PackageInfo,
   source: PathBuf,
   destination: PathBuf,
   options: Option<FileOperationOptions>,
 ) -> crate::Result<()> {
-  let (src, dest) = match options.and_then(|o| o.dir) {
-    Some(dir) => (
-      resolve_path(config, package_info, source, Some(dir.clone()))?,
-      resolve_path(config, package_info, destination, Some(dir))?,
-    ),
-    None => (source, destination),
-  };
+  let dir = options.and_then(|o| o.dir);
+  let src = resolve_scoped_path(window, config, package_info, source, dir.clone())?;
+  let dest = resolve_scoped_path(window, config, package_info, destination, dir)?;
   fs::copy(src, dest)?;
   Ok(())
 }
 
 /// Creates a directory.
 #[cfg(fs_create_dir)]
-pub fn create_dir(
+pub fn create_dir<P: Params>(
+  window: &Window<P>,
   config: &Config,
   package_info: &PackageInfo,
   path: PathBuf,
@@ -262,7 +839,7 @@ pub fn create_dir(
   } else {
     (false, None)
   };
-  let resolved_path = resolve_path(config, package_info, path, dir)?;
+  let resolved_path = resolve_scoped_path(window, config, package_info, path, dir)?;
   if recursive {
     fs::create_dir_all(resolved_path)?;
   } else {
@@ -274,19 +851,31 @@ pub fn create_dir(
 
 /// Removes a directory.
 #[cfg(fs_remove_dir)]
-pub fn remove_dir(
+pub fn remove_dir<P: Params>(
+  window: &Window<P>,
   config: &Config,
   package_info: &PackageInfo,
   path: PathBuf,
   options: Option<DirOperationOptions>,
 ) -> crate::Result<()> {
-  let (recursive, dir) = if let Some(options_value) = options {
-    (options_value.recursive, options_value.dir)
+  let (recursive, trash, dir) = if let Some(options_value) = options {
+    (
+      options_value.recursive,
+      options_value.trash,
+      options_value.dir,
+    )
   } else {
-    (false, None)
+    (false, false, None)
   };
-  let resolved_path = resolve_path(config, package_info, path, dir)?;
-  if recursive {
+  let resolved_path = resolve_scoped_path(window, config, package_info, path, dir)?;
+  if trash {
+    if !recursive && fs::read_dir(&resolved_path)?.next().is_some() {
+      return Err(
+        std::io::Error::new(std::io::ErrorKind::Other, "the directory is not empty").into(),
+      );
+    }
+    move_to_trash(config, resolved_path)?;
+  } else if recursive {
     fs::remove_dir_all(resolved_path)?;
   } else {
     fs::remove_dir(resolved_path)?;
@@ -297,89 +886,204 @@ pub fn remove_dir(
 
 /// Removes a file
 #[cfg(fs_remove_file)]
-pub fn remove_file(
+pub fn remove_file<P: Params>(
+  window: &Window<P>,
   config: &Config,
   package_info: &PackageInfo,
   path: PathBuf,
   options: Option<FileOperationOptions>,
 ) -> crate::Result<()> {
-  let resolved_path = resolve_path(config, package_info, path, options.and_then(|o| o.dir))?;
-  fs::remove_file(resolved_path)?;
+  let (trash, dir) = match options {
+    Some(options) => (options.trash, options.dir),
+    None => (false, None),
+  };
+  let resolved_path = resolve_scoped_path(window, config, package_info, path, dir)?;
+  if trash {
+    move_to_trash(config, resolved_path)?;
+  } else {
+    fs::remove_file(resolved_path)?;
+  }
+  Ok(())
+}
+
+// Moves the path to the trash and records the item, so the app can list and restore it
+#[cfg(fs_trash)]
+#[allow(dead_code)]
+fn move_to_trash(config: &Config, path: PathBuf) -> crate::Result<()> {
+  let item = crate::api::trash::delete(path)?;
+  if !item.id.is_empty() {
+    let _guard = TRASH_RECORD.lock().unwrap();
+    let mut ids = trashed_ids(config)?;
+    ids.push(item.id);
+    save_trashed_ids(config, &ids)?;
+  }
+  Ok(())
+}
+
+#[cfg(not(fs_trash))]
+#[allow(dead_code)]
+fn move_to_trash(_config: &Config, _path: PathBuf) -> crate::Result<()> {
+  Err(crate::Error::ApiNotAllowlisted("fs > trash".to_string()))
+}
+
+#[cfg(fs_trash)]
+fn trash_record_path(config: &Config) -> crate::Result<PathBuf> {
+  let app_dir = crate::api::path::app_dir(config)
+    .ok_or_else(|| crate::api::Error::Path("unable to determine the app directory".into()))?;
+  Ok(app_dir.join("trash.json"))
+}
+
+#[cfg(fs_trash)]
+fn trashed_ids(config: &Config) -> crate::Result<Vec<String>> {
+  let path = trash_record_path(config)?;
+  if path.exists() {
+    Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+  } else {
+    Ok(Vec::new())
+  }
+}
+
+#[cfg(fs_trash)]
+fn save_trashed_ids(config: &Config, ids: &[String]) -> crate::Result<()> {
+  let path = trash_record_path(config)?;
+  if let Some(parent) = path.parent() {
+    fs::create_dir_all(parent)?;
+  }
+  fs::write(path, serde_json::to_string(ids)?)?;
   Ok(())
 }
 
+/// Lists the trash items created by the app, the items allowed by the fs scope.
+#[cfg(fs_trash)]
+pub fn list_trash<P: Params>(
+  window: &Window<P>,
+  config: &Config,
+) -> crate::Result<Vec<crate::api::trash::TrashItem>> {
+  let _guard = TRASH_RECORD.lock().unwrap();
+  let ids = trashed_ids(config)?;
+  let items: Vec<_> = crate::api::trash::list()?
+    .into_iter()
+    .filter(|item| ids.contains(&item.id))
+    .collect();
+  // forget the items that were restored or deleted outside of the app
+  if items.len() != ids.len() {
+    let ids: Vec<_> = items.iter().map(|item| item.id.clone()).collect();
+    save_trashed_ids(config, &ids)?;
+  }
+  let scope = window.state::<FsScope>();
+  let label = window.label().to_string();
+  Ok(
+    items
+      .into_iter()
+      .filter(|item| scope.is_allowed(&label, &item.original_path))
+      .collect(),
+  )
+}
+
+/// Restores a trash item created by the app, its original path must be allowed by the fs scope.
+#[cfg(fs_trash)]
+pub fn restore_trash<P: Params>(
+  window: &Window<P>,
+  config: &Config,
+  id: String,
+) -> crate::Result<PathBuf> {
+  let _guard = TRASH_RECORD.lock().unwrap();
+  let mut ids = trashed_ids(config)?;
+  let item = crate::api::trash::list()?
+    .into_iter()
+    .find(|item| item.id == id && ids.contains(&item.id))
+    .ok_or_else(|| crate::api::Error::Trash(format!("trash item {} not found", id)))?;
+  window
+    .state::<FsScope>()
+    .check(&window.label().to_string(), &item.original_path)?;
+  let path = crate::api::trash::restore(&item.id)?;
+  ids.retain(|trashed| trashed != &id);
+  save_trashed_ids(config, &ids)?;
+  Ok(path)
+}
+
 /// Renames a file.
 #[cfg(fs_rename_file)]
-pub fn rename_file(
+pub fn rename_file<P: Params>(
+  window: &Window<P>,
   config: &Config,
   package_info: &PackageInfo,
   old_path: PathBuf,
   new_path: PathBuf,
   options: Option<FileOperationOptions>,
 ) -> crate::Result<()> {
-  let (old, new) = match options.and_then(|o| o.dir) {
-    Some(dir) => (
-      resolve_path(config, package_info, old_path, Some(dir.clone()))?,
-      resolve_path(config, package_info, new_path, Some(dir))?,
-    ),
-    None => (old_path, new_path),
-  };
+  let dir = options.and_then(|o| o.dir);
+  let old = resolve_scoped_path(window, config, package_info, old_path, dir.clone())?;
+  let new = resolve_scoped_path(window, config, package_info, new_path, dir)?;
   fs::rename(old, new).map_err(crate::Error::Io)
 }
 
 /// Writes a text file.
 #[cfg(fs_write_file)]
-pub fn write_file(
+pub fn write_file<P: Params>(
+  window: &Window<P>,
   config: &Config,
   package_info: &PackageInfo,
   path: PathBuf,
   contents: String,
-  options: Option<FileOperationOptions>,
+  options: Option<WriteFileOptions>,
 ) -> crate::Result<()> {
-  File::create(resolve_path(
+  write(
+    window,
     config,
     package_info,
     path,
-    options.and_then(|o| o.dir),
-  )?)
-  .map_err(crate::Error::Io)
-  .and_then(|mut f| f.write_all(contents.as_bytes()).map_err(|err| err.into()))?;
-  Ok(())
+    contents.as_bytes(),
+    options,
+  )
 }
 
 /// Writes a binary file.
 #[cfg(fs_write_binary_file)]
-pub fn write_binary_file(
+pub fn write_binary_file<P: Params>(
+  window: &Window<P>,
   config: &Config,
   package_info: &PackageInfo,
   path: PathBuf,
   contents: String,
-  options: Option<FileOperationOptions>,
+  options: Option<WriteFileOptions>,
 ) -> crate::Result<()> {
-  base64::decode(contents)
-    .map_err(crate::Error::Base64Decode)
-    .and_then(|c| {
-      File::create(resolve_path(
-        config,
-        package_info,
-        path,
-        options.and_then(|o| o.dir),
-      )?)
-      .map_err(Into::into)
-      .and_then(|mut f| f.write_all(&c).map_err(|err| err.into()))
-    })?;
-  Ok(())
+  let contents = base64::decode(contents).map_err(crate::Error::Base64Decode)?;
+  write(window, config, package_info, path, &contents, options)
+}
+
+#[cfg(any(fs_write_file, fs_write_binary_file))]
+fn write<P: Params>(
+  window: &Window<P>,
+  config: &Config,
+  package_info: &PackageInfo,
+  path: PathBuf,
+  contents: &[u8],
+  options: Option<WriteFileOptions>,
+) -> crate::Result<()> {
+  let (write_options, dir) = match options {
+    Some(options) => (options.write_options(), options.dir),
+    None => (file::WriteOptions::new(), None),
+  };
+  file::write_with_options(
+    resolve_scoped_path(window, config, package_info, path, dir)?,
+    contents,
+    &write_options,
+  )
+  .map_err(crate::Error::FailedToExecuteApi)
 }
 
 /// Reads a text file.
 #[cfg(fs_read_text_file)]
-pub fn read_text_file(
+pub fn read_text_file<P: Params>(
+  window: &Window<P>,
   config: &Config,
   package_info: &PackageInfo,
   path: PathBuf,
   options: Option<FileOperationOptions>,
 ) -> crate::Result<String> {
-  file::read_string(resolve_path(
+  file::read_string(resolve_scoped_path(
+    window,
     config,
     package_info,
     path,
@@ -390,13 +1094,34 @@ pub fn read_text_file(
 
 /// Reads a binary file.
 #[cfg(fs_read_binary_file)]
-pub fn read_binary_file(
+pub fn read_binary_file<P: Params>(
+  window: &Window<P>,
   config: &Config,
   package_info: &PackageInfo,
   path: PathBuf,
   options: Option<FileOperationOptions>,
 ) -> crate::Result<Vec<u8>> {
-  file::read_binary(resolve_path(
+  file::read_binary(resolve_scoped_path(
+    window,
+    config,
+    package_info,
+    path,
+    options.and_then(|o| o.dir),
+  )?)
+  .map_err(crate::Error::FailedToExecuteApi)
+}
+
+/// Reads the metadata of a file or directory.
+#[cfg(fs_metadata)]
+pub fn metadata<P: Params>(
+  window: &Window<P>,
+  config: &Config,
+  package_info: &PackageInfo,
+  path: PathBuf,
+  options: Option<FileOperationOptions>,
+) -> crate::Result<file::Metadata> {
+  file::metadata(resolve_scoped_path(
+    window,
     config,
     package_info,
     path,
@@ -405,6 +1130,119 @@ pub fn read_binary_file(
   .map_err(crate::Error::FailedToExecuteApi)
 }
 
+/// Checks if a path exists.
+#[cfg(fs_metadata)]
+pub fn exists<P: Params>(
+  window: &Window<P>,
+  config: &Config,
+  package_info: &PackageInfo,
+  path: PathBuf,
+  options: Option<FileOperationOptions>,
+) -> crate::Result<bool> {
+  Ok(file::exists(resolve_scoped_path(
+    window,
+    config,
+    package_info,
+    path,
+    options.and_then(|o| o.dir),
+  )?))
+}
+
+/// Sets the permissions of a file or directory.
+#[cfg(fs_set_permissions)]
+pub fn set_permissions<P: Params>(
+  window: &Window<P>,
+  config: &Config,
+  package_info: &PackageInfo,
+  path: PathBuf,
+  permissions: file::Permissions,
+  options: Option<FileOperationOptions>,
+) -> crate::Result<()> {
+  file::set_permissions(
+    resolve_scoped_path(
+      window,
+      config,
+      package_info,
+      path,
+      options.and_then(|o| o.dir),
+    )?,
+    permissions,
+  )
+  .map_err(crate::Error::FailedToExecuteApi)
+}
+
+/// Extracts an archive into the destination directory.
+#[cfg(fs_archive)]
+pub fn extract_archive<P: Params>(
+  window: &Window<P>,
+  config: &Config,
+  package_info: &PackageInfo,
+  source: PathBuf,
+  destination: PathBuf,
+  options: Option<FileOperationOptions>,
+) -> crate::Result<()> {
+  let dir = options.and_then(|o| o.dir);
+  let source = resolve_scoped_path(window, config, package_info, source, dir.clone())?;
+  let destination = resolve_scoped_path(window, config, package_info, destination, dir)?;
+  file::Extract::from_source(&source)
+    .extract_into(&destination)
+    .map_err(crate::Error::FailedToExecuteApi)
+}
+
+/// Creates an archive from the paths, the progress is sent to the `on_progress_fn` callback.
+#[cfg(fs_archive)]
+pub fn create_archive<P: Params>(
+  window: Window<P>,
+  config: &Config,
+  package_info: &PackageInfo,
+  destination: PathBuf,
+  paths: Vec<PathBuf>,
+  options: Option<FileOperationOptions>,
+  on_progress_fn: Option<String>,
+) -> crate::Result<()> {
+  let dir = options.and_then(|o| o.dir);
+  let mut archive = file::Archive::to_destination(resolve_scoped_path(
+    &window,
+    config,
+    package_info,
+    destination,
+    dir.clone(),
+  )?);
+  for path in paths {
+    archive.add(resolve_scoped_path(
+      &window,
+      config,
+      package_info,
+      path,
+      dir.clone(),
+    )?);
+  }
+  archive
+    .create_with_progress(|progress| {
+      if let Some(on_progress_fn) = &on_progress_fn {
+        let js = crate::api::rpc::format_callback(on_progress_fn.clone(), &progress)
+          .expect("unable to serialize ArchiveProgress");
+        let _ = window.eval(js.as_str());
+      }
+    })
+    .map_err(crate::Error::FailedToExecuteApi)
+}
+
+// Resolves the path with the optional base directory and checks it's allowed for the window by the fs scope.
+fn resolve_scoped_path<P: Params, T: AsRef<std::path::Path>>(
+  window: &Window<P>,
+  config: &Config,
+  package_info: &PackageInfo,
+  path: T,
+  dir: Option<BaseDirectory>,
+) -> crate::Result<PathBuf> {
+  let path = resolve_path(config, package_info, path, dir)?;
+  window
+    .state::<FsScope>()
+    .check(&window.label().to_string(), &path)?;
+  Ok(path)
+}
+
 #[cfg(fs_path)]
 pub fn resolve_path_handler(
   config: &Config,
@@ -412,13 +1250,22 @@ pub fn resolve_path_handler(
   path: String,
   directory: Option<BaseDirectory>,
 ) -> crate::Result<PathBuf> {
-  resolve_path(config, package_info, path, directory).map_err(Into::into)
+  crate::api::path::resolve_path(config, package_info, path, directory).map_err(Into::into)
 }
 
 // test webview functionality.
 #[cfg(test)]
 mod test {
-  // use super::*;
+  use super::*;
+
+  #[test]
+  fn read_chunks() {
+    let mut reader = std::io::Cursor::new(b"hello world".to_vec());
+    assert_eq!(read_chunk(&mut reader, 5).unwrap(), b"hello");
+    assert_eq!(read_chunk(&mut reader, 100).unwrap(), b" world");
+    assert!(read_chunk(&mut reader, 5).unwrap().is_empty());
+  }
+
   // use web_view::*;
 
   // create a makeshift webview
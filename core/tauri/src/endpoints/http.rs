@@ -2,31 +2,65 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-License-Identifier: MIT
 
-use super::InvokeResponse;
+use super::{
+  resources::{ResourceId, ResourceTable},
+  InvokeResponse,
+};
 
-use crate::api::http::{Client, ClientBuilder, HttpRequestBuilder};
+use crate::{
+  api::{
+    http::{Body, Client, ClientBuilder, DownloadCancel, HttpRequestBuilder},
+    path::BaseDirectory,
+    scope::FsScope,
+  },
+  Config, Manager, PackageInfo, Params, Window,
+};
 use once_cell::sync::Lazy;
 use serde::Deserialize;
 
 use std::{
   collections::HashMap,
+  path::PathBuf,
   sync::{Arc, Mutex},
 };
 
 type ClientId = u32;
 type ClientStore = Arc<Mutex<HashMap<ClientId, Client>>>;
 
+/// The cancellation handles of the downloads, created before the download starts
+/// so a cancellation is never lost.
+static DOWNLOADS: Lazy<ResourceTable<DownloadCancel>> = Lazy::new(Default::default);
+
 fn clients() -> &'static ClientStore {
   static STORE: Lazy<ClientStore> = Lazy::new(Default::default);
   &STORE
 }
 
+/// The options for the download API.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadOptions {
+  /// The destination of the response body.
+  path: PathBuf,
+  /// The base directory of the destination path.
+  dir: Option<BaseDirectory>,
+  /// The expected SHA-256 checksum (hex encoded) of the file.
+  sha256: Option<String>,
+  /// The callback receiving the download progress.
+  on_progress_fn: Option<String>,
+}
+
 /// The API descriptor.
 #[derive(Deserialize)]
 #[serde(tag = "cmd", rename_all = "camelCase")]
 pub enum Cmd {
   /// Create a new HTTP client.
-  CreateClient { options: Option<ClientBuilder> },
+  #[serde(rename_all = "camelCase")]
+  CreateClient {
+    options: Option<ClientBuilder>,
+    /// The name of the persistent cookie jar.
+    cookie_jar: Option<String>,
+  },
   /// Drop a HTTP client.
   DropClient { client: ClientId },
   /// The HTTP request API.
@@ -34,13 +68,33 @@ pub enum Cmd {
     client: ClientId,
     options: Box<HttpRequestBuilder>,
   },
+  /// Create a download, returning its identifier.
+  CreateDownload,
+  /// Download a response body to a file.
+  Download {
+    client: ClientId,
+    id: ResourceId,
+    options: Box<HttpRequestBuilder>,
+    download: DownloadOptions,
+  },
+  /// Cancel a download.
+  CancelDownload { id: ResourceId },
 }
 
 impl Cmd {
-  pub async fn run(self) -> crate::Result<InvokeResponse> {
+  #[allow(unused_variables)]
+  pub async fn run<P: Params>(
+    self,
+    window: Window<P>,
+    config: Arc<Config>,
+    package_info: PackageInfo,
+  ) -> crate::Result<InvokeResponse> {
     match self {
-      Self::CreateClient { options } => {
-        let client = options.unwrap_or_default().build()?;
+      Self::CreateClient {
+        options,
+        cookie_jar,
+      } => {
+        let client = create_client(&window, &config, options.unwrap_or_default(), cookie_jar)?;
         let mut store = clients().lock().unwrap();
         let id = rand::random::<ClientId>();
         store.insert(id, client);
@@ -53,22 +107,100 @@ impl Cmd {
       }
       #[cfg(http_request)]
       Self::HttpRequest { client, options } => {
-        return make_request(client, *options).await.map(Into::into);
+        return make_request(&window, client, *options)
+          .await
+          .map(Into::into);
       }
       #[cfg(not(http_request))]
       Self::HttpRequest { .. } => Err(crate::Error::ApiNotAllowlisted(
         "http > request".to_string(),
       )),
+      Self::CreateDownload => Ok(DOWNLOADS.add(&window, DownloadCancel::new()).into()),
+      #[cfg(http_request)]
+      Self::Download {
+        client,
+        id,
+        options,
+        download,
+      } => {
+        return make_download(
+          window,
+          &config,
+          &package_info,
+          client,
+          id,
+          *options,
+          download,
+        )
+        .await
+        .map(Into::into);
+      }
+      #[cfg(not(http_request))]
+      Self::Download { .. } => Err(crate::Error::ApiNotAllowlisted(
+        "http > request".to_string(),
+      )),
+      Self::CancelDownload { id } => {
+        // the handle is only removed once the download finishes, so it can't be cancelled anymore
+        let _ = DOWNLOADS.with(&window.label().to_string(), id, |cancel| {
+          cancel.cancel();
+          Ok(())
+        });
+        Ok(().into())
+      }
     }
   }
 }
 
+/// Builds a client whose certificate files are allowed by the fs scope,
+/// persisting its cookies on `$APP/cookies/<cookie_jar>.json`.
+fn create_client<P: Params>(
+  window: &Window<P>,
+  config: &Config,
+  options: ClientBuilder,
+  cookie_jar: Option<String>,
+) -> crate::Result<Client> {
+  let scope = window.state::<FsScope>();
+  let label = window.label().to_string();
+  for path in &options.root_certificates {
+    scope.check(&label, path)?;
+  }
+  if let Some(certificate) = &options.client_certificate {
+    scope.check(&label, &certificate.path)?;
+  }
+
+  let options = match cookie_jar {
+    Some(name) => options.cookie_store(cookie_jar_path(config, &name)?),
+    None => options,
+  };
+  options.build().map_err(Into::into)
+}
+
+// The cookie jar name is used as a file name, so it can't contain path separators
+fn cookie_jar_path(config: &Config, name: &str) -> crate::Result<PathBuf> {
+  if name.is_empty()
+    || !name
+      .chars()
+      .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+  {
+    return Err(crate::api::Error::Path(format!("invalid cookie jar name `{}`", name)).into());
+  }
+  let app_dir = crate::api::path::app_dir(config)
+    .ok_or_else(|| crate::api::Error::Path("unable to determine the app directory".into()))?;
+  Ok(app_dir.join("cookies").join(format!("{}.json", name)))
+}
+
 /// Makes an HTTP request and resolves the response to the webview
 #[cfg(http_request)]
-pub async fn make_request(
+pub async fn make_request<P: Params>(
+  window: &Window<P>,
   client_id: ClientId,
   options: HttpRequestBuilder,
 ) -> crate::Result<crate::api::http::ResponseData> {
+  check_form_files(
+    &window.state::<FsScope>(),
+    &window.label().to_string(),
+    &options,
+  )?;
   let client = clients()
     .lock()
     .unwrap()
@@ -78,3 +210,136 @@ pub async fn make_request(
   let response = client.send(options).await?;
   Ok(response.read().await?)
 }
+
+// The form files are read from the disk, so uploading them requires
+// the `readBinaryFile` API and the paths must be allowed by the fs scope
+#[cfg(any(http_request, test))]
+fn check_form_files(
+  scope: &FsScope,
+  window: &str,
+  options: &HttpRequestBuilder,
+) -> crate::Result<()> {
+  if let Some(Body::Form(form)) = &options.body {
+    for path in form.files() {
+      if !cfg!(fs_read_binary_file) {
+        return Err(crate::Error::ApiNotAllowlisted(
+          "fs > readBinaryFile".to_string(),
+        ));
+      }
+      scope.check(window, path)?;
+    }
+  }
+  Ok(())
+}
+
+/// Downloads a response body to a file allowed by the fs scope, sending the progress to the webview.
+#[cfg(http_request)]
+pub async fn make_download<P: Params>(
+  window: Window<P>,
+  config: &Config,
+  package_info: &PackageInfo,
+  client_id: ClientId,
+  download_id: ResourceId,
+  options: HttpRequestBuilder,
+  download: DownloadOptions,
+) -> crate::Result<()> {
+  let label = window.label().to_string();
+  let cancel = DOWNLOADS.with(&label, download_id, |cancel| Ok(cancel.clone()))?;
+  let result = download_to_file(
+    &window,
+    config,
+    package_info,
+    client_id,
+    cancel,
+    options,
+    download,
+  )
+  .await;
+  DOWNLOADS.remove(&label, download_id);
+  result
+}
+
+#[cfg(http_request)]
+async fn download_to_file<P: Params>(
+  window: &Window<P>,
+  config: &Config,
+  package_info: &PackageInfo,
+  client_id: ClientId,
+  cancel: DownloadCancel,
+  options: HttpRequestBuilder,
+  download: DownloadOptions,
+) -> crate::Result<()> {
+  use crate::api::{http::DownloadBuilder, path::resolve_path};
+
+  let client = clients()
+    .lock()
+    .unwrap()
+    .get(&client_id)
+    .ok_or(crate::Error::HttpClientNotInitialized)?
+    .clone();
+
+  let path = resolve_path(config, package_info, download.path, download.dir)?;
+  window
+    .state::<FsScope>()
+    .check(&window.label().to_string(), &path)?;
+
+  let mut builder = DownloadBuilder::new(path).cancel_handle(cancel);
+  if let Some(sha256) = download.sha256 {
+    builder = builder.sha256(sha256);
+  }
+
+  let on_progress_fn = download.on_progress_fn;
+  client
+    .download(options, builder, |progress| {
+      if let Some(on_progress_fn) = &on_progress_fn {
+        if let Ok(js) = crate::api::rpc::format_callback(on_progress_fn, &progress) {
+          let _ = window.eval(js.as_str());
+        }
+      }
+    })
+    .await
+    .map_err(Into::into)
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::api::http::{FormBody, FormPart};
+
+  fn upload(file: &str) -> HttpRequestBuilder {
+    let mut form = HashMap::new();
+    form.insert(
+      "file".to_string(),
+      FormPart::File {
+        file: file.into(),
+        mime: None,
+        file_name: None,
+      },
+    );
+    HttpRequestBuilder::new("POST", "https://tauri.studio").body(Body::Form(FormBody::new(form)))
+  }
+
+  #[cfg(not(windows))]
+  #[test]
+  fn form_files_outside_scope() {
+    let mut config: Config = Default::default();
+    config.tauri.allowlist.fs.scope = Some(vec!["/home/tauri/uploads/*".into()]);
+    let package_info = PackageInfo {
+      name: "app".into(),
+      version: "1.0.0".into(),
+    };
+    let scope = FsScope::for_fs_api(&config, &package_info).unwrap();
+
+    assert!(check_form_files(&scope, "main", &upload("/home/tauri/.ssh/id_rsa")).is_err());
+    assert!(check_form_files(
+      &scope,
+      "main",
+      &upload("/home/tauri/uploads/../.ssh/id_rsa")
+    )
+    .is_err());
+    assert_eq!(
+      check_form_files(&scope, "main", &upload("/home/tauri/uploads/avatar.png")).is_ok(),
+      cfg!(fs_read_binary_file)
+    );
+  }
+}
@@ -6,8 +6,8 @@ use super::InvokeResponse;
 use serde::Deserialize;
 
 #[cfg(notification_all)]
-use crate::api::notification::Notification;
-use crate::{Config, PackageInfo};
+use crate::api::notification::{Notification, NotificationEvent, Timeout, Urgency};
+use crate::{Config, PackageInfo, Params, Window};
 
 use std::sync::Arc;
 
@@ -17,8 +17,41 @@ const PERMISSION_GRANTED: &str = "granted";
 // `Denied` response from `request_permission`. Matches the Web API return value.
 const PERMISSION_DENIED: &str = "denied";
 
+/// An action button of a notification.
+#[derive(Deserialize)]
+pub struct NotificationActionOptions {
+  /// The identifier of the action, sent with the `action` event.
+  pub id: String,
+  /// The label of the action button.
+  pub title: String,
+}
+
+/// The urgency level of a notification.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum NotificationUrgency {
+  /// Low urgency.
+  Low,
+  /// Normal urgency.
+  Normal,
+  /// Critical urgency.
+  Critical,
+}
+
+#[cfg(notification_all)]
+impl From<NotificationUrgency> for Urgency {
+  fn from(urgency: NotificationUrgency) -> Self {
+    match urgency {
+      NotificationUrgency::Low => Self::Low,
+      NotificationUrgency::Normal => Self::Normal,
+      NotificationUrgency::Critical => Self::Critical,
+    }
+  }
+}
+
 /// The options for the notification API.
 #[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct NotificationOptions {
   /// The notification title.
   pub title: String,
@@ -26,14 +59,30 @@ pub struct NotificationOptions {
   pub body: Option<String>,
   /// The notification icon.
   pub icon: Option<String>,
+  /// The ID of the notification to replace.
+  pub id: Option<u32>,
+  /// The action buttons.
+  #[serde(default)]
+  pub actions: Vec<NotificationActionOptions>,
+  /// The urgency level.
+  pub urgency: Option<NotificationUrgency>,
+  /// The expiration timeout in milliseconds, `0` means the notification never expires.
+  pub timeout: Option<u32>,
+  /// The sound to play.
+  pub sound: Option<String>,
 }
 
 /// The API descriptor.
 #[derive(Deserialize)]
 #[serde(tag = "cmd", rename_all = "camelCase")]
 pub enum Cmd {
-  /// The show notification API.
-  Notification { options: NotificationOptions },
+  /// The show notification API, returning the notification ID.
+  Notification {
+    options: NotificationOptions,
+    on_event_fn: Option<String>,
+  },
+  /// The close notification API.
+  CloseNotification { id: u32 },
   /// The request notification permission API.
   RequestNotificationPermission,
   /// The notification permission check API.
@@ -42,16 +91,29 @@ pub enum Cmd {
 
 impl Cmd {
   #[allow(unused_variables)]
-  pub fn run(
+  pub async fn run<P: Params>(
     self,
+    window: Window<P>,
     config: Arc<Config>,
     package_info: &PackageInfo,
   ) -> crate::Result<InvokeResponse> {
     match self {
       #[cfg(notification_all)]
-      Self::Notification { options } => send(options, &config).map(Into::into),
+      Self::Notification {
+        options,
+        on_event_fn,
+      } => send(window, options, on_event_fn, &config).map(Into::into),
       #[cfg(not(notification_all))]
       Self::Notification { .. } => Err(crate::Error::ApiNotAllowlisted("notification".to_string())),
+
+      #[cfg(notification_all)]
+      Self::CloseNotification { id } => crate::api::notification::close(id)
+        .map(Into::into)
+        .map_err(Into::into),
+      #[cfg(not(notification_all))]
+      Self::CloseNotification { .. } => {
+        Err(crate::Error::ApiNotAllowlisted("notification".to_string()))
+      }
       Self::IsNotificationPermissionGranted => {
         #[cfg(notification_all)]
         return is_permission_granted(&config, package_info).map(Into::into);
@@ -60,7 +122,9 @@ impl Cmd {
       }
       Self::RequestNotificationPermission => {
         #[cfg(notification_all)]
-        return request_permission(&config, package_info).map(Into::into);
+        return request_permission(&config, package_info)
+          .await
+          .map(Into::into);
         #[cfg(not(notification_all))]
         Ok(PERMISSION_DENIED.into())
       }
@@ -68,8 +132,14 @@ impl Cmd {
   }
 }
 
+/// Shows a notification, sending its first event to the `on_event_fn` callback of the window.
 #[cfg(notification_all)]
-pub fn send(options: NotificationOptions, config: &Config) -> crate::Result<InvokeResponse> {
+pub fn send<P: Params>(
+  window: Window<P>,
+  options: NotificationOptions,
+  on_event_fn: Option<String>,
+  config: &Config,
+) -> crate::Result<InvokeResponse> {
   let mut notification =
     Notification::new(config.tauri.bundle.identifier.clone()).title(options.title);
   if let Some(body) = options.body {
@@ -78,8 +148,33 @@ pub fn send(options: NotificationOptions, config: &Config) -> crate::Result<Invo
   if let Some(icon) = options.icon {
     notification = notification.icon(icon);
   }
-  notification.show()?;
-  Ok(().into())
+  if let Some(id) = options.id {
+    notification = notification.id(id);
+  }
+  for action in options.actions {
+    notification = notification.action(action.id, action.title);
+  }
+  if let Some(urgency) = options.urgency {
+    notification = notification.urgency(urgency.into());
+  }
+  if let Some(timeout) = options.timeout {
+    notification = notification.timeout(match timeout {
+      0 => Timeout::Never,
+      ms => Timeout::Milliseconds(ms),
+    });
+  }
+  if let Some(sound) = options.sound {
+    notification = notification.sound(sound);
+  }
+  if let Some(on_event_fn) = on_event_fn {
+    notification = notification.on_event(move |event: NotificationEvent| {
+      let js = crate::api::rpc::format_callback(on_event_fn, &event)
+        .expect("unable to serialize NotificationEvent");
+      let _ = window.eval(js.as_str());
+    });
+  }
+  let handle = notification.show()?;
+  Ok(handle.id().into())
 }
 
 #[cfg(notification_all)]
@@ -96,7 +191,10 @@ pub fn is_permission_granted(
 }
 
 #[cfg(notification_all)]
-pub fn request_permission(config: &Config, package_info: &PackageInfo) -> crate::Result<String> {
+pub async fn request_permission(
+  config: &Config,
+  package_info: &PackageInfo,
+) -> crate::Result<String> {
   let mut settings = crate::settings::read_settings(config, package_info);
   if let Some(allow_notification) = settings.allow_notification {
     return Ok(if allow_notification {
@@ -105,10 +203,11 @@ pub fn request_permission(config: &Config, package_info: &PackageInfo) -> crate:
       PERMISSION_DENIED.to_string()
     });
   }
-  let answer = crate::api::dialog::ask(
+  let answer = crate::api::dialog::ask_async(
     "Permissions",
     "This app wants to show notifications. Do you allow?",
-  );
+  )
+  .await?;
   match answer {
     crate::api::dialog::AskResponse::Yes => {
       settings.allow_notification = Some(true);
@@ -0,0 +1,113 @@
+// Copyright 2019-2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use super::InvokeResponse;
+use serde::Deserialize;
+
+#[cfg(fs_path)]
+use std::path::{Path, PathBuf};
+
+/// The API descriptor.
+#[derive(Deserialize)]
+#[serde(tag = "cmd", rename_all = "camelCase")]
+pub enum Cmd {
+  /// Joins the paths, then normalizes the result.
+  Join { paths: Vec<String> },
+  /// Normalizes the path.
+  Normalize { path: String },
+  /// Returns the parent directory of the path.
+  Dirname { path: String },
+  /// Returns the last component of the path, optionally without the given extension.
+  Basename { path: String, ext: Option<String> },
+  /// Returns the extension of the path.
+  Extname { path: String },
+  /// Checks if the path is absolute.
+  IsAbsolute { path: String },
+  /// Returns the relative path from `from` to `to`.
+  Relative { from: String, to: String },
+}
+
+impl Cmd {
+  #[allow(unused_variables)]
+  pub fn run(self) -> crate::Result<InvokeResponse> {
+    #[cfg(fs_path)]
+    return match self {
+      Self::Join { paths } => Ok(join(paths).into()),
+      Self::Normalize { path } => Ok(crate::api::path::normalize(path).into()),
+      Self::Dirname { path } => dirname(path).map(Into::into),
+      Self::Basename { path, ext } => basename(path, ext).map(Into::into),
+      Self::Extname { path } => extname(path).map(Into::into),
+      Self::IsAbsolute { path } => Ok(Path::new(&path).is_absolute().into()),
+      Self::Relative { from, to } => crate::api::path::relative(from, to)
+        .map(Into::into)
+        .map_err(Into::into),
+    };
+    #[cfg(not(fs_path))]
+    Err(crate::Error::ApiNotAllowlisted("fs > pathApi".to_string()))
+  }
+}
+
+#[cfg(fs_path)]
+fn join(paths: Vec<String>) -> PathBuf {
+  // an absolute path replaces the previous ones, like `PathBuf::push`
+  let path = paths.into_iter().collect::<PathBuf>();
+  crate::api::path::normalize(path)
+}
+
+#[cfg(fs_path)]
+fn dirname(path: String) -> crate::Result<PathBuf> {
+  match Path::new(&path).parent() {
+    Some(parent) if parent.as_os_str().is_empty() => Ok(PathBuf::from(".")),
+    Some(parent) => Ok(parent.to_path_buf()),
+    None => Err(path_error(format!("{} has no parent directory", path))),
+  }
+}
+
+#[cfg(fs_path)]
+fn basename(path: String, ext: Option<String>) -> crate::Result<String> {
+  let name = Path::new(&path)
+    .file_name()
+    .ok_or_else(|| path_error(format!("{} has no file name", path)))?
+    .to_string_lossy();
+  let name = match ext {
+    Some(ext) => name.strip_suffix(ext.as_str()).unwrap_or(&name),
+    None => &name,
+  };
+  Ok(name.to_string())
+}
+
+#[cfg(fs_path)]
+fn extname(path: String) -> crate::Result<String> {
+  Path::new(&path)
+    .extension()
+    .map(|ext| ext.to_string_lossy().into_owned())
+    .ok_or_else(|| path_error(format!("{} has no extension", path)))
+}
+
+#[cfg(fs_path)]
+fn path_error(message: String) -> crate::Error {
+  crate::api::Error::Path(message).into()
+}
+
+#[cfg(all(test, fs_path))]
+mod test {
+  use super::*;
+
+  #[test]
+  fn path_components() {
+    assert_eq!(
+      join(vec!["a".into(), "b/../c".into()]),
+      Path::new("a").join("c")
+    );
+    assert_eq!(dirname("a/b.txt".into()).unwrap(), Path::new("a"));
+    assert_eq!(dirname("b.txt".into()).unwrap(), Path::new("."));
+    assert_eq!(basename("a/b.txt".into(), None).unwrap(), "b.txt");
+    assert_eq!(
+      basename("a/b.txt".into(), Some(".txt".into())).unwrap(),
+      "b"
+    );
+    assert_eq!(extname("a/b.tar.gz".into()).unwrap(), "gz");
+    assert!(extname("a/b".into()).is_err());
+  }
+}
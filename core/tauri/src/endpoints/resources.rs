@@ -0,0 +1,146 @@
+// Copyright 2019-2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use crate::{runtime::window::WindowEvent, Params, Window};
+
+use std::{
+  collections::{HashMap, HashSet},
+  sync::{Arc, Mutex},
+};
+
+/// The identifier of a resource on a [`ResourceTable`].
+pub type ResourceId = u32;
+
+/// A table of resources owned by the webview windows, e.g. open files.
+///
+/// A resource can only be used by the window that owns it,
+/// and the resources of a window are dropped when the window is destroyed.
+pub struct ResourceTable<T> {
+  // each resource has its own lock, so a slow operation doesn't block the other resources
+  resources: Mutex<HashMap<ResourceId, (String, Arc<Mutex<T>>)>>,
+  // the windows with a destroy listener
+  windows: Mutex<HashSet<String>>,
+}
+
+impl<T> Default for ResourceTable<T> {
+  fn default() -> Self {
+    Self {
+      resources: Default::default(),
+      windows: Default::default(),
+    }
+  }
+}
+
+impl<T: Send + 'static> ResourceTable<T> {
+  /// Adds a resource owned by the window.
+  pub fn add<P: Params>(&'static self, window: &Window<P>, resource: T) -> ResourceId {
+    let label = window.label().to_string();
+    if self.windows.lock().unwrap().insert(label.clone()) {
+      let window_label = label.clone();
+      window.on_window_event(move |event| {
+        if let WindowEvent::Destroyed = event {
+          self.close_window(&window_label);
+        }
+      });
+    }
+    self.insert(label, resource)
+  }
+
+  fn insert(&self, label: String, resource: T) -> ResourceId {
+    let mut resources = self.resources.lock().unwrap();
+    let mut rid = rand::random::<ResourceId>();
+    while resources.contains_key(&rid) {
+      rid = rand::random();
+    }
+    resources.insert(rid, (label, Arc::new(Mutex::new(resource))));
+    rid
+  }
+
+  /// Runs the closure with the resource owned by the window.
+  ///
+  /// The table isn't locked while the closure runs.
+  pub fn with<R, F: FnOnce(&mut T) -> crate::Result<R>>(
+    &self,
+    window: &str,
+    rid: ResourceId,
+    f: F,
+  ) -> crate::Result<R> {
+    let resource = match self.resources.lock().unwrap().get(&rid) {
+      Some((owner, resource)) if owner == window => resource.clone(),
+      _ => return Err(crate::Error::ResourceNotFound(rid)),
+    };
+    let mut resource = resource.lock().unwrap();
+    f(&mut resource)
+  }
+
+  /// Removes the resource owned by the window from the table.
+  ///
+  /// The resource is dropped once the operations running on it finish.
+  pub fn remove(&self, window: &str, rid: ResourceId) -> Option<Arc<Mutex<T>>> {
+    let mut resources = self.resources.lock().unwrap();
+    match resources.get(&rid) {
+      Some((owner, _)) if owner == window => resources.remove(&rid).map(|(_, resource)| resource),
+      _ => None,
+    }
+  }
+
+  /// Drops the resources owned by the window.
+  pub fn close_window(&self, label: &str) {
+    self
+      .resources
+      .lock()
+      .unwrap()
+      .retain(|_, (owner, _)| owner != label);
+    self.windows.lock().unwrap().remove(label);
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn window_resources() {
+    let table = ResourceTable::default();
+    let main = table.insert("main".into(), 1);
+    let other = table.insert("other".into(), 2);
+
+    table
+      .with("main", main, |value| {
+        *value += 10;
+        Ok(())
+      })
+      .unwrap();
+    assert_eq!(table.with("main", main, |value| Ok(*value)).unwrap(), 11);
+
+    table.close_window("main");
+    assert!(table.with("main", main, |_| Ok(())).is_err());
+    assert_eq!(*table.remove("other", other).unwrap().lock().unwrap(), 2);
+    assert!(table.remove("other", other).is_none());
+  }
+
+  #[test]
+  fn other_window_resources() {
+    let table = ResourceTable::default();
+    let main = table.insert("main".into(), 1);
+
+    assert!(table.with("other", main, |_| Ok(())).is_err());
+    assert!(table.remove("other", main).is_none());
+    assert_eq!(table.with("main", main, |value| Ok(*value)).unwrap(), 1);
+  }
+
+  #[test]
+  fn with_doesnt_lock_the_table() {
+    let table = ResourceTable::default();
+    let first = table.insert("main".into(), 1);
+    let second = table.insert("main".into(), 2);
+
+    let sum = table
+      .with("main", first, |first| {
+        table.with("main", second, |second| Ok(*first + *second))
+      })
+      .unwrap();
+    assert_eq!(sum, 3);
+  }
+}
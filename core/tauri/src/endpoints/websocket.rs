@@ -0,0 +1,164 @@
+// Copyright 2019-2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use super::{resources::ResourceId, InvokeResponse};
+use crate::{Config, Params, Window};
+use serde::Deserialize;
+
+#[cfg(websocket_all)]
+use super::resources::ResourceTable;
+#[cfg(websocket_all)]
+use crate::api::websocket::{Message, WebSocket};
+#[cfg(websocket_all)]
+use once_cell::sync::Lazy;
+
+use std::{collections::HashMap, sync::Arc};
+
+type ConnectionId = ResourceId;
+
+// the connections are owned by the window that created them
+#[cfg(websocket_all)]
+static CONNECTIONS: Lazy<ResourceTable<Connection>> = Lazy::new(Default::default);
+
+// Closes the connection when it's removed from the table, e.g. when its window is destroyed
+#[cfg(websocket_all)]
+struct Connection(Option<WebSocket>);
+
+#[cfg(websocket_all)]
+impl Drop for Connection {
+  fn drop(&mut self) {
+    if let Some(socket) = self.0.take() {
+      crate::async_runtime::spawn(async move {
+        let _ = socket.close().await;
+      });
+    }
+  }
+}
+
+#[cfg(websocket_all)]
+fn socket(window: &str, id: ConnectionId) -> crate::Result<WebSocket> {
+  CONNECTIONS
+    .with(window, id, |connection| Ok(connection.0.clone()))
+    .ok()
+    .flatten()
+    .ok_or(crate::Error::WebSocketNotConnected)
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub enum Buffer {
+  Text(String),
+  Raw(Vec<u8>),
+}
+
+/// The API descriptor.
+#[derive(Deserialize)]
+#[serde(tag = "cmd", rename_all = "camelCase")]
+pub enum Cmd {
+  /// Connect to a WebSocket server, the events are sent to `on_event_fn`.
+  #[serde(rename_all = "camelCase")]
+  Connect {
+    url: String,
+    headers: Option<HashMap<String, String>>,
+    on_event_fn: String,
+  },
+  /// Send a text or binary message.
+  Send { id: ConnectionId, buffer: Buffer },
+  /// Close the connection.
+  Disconnect { id: ConnectionId },
+}
+
+#[cfg(not(websocket_all))]
+impl Cmd {
+  pub async fn run<P: Params>(
+    self,
+    _window: Window<P>,
+    _config: Arc<Config>,
+  ) -> crate::Result<InvokeResponse> {
+    Err(crate::Error::ApiNotAllowlisted(
+      "websocket > all".to_string(),
+    ))
+  }
+}
+
+#[cfg(websocket_all)]
+impl Cmd {
+  pub async fn run<P: Params>(
+    self,
+    window: Window<P>,
+    _config: Arc<Config>,
+  ) -> crate::Result<InvokeResponse> {
+    match self {
+      Self::Connect {
+        url,
+        headers,
+        on_event_fn,
+      } => connect(window, url, headers, on_event_fn)
+        .await
+        .map(Into::into),
+      Self::Send { id, buffer } => {
+        let socket = socket(&window.label().to_string(), id)?;
+        let message = match buffer {
+          Buffer::Text(text) => Message::Text(text),
+          Buffer::Raw(data) => Message::Binary(data),
+        };
+        socket.send(message).await?;
+        Ok(().into())
+      }
+      Self::Disconnect { id } => {
+        let socket = match CONNECTIONS.remove(&window.label().to_string(), id) {
+          Some(connection) => connection.lock().unwrap().0.take(),
+          None => None,
+        };
+        if let Some(socket) = socket {
+          socket.close().await?;
+        }
+        Ok(().into())
+      }
+    }
+  }
+}
+
+/// Connects to a WebSocket server allowed by the scope, sending the connection events to the webview.
+/// The connection is closed when the window is destroyed.
+#[cfg(websocket_all)]
+async fn connect<P: Params>(
+  window: Window<P>,
+  url: String,
+  headers: Option<HashMap<String, String>>,
+  on_event_fn: String,
+) -> crate::Result<ConnectionId> {
+  use crate::{
+    api::{
+      scope::UrlScope,
+      websocket::{WebSocketBuilder, WebSocketEvent},
+    },
+    Manager,
+  };
+
+  window.state::<UrlScope>().check(&url)?;
+
+  let mut builder = WebSocketBuilder::new(url);
+  if let Some(headers) = headers {
+    builder = builder.headers(headers);
+  }
+  let (mut rx, socket) = builder.connect().await?;
+
+  let id = CONNECTIONS.add(&window, Connection(Some(socket)));
+  let label = window.label().to_string();
+
+  crate::async_runtime::spawn(async move {
+    while let Some(event) = rx.recv().await {
+      if matches!(event, WebSocketEvent::Closed(_)) {
+        CONNECTIONS.remove(&label, id);
+      }
+      let js = crate::api::rpc::format_callback(on_event_fn.clone(), &event)
+        .expect("unable to serialize WebSocketEvent");
+
+      let _ = window.eval(js.as_str());
+    }
+  });
+
+  Ok(id)
+}
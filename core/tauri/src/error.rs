@@ -48,6 +48,12 @@ pub enum Error {
   /// Client with specified ID not found.
   #[error("http client dropped or not initialized")]
   HttpClientNotInitialized,
+  /// Resource with specified ID not found, e.g. a closed file handle.
+  #[error("resource {0} closed or not found")]
+  ResourceNotFound(u32),
+  /// WebSocket connection with specified ID not found.
+  #[error("websocket closed or not connected")]
+  WebSocketNotConnected,
   /// API not enabled by Tauri.
   #[error("{0}")]
   ApiNotEnabled(String),
@@ -10,6 +10,7 @@ use crate::{
     assets::Assets,
     config::{AppUrl, Config, WindowUrl},
     path::{resolve_path, BaseDirectory},
+    scope::FsScope,
     PackageInfo,
   },
   app::{GlobalWindowEvent, GlobalWindowEventListener},
@@ -26,6 +27,7 @@ use crate::{
     window::{dpi::PhysicalSize, DetachedWindow, PendingWindow, WindowEvent},
     Icon, MenuId, Params, Runtime,
   },
+  sealed::ManagerBase,
   App, Context, Invoke, StateManager, Window,
 };
 
@@ -791,12 +793,18 @@ fn on_window_event<P: Params>(window: &Window<P>, event: &WindowEvent) -> crate:
         .unwrap_or_else(|_| panic!("unhandled event")),
       Some(()),
     )?,
-    WindowEvent::Destroyed => window.emit(
-      &WINDOW_DESTROYED_EVENT
-        .parse()
-        .unwrap_or_else(|_| panic!("unhandled event")),
-      Some(()),
-    )?,
+    WindowEvent::Destroyed => {
+      // the paths granted to the window must not be inherited by a new window with the same label
+      if let Some(scope) = window.manager().state().try_get::<FsScope>() {
+        scope.forget_window(&window.label().to_string());
+      }
+      window.emit(
+        &WINDOW_DESTROYED_EVENT
+          .parse()
+          .unwrap_or_else(|_| panic!("unhandled event")),
+        Some(()),
+      )?
+    }
     WindowEvent::Focused(focused) => window.emit(
       &if *focused {
         WINDOW_FOCUS_EVENT
@@ -57,4 +57,9 @@ impl StateManager {
   pub fn get<T: Send + Sync + 'static>(&self) -> State<'_, T> {
     State(self.0.get())
   }
+
+  /// Gets the state associated with the specified type, if it's managed.
+  pub(crate) fn try_get<T: Send + Sync + 'static>(&self) -> Option<State<'_, T>> {
+    self.0.try_get().map(State)
+  }
 }
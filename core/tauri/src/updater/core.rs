@@ -2,11 +2,15 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-License-Identifier: MIT
 
-use super::error::{Error, Result};
-use crate::api::{file::Extract, version};
+use super::{
+  error::{Error, Result},
+  rollback::{rolled_back_versions, RollbackState},
+};
+use crate::api::{config::UpdaterPubkey, file::Extract, version};
 use base64::decode;
 use http::StatusCode;
 use minisign_verify::{PublicKey, Signature};
+use serde::Deserialize;
 use std::{
   collections::HashMap,
   env,
@@ -21,10 +25,19 @@ use std::{
 #[cfg(not(target_os = "macos"))]
 use std::process::Command;
 
-#[cfg(target_os = "macos")]
+#[cfg(target_os = "linux")]
+use qbsdiff::Bspatch;
+#[cfg(target_os = "linux")]
+use sha2::{Digest, Sha256};
+#[cfg(target_os = "linux")]
+use std::{fs, io::Cursor, process::Stdio};
+
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+use super::rollback::{path_with_suffix, remove_path};
+#[cfg(any(target_os = "macos", target_os = "linux"))]
 use crate::api::file::Move;
 
-use crate::api::http::{ClientBuilder, HttpRequestBuilder};
+use crate::api::http::{ClientBuilder, HttpRequestBuilder, Response, RetryPolicy};
 
 #[cfg(target_os = "windows")]
 use std::process::exit;
@@ -41,6 +54,20 @@ pub struct RemoteRelease {
   pub body: Option<String>,
   /// Optional signature for the current platform
   pub signature: Option<String>,
+  /// Binary patches for the current platform, keyed by the version they apply to
+  pub patches: HashMap<String, RemotePatch>,
+}
+
+/// A binary patch from a previous version, applied to the installed file instead of
+/// downloading the whole update.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RemotePatch {
+  /// Download URL of the patch
+  pub url: String,
+  /// Optional signature of the patch
+  pub signature: Option<String>,
+  /// SHA-256 hash (hex) of the patched file
+  pub sha256: String,
 }
 
 impl RemoteRelease {
@@ -84,6 +111,9 @@ impl RemoteRelease {
       .map(|signature| signature.as_str().unwrap_or("").to_string());
 
     let download_url;
+    // patches are optional, an invalid map is ignored
+    // as we can always download the whole update
+    let mut patches = read_patches(release);
 
     match release.get("platforms") {
       //
@@ -107,6 +137,7 @@ impl RemoteRelease {
           signature = current_target_data
             .get("signature")
             .map(|found_signature| found_signature.as_str().unwrap_or("").to_string());
+          patches = read_patches(current_target_data);
           // Download URL is required
           download_url = current_target_data
             .get("url")
@@ -141,10 +172,19 @@ impl RemoteRelease {
       download_url,
       body,
       signature,
+      patches,
     })
   }
 }
 
+// Read the `patches` map, keyed by the version the patch applies to
+fn read_patches(data: &serde_json::Value) -> HashMap<String, RemotePatch> {
+  data
+    .get("patches")
+    .and_then(|patches| serde_json::from_value(patches.clone()).ok())
+    .unwrap_or_default()
+}
+
 pub struct UpdateBuilder<'a> {
   /// Current version we are running to compare with announced version
   pub current_version: &'a str,
@@ -154,6 +194,21 @@ pub struct UpdateBuilder<'a> {
   pub target: Option<String>,
   /// The current executable path. Default is automatically extracted.
   pub executable_path: Option<PathBuf>,
+  /// The public keys trusted to validate the signatures.
+  pub pubkeys: Vec<UpdaterPubkey>,
+  /// Whether the manifest must be signed with one of the trusted public keys.
+  pub verify_manifest: bool,
+  /// Where the rollback state is written. The previous version is only kept as a backup when set.
+  pub rollback_state_path: Option<PathBuf>,
+  /// Custom headers added to the manifest and download requests.
+  pub headers: HashMap<String, String>,
+  /// The proxy used to reach the update server.
+  pub proxy: Option<String>,
+  /// The retry policy of each endpoint before falling back to the next one.
+  pub retry: RetryPolicy,
+  /// How the application was installed on Linux. Default is detected from the executable path.
+  #[cfg(target_os = "linux")]
+  pub linux_install: Option<LinuxInstall>,
 }
 
 impl<'a> Default for UpdateBuilder<'a> {
@@ -162,6 +217,14 @@ impl<'a> Default for UpdateBuilder<'a> {
       urls: Vec::new(),
       target: None,
       executable_path: None,
+      pubkeys: Vec::new(),
+      verify_manifest: false,
+      rollback_state_path: None,
+      headers: HashMap::new(),
+      proxy: None,
+      retry: RetryPolicy::default(),
+      #[cfg(target_os = "linux")]
+      linux_install: None,
       current_version: env!("CARGO_PKG_VERSION"),
     }
   }
@@ -211,6 +274,61 @@ impl<'a> UpdateBuilder<'a> {
     self
   }
 
+  /// Set how the application was installed on Linux, instead of detecting it from the executable path
+  #[cfg(all(test, target_os = "linux"))]
+  pub fn linux_install(mut self, linux_install: LinuxInstall) -> Self {
+    self.linux_install = Some(linux_install);
+    self
+  }
+
+  /// Add a public key trusted to validate the signatures
+  pub fn pubkey(mut self, pubkey: UpdaterPubkey) -> Self {
+    self.pubkeys.push(pubkey);
+    self
+  }
+
+  /// Add multiple trusted public keys at once, used to rotate the signing key
+  pub fn pubkeys(mut self, pubkeys: &[UpdaterPubkey]) -> Self {
+    self.pubkeys.extend_from_slice(pubkeys);
+    self
+  }
+
+  /// Require the manifest to be signed with one of the trusted public keys.
+  /// The signature is downloaded from the endpoint URL with a `.sig` suffix.
+  pub fn verify_manifest(mut self, verify_manifest: bool) -> Self {
+    self.verify_manifest = verify_manifest;
+    self
+  }
+
+  /// Add a header to the manifest and download requests, e.g. an `Authorization` token
+  pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+    self.headers.insert(name.into(), value.into());
+    self
+  }
+
+  /// Set the proxy used to reach the update server, e.g. `http://proxy.example.com:8080`
+  pub fn proxy(mut self, proxy: impl Into<String>) -> Self {
+    self.proxy = Some(proxy.into());
+    self
+  }
+
+  /// Set the retry policy of the update requests. Each endpoint is retried before falling back to the next one.
+  pub fn retry(mut self, retry: RetryPolicy) -> Self {
+    self.retry = retry;
+    self
+  }
+
+  fn client(&self) -> ClientBuilder {
+    http_client(self.proxy.as_ref(), &self.retry)
+  }
+
+  /// Keep the previous version as a backup and write the rollback state to the given path,
+  /// so the backup can be restored if the update fails to confirm its launch.
+  pub fn rollback_state_path<A: AsRef<Path>>(mut self, rollback_state_path: A) -> Self {
+    self.rollback_state_path = Some(PathBuf::from(rollback_state_path.as_ref()));
+    self
+  }
+
   pub async fn build(self) -> Result<Update> {
     let mut remote_release: Option<RemoteRelease> = None;
 
@@ -221,6 +339,13 @@ impl<'a> UpdateBuilder<'a> {
       ));
     };
 
+    // we can't validate the manifest without any key
+    if self.verify_manifest && self.pubkeys.is_empty() {
+      return Err(Error::Builder(
+        "Unable to verify the manifest, `pubkey` is required.".into(),
+      ));
+    };
+
     // set current version if not set
     let current_version = self.current_version;
 
@@ -233,16 +358,37 @@ impl<'a> UpdateBuilder<'a> {
       env::current_exe().expect("Can't access current executable path.")
     };
 
+    // Each Linux installation method has its own update strategy
+    #[cfg(target_os = "linux")]
+    let linux_install = match (self.linux_install, &self.executable_path) {
+      (Some(linux_install), _) => linux_install,
+      (None, Some(executable_path)) => LinuxInstall::detect(executable_path),
+      (None, None) => LinuxInstall::current(),
+    };
+
     // Did the target is provided by the config?
-    // Should be: linux, darwin, win32 or win64
+    // Should be: linux, linux-deb, linux-rpm, linux-binary, darwin, win32 or win64
     let target = if let Some(t) = &self.target {
       t.clone()
     } else {
       get_updater_target().ok_or(Error::UnsupportedPlatform)?
     };
+    // the Linux target depends on the installation method
+    #[cfg(target_os = "linux")]
+    let target = match self.target {
+      Some(_) => target,
+      None => linux_install.target(target),
+    };
 
     // Get the extract_path from the provided executable_path
     let extract_path = extract_path_from_executable(&executable_path);
+    // a plain binary is swapped with the new one so we need the binary path itself
+    #[cfg(target_os = "linux")]
+    let extract_path = if linux_install == LinuxInstall::Binary {
+      executable_path.clone()
+    } else {
+      extract_path
+    };
 
     // Set SSL certs for linux if they aren't available.
     // We do not require to recheck in the download_and_install as we use
@@ -277,20 +423,12 @@ impl<'a> UpdateBuilder<'a> {
       let mut headers = HashMap::new();
       headers.insert("Accept".into(), "application/json".into());
 
-      let resp = ClientBuilder::new()
-        .build()?
-        .send(
-          HttpRequestBuilder::new("GET", &fixed_link)
-            .headers(headers)
-            // wait 20sec for the firewall
-            .timeout(20),
-        )
-        .await;
+      let resp = send_request(&fixed_link, headers, &self.headers, self.client()).await;
 
       // If we got a success, we stop the loop
       // and we set our remote_release variable
       if let Ok(res) = resp {
-        let res = res.read().await?;
+        let res = res.bytes().await?;
         // got status code 2XX
         if StatusCode::from_u16(res.status).unwrap().is_success() {
           // if we got 204
@@ -299,8 +437,25 @@ impl<'a> UpdateBuilder<'a> {
             // we should catch on the client
             return Err(Error::UpToDate);
           };
+          // make sure the manifest has been signed by one of our trusted keys
+          // otherwise a compromised server could announce any release
+          if self.verify_manifest {
+            if let Err(err) = verify_manifest_signature(
+              &fixed_link,
+              &res.data,
+              &self.pubkeys,
+              &self.headers,
+              self.client(),
+            )
+            .await
+            {
+              last_error = Some(err);
+              continue;
+            }
+          }
+          let release: serde_json::Value = serde_json::from_slice(&res.data)?;
           // Convert the remote result to our local struct
-          let built_release = RemoteRelease::from_release(&res.data, &target);
+          let built_release = RemoteRelease::from_release(&release, &target);
           // make sure all went well and the remote data is compatible
           // with what we need locally
           match built_release {
@@ -326,9 +481,24 @@ impl<'a> UpdateBuilder<'a> {
       Error::RemoteMetadata("Unable to extract update metadata from the remote server.".into())
     })?;
 
+    // the patch from our current version, if any
+    #[cfg(target_os = "linux")]
+    let patch = final_release
+      .patches
+      .iter()
+      .find(|(from_version, _)| from_version.trim_start_matches('v') == current_version)
+      .map(|(_, patch)| patch.clone());
+
+    // a version restored by a rollback failed to start, so it isn't offered again
+    let rolled_back = match &self.rollback_state_path {
+      Some(state_path) => rolled_back_versions(state_path),
+      None => Vec::new(),
+    };
+
     // did the announced version is greated than our current one?
-    let should_update =
-      version::is_greater(&current_version, &final_release.version).unwrap_or(false);
+    let should_update = version::is_greater(&current_version, &final_release.version)
+      .unwrap_or(false)
+      && !rolled_back.contains(&final_release.version);
 
     // create our new updater
     Ok(Update {
@@ -341,6 +511,17 @@ impl<'a> UpdateBuilder<'a> {
       download_url: final_release.download_url,
       body: final_release.body,
       signature: final_release.signature,
+      pubkeys: self.pubkeys,
+      rollback_state_path: self.rollback_state_path,
+      headers: self.headers,
+      proxy: self.proxy,
+      retry: self.retry,
+      #[cfg(target_os = "linux")]
+      patch,
+      #[cfg(target_os = "linux")]
+      package_installer: PackageInstaller::for_install(linux_install),
+      #[cfg(target_os = "linux")]
+      linux_install,
     })
   }
 }
@@ -369,25 +550,44 @@ pub struct Update {
   download_url: String,
   /// Signature announced
   signature: Option<String>,
+  /// Public keys trusted to validate the signature
+  pubkeys: Vec<UpdaterPubkey>,
+  /// Where the rollback state is written
+  rollback_state_path: Option<PathBuf>,
+  /// Custom headers added to the download request
+  headers: HashMap<String, String>,
+  /// The proxy used to reach the update server
+  proxy: Option<String>,
+  /// The retry policy of the update requests
+  retry: RetryPolicy,
+  /// Binary patch from the running version
+  #[cfg(target_os = "linux")]
+  patch: Option<RemotePatch>,
+  /// How the application was installed
+  #[cfg(target_os = "linux")]
+  linux_install: LinuxInstall,
+  /// Command used to install the `.deb` and `.rpm` packages
+  #[cfg(target_os = "linux")]
+  package_installer: PackageInstaller,
 }
 
 impl Update {
+  fn client(&self) -> ClientBuilder {
+    http_client(self.proxy.as_ref(), &self.retry)
+  }
+
   // Download and install our update
   // @todo(lemarier): Split into download and install (two step) but need to be thread safe
-  pub async fn download_and_install(&self, pub_key: Option<String>) -> Result {
+  pub async fn download_and_install(&self) -> Result {
     // download url for selected release
     let url = self.download_url.clone();
     // extract path
     let extract_path = self.extract_path.clone();
 
-    // make sure we can install the update on linux
-    // We fail here because later we can add more linux support
-    // actually if we use APPIMAGE, our extract path should already
-    // be set with our APPIMAGE env variable, we don't need to do
-    // anythin with it yet
-    #[cfg(target_os = "linux")]
-    if env::var_os("APPIMAGE").is_none() {
-      return Err(Error::UnsupportedPlatform);
+    // never install a release older than the running one, even if the server announced it
+    // a compromised manifest could otherwise serve an old (but validly signed) build
+    if !version::is_greater(&self.current_version, &self.version).unwrap_or(false) {
+      return Err(Error::Downgrade(self.version.clone()));
     }
 
     // used  for temp file name
@@ -410,9 +610,50 @@ impl Update {
       .prefix(&format!("{}_{}_download", bin_name, current_time))
       .tempdir()?;
 
+    // try the binary patch from our version first,
+    // anything going wrong falls back to the whole update
+    #[cfg(target_os = "linux")]
+    let patched = self.download_patch(tmp_dir.path()).await.is_ok();
+    #[cfg(not(target_os = "linux"))]
+    let patched = false;
+    if !patched {
+      self.download_archive(&url, tmp_dir.path()).await?;
+    }
+
+    // we copy the files depending of the operating system
+    // we run the setup, appimage re-install, linux package install,
+    // binary swap or overwrite the macos .app
+    let keep_backup = self.rollback_state_path.is_some();
+    #[cfg(target_os = "linux")]
+    let backup_path = copy_files_and_run(
+      tmp_dir,
+      extract_path.clone(),
+      keep_backup,
+      self.linux_install,
+      &self.package_installer,
+    )?;
+    #[cfg(not(target_os = "linux"))]
+    let backup_path = copy_files_and_run(tmp_dir, extract_path.clone(), keep_backup)?;
+    // the previous version is restored on startup
+    // if the new version doesn't confirm its launch in time
+    if let (Some(state_path), Some(backup_path)) = (&self.rollback_state_path, backup_path) {
+      RollbackState {
+        version: self.version.clone(),
+        install_path: extract_path,
+        backup_path,
+        launches: 0,
+      }
+      .save(state_path)?;
+    }
+    // We are done!
+    Ok(())
+  }
+
+  // Download the update archive, validate its signature and extract it into the tmp dir
+  async fn download_archive(&self, url: &str, tmp_dir: &Path) -> Result {
     // tmp directories are used to create backup of current application
     // if something goes wrong, we can restore to previous state
-    let tmp_archive_path = tmp_dir.path().join(detect_archive_in_url(&url));
+    let tmp_archive_path = tmp_dir.join(detect_archive_in_url(url));
     let mut tmp_archive = File::create(&tmp_archive_path)?;
 
     // set our headers
@@ -421,14 +662,7 @@ impl Update {
     headers.insert("User-Agent".into(), "tauri/updater".into());
 
     // Create our request
-    let resp = ClientBuilder::new()
-      .build()?
-      .send(
-        HttpRequestBuilder::new("GET", &url)
-          .headers(headers)
-          // wait 20sec for the firewall
-          .timeout(20),
-      )
+    let resp = send_request(url, headers, &self.headers, self.client())
       .await?
       .bytes()
       .await?;
@@ -444,12 +678,12 @@ impl Update {
     tmp_archive.write_all(&resp.data)?;
 
     // Validate signature ONLY if pubkey is available in tauri.conf.json
-    if let Some(pub_key) = pub_key {
+    if !self.pubkeys.is_empty() {
       // We need an announced signature by the server
       // if there is no signature, bail out.
       if let Some(signature) = self.signature.clone() {
-        // we make sure the archive is valid and signed with the private key linked with the publickey
-        verify_signature(&tmp_archive_path, signature, &pub_key)?;
+        // we make sure the archive is valid and signed with the private key linked with one of the publickeys
+        verify_signature_with_keys(&tmp_archive_path, signature, &self.pubkeys)?;
       } else {
         // We have a public key inside our source file, but not announced by the server,
         // we assume this update is NOT valid.
@@ -457,16 +691,206 @@ impl Update {
       }
     }
     // extract using tauri api inside a tmp path
-    Extract::from_source(&tmp_archive_path).extract_into(&tmp_dir.path())?;
+    Extract::from_source(&tmp_archive_path).extract_into(tmp_dir)?;
     // Remove archive (not needed anymore)
     remove_file(&tmp_archive_path)?;
-    // we copy the files depending of the operating system
-    // we run the setup, appimage re-install or overwrite the
-    // macos .app
-    copy_files_and_run(tmp_dir, extract_path)?;
-    // We are done!
     Ok(())
   }
+
+  // Download the binary patch from the running version and apply it to the installed file,
+  // the patched file is validated against the announced hash before being installed
+  #[cfg(target_os = "linux")]
+  async fn download_patch(&self, tmp_dir: &Path) -> Result {
+    // only single file installations can be patched
+    let patch = match (&self.patch, self.linux_install) {
+      (Some(patch), LinuxInstall::AppImage) | (Some(patch), LinuxInstall::Binary) => patch,
+      _ => return Err(Error::Patch("no patch available".into())),
+    };
+
+    let mut headers = HashMap::new();
+    headers.insert("Accept".into(), "application/octet-stream".into());
+    headers.insert("User-Agent".into(), "tauri/updater".into());
+
+    let resp = send_request(&patch.url, headers, &self.headers, self.client())
+      .await?
+      .bytes()
+      .await?;
+
+    if !StatusCode::from_u16(resp.status).unwrap().is_success() {
+      return Err(Error::Network(format!(
+        "Patch download request failed with status: {}",
+        resp.status
+      )));
+    }
+
+    // the patch must be signed like the archive
+    if !self.pubkeys.is_empty() {
+      let signature = patch
+        .signature
+        .as_ref()
+        .ok_or(Error::PubkeyButNoSignature)?;
+      verify_data_with_keys(&resp.data, signature, &self.pubkeys)?;
+    }
+
+    let source = fs::read(&self.extract_path)?;
+    let patcher = Bspatch::new(&resp.data)?;
+    let mut patched = Vec::with_capacity(patcher.hint_target_size() as usize);
+    patcher.apply(&source, Cursor::new(&mut patched))?;
+
+    // the installed file may not be the one the patch was created from
+    let hash = format!("{:x}", Sha256::digest(&patched));
+    if !hash.eq_ignore_ascii_case(&patch.sha256) {
+      return Err(Error::Patch(
+        "the patched file doesn't match the announced hash".into(),
+      ));
+    }
+
+    // same file name and permissions as the installed file
+    // so it's found and installed like an extracted update
+    let file_name = self
+      .extract_path
+      .file_name()
+      .ok_or_else(|| Error::Patch("invalid installation path".into()))?;
+    let patched_path = tmp_dir.join(file_name);
+    fs::write(&patched_path, patched)?;
+    fs::set_permissions(
+      &patched_path,
+      fs::metadata(&self.extract_path)?.permissions(),
+    )?;
+
+    Ok(())
+  }
+}
+
+/// How the application was installed on Linux.
+///
+/// Each installation method has its own update strategy.
+#[cfg(target_os = "linux")]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LinuxInstall {
+  /// An AppImage, replaced by the new AppImage.
+  AppImage,
+  /// A `.deb` package, updated with the package manager.
+  Deb,
+  /// A `.rpm` package, updated with the package manager.
+  Rpm,
+  /// A plain binary, atomically swapped with the new binary.
+  Binary,
+}
+
+#[cfg(target_os = "linux")]
+impl LinuxInstall {
+  /// Detects how the running executable was installed.
+  ///
+  /// The package managers are only queried on the first call, the result is reused by the next update checks.
+  pub fn current() -> Self {
+    static CURRENT: once_cell::sync::OnceCell<LinuxInstall> = once_cell::sync::OnceCell::new();
+    *CURRENT.get_or_init(|| match env::current_exe() {
+      Ok(executable_path) => Self::detect(&executable_path),
+      Err(_) => Self::Binary,
+    })
+  }
+
+  /// Detects how the given executable was installed.
+  pub fn detect(executable_path: &Path) -> Self {
+    if env::var_os("APPIMAGE").is_some() {
+      Self::AppImage
+    } else if is_owned_by_package("dpkg", "-S", executable_path) {
+      Self::Deb
+    } else if is_owned_by_package("rpm", "-qf", executable_path) {
+      Self::Rpm
+    } else {
+      Self::Binary
+    }
+  }
+
+  /// The updater target announced to the server for this installation method,
+  /// e.g. `linux` for an AppImage and `linux-deb` for a `.deb` package.
+  pub fn target(&self, os_target: String) -> String {
+    match self {
+      Self::AppImage => os_target,
+      Self::Deb => format!("{}-deb", os_target),
+      Self::Rpm => format!("{}-rpm", os_target),
+      Self::Binary => format!("{}-binary", os_target),
+    }
+  }
+}
+
+// Ask the package manager if the file belongs to an installed package
+#[cfg(target_os = "linux")]
+fn is_owned_by_package(program: &str, query_flag: &str, path: &Path) -> bool {
+  Command::new(program)
+    .arg(query_flag)
+    .arg(path)
+    .stdout(Stdio::null())
+    .stderr(Stdio::null())
+    .status()
+    .map(|status| status.success())
+    .unwrap_or(false)
+}
+
+/// The command used to install a downloaded `.deb` or `.rpm` package.
+///
+/// The package path is appended to the arguments.
+#[cfg(target_os = "linux")]
+#[derive(Debug, Clone)]
+pub struct PackageInstaller {
+  program: String,
+  args: Vec<String>,
+}
+
+#[cfg(target_os = "linux")]
+impl PackageInstaller {
+  /// Creates a new package installer command.
+  pub fn new(program: impl Into<String>, args: Vec<String>) -> Self {
+    Self {
+      program: program.into(),
+      args,
+    }
+  }
+
+  // The package manager requires root privileges, so we ask them with `pkexec`
+  fn for_install(linux_install: LinuxInstall) -> Self {
+    match linux_install {
+      LinuxInstall::Rpm => Self::new("pkexec", vec!["rpm".into(), "-U".into()]),
+      _ => Self::new("pkexec", vec!["dpkg".into(), "-i".into()]),
+    }
+  }
+
+  fn install(&self, package_path: &Path) -> Result {
+    let status = Command::new(&self.program)
+      .args(&self.args)
+      .arg(package_path)
+      .status()?;
+
+    if status.success() {
+      Ok(())
+    } else {
+      Err(Error::PackageInstall(format!(
+        "`{}` exited with {}",
+        self.program, status
+      )))
+    }
+  }
+}
+
+// Linux
+
+#[cfg(target_os = "linux")]
+fn copy_files_and_run(
+  tmp_dir: tempfile::TempDir,
+  extract_path: PathBuf,
+  keep_backup: bool,
+  linux_install: LinuxInstall,
+  package_installer: &PackageInstaller,
+) -> Result<Option<PathBuf>> {
+  match linux_install {
+    LinuxInstall::AppImage => copy_app_image(tmp_dir, extract_path, keep_backup),
+    // the package manager owns the installed files, we can't keep a backup
+    LinuxInstall::Deb => install_package(tmp_dir, "deb", package_installer).map(|_| None),
+    LinuxInstall::Rpm => install_package(tmp_dir, "rpm", package_installer).map(|_| None),
+    LinuxInstall::Binary => swap_binary(tmp_dir, extract_path, keep_backup),
+  }
 }
 
 // Linux (AppImage)
@@ -479,11 +903,22 @@ impl Update {
 // We should have an AppImage already installed to be able to copy and install
 // the extract_path is the current AppImage path
 // tmp_dir is where our new AppImage is found
+// with `keep_backup`, the current AppImage is kept next to it with a `.old` suffix
 
 #[cfg(target_os = "linux")]
-fn copy_files_and_run(tmp_dir: tempfile::TempDir, extract_path: PathBuf) -> Result {
-  // we delete our current AppImage (we'll create a new one later)
-  remove_file(&extract_path)?;
+fn copy_app_image(
+  tmp_dir: tempfile::TempDir,
+  extract_path: PathBuf,
+  keep_backup: bool,
+) -> Result<Option<PathBuf>> {
+  let backup_path = path_with_suffix(&extract_path, ".old");
+  if keep_backup {
+    remove_path(&backup_path)?;
+    fs::rename(&extract_path, &backup_path)?;
+  } else {
+    // we delete our current AppImage (we'll create a new one later)
+    remove_file(&extract_path)?;
+  }
 
   // In our tempdir we expect 1 directory (should be the <app>.app)
   let paths = read_dir(&tmp_dir).unwrap();
@@ -501,11 +936,99 @@ fn copy_files_and_run(tmp_dir: tempfile::TempDir, extract_path: PathBuf) -> Resu
         .status()?;
 
       // early finish we have everything we need here
-      return Ok(());
+      return Ok(if keep_backup { Some(backup_path) } else { None });
     }
   }
 
-  Ok(())
+  // no AppImage in the archive, put the current one back in place
+  if keep_backup {
+    fs::rename(&backup_path, &extract_path)?;
+  }
+
+  Ok(None)
+}
+
+// Linux (deb and rpm)
+
+// ### Expected structure:
+// ├── [AppName]_[version]_amd64.deb.tar.gz       # GZ generated by tauri-bundler
+// │   └──[AppName]_[version]_amd64.deb           # Application package (.deb or .rpm)
+// └── ...
+
+// The package is handed to the package manager, which replaces the installed version.
+
+#[cfg(target_os = "linux")]
+fn install_package(
+  tmp_dir: tempfile::TempDir,
+  extension: &str,
+  package_installer: &PackageInstaller,
+) -> Result {
+  let paths = read_dir(&tmp_dir)?;
+
+  for path in paths {
+    let found_path = path?.path();
+    // make sure it's our package
+    if found_path.extension() == Some(OsStr::new(extension)) {
+      return package_installer.install(&found_path);
+    }
+  }
+
+  Err(Error::PackageInstall(format!(
+    "no .{} package found in the update archive",
+    extension
+  )))
+}
+
+// Linux (plain binary)
+
+// ### Expected structure:
+// ├── [AppName].tar.gz                           # GZ generated by tauri-bundler
+// │   └──[AppName]                               # Application binary
+// └── ...
+
+// The extract_path is the current binary path, replaced with an atomic rename
+// with `keep_backup`, the current binary is kept next to it with a `.old` suffix
+
+#[cfg(target_os = "linux")]
+fn swap_binary(
+  tmp_dir: tempfile::TempDir,
+  extract_path: PathBuf,
+  keep_backup: bool,
+) -> Result<Option<PathBuf>> {
+  let paths = read_dir(&tmp_dir)?;
+
+  for path in paths {
+    let found_path = path?.path();
+    if found_path.is_file() {
+      // `rename` only works on the same filesystem
+      // so we copy the new binary next to the current one first
+      let new_binary_path = path_with_suffix(&extract_path, ".new");
+      fs::copy(&found_path, &new_binary_path)?;
+      // keep the permissions of the current binary (executable bit)
+      fs::set_permissions(&new_binary_path, fs::metadata(&extract_path)?.permissions())?;
+
+      let backup_path = path_with_suffix(&extract_path, ".old");
+      remove_path(&backup_path)?;
+      let swapped = Move::from_source(&new_binary_path)
+        .replace_using_temp(&backup_path)
+        .to_dest(&extract_path);
+      if let Err(e) = swapped {
+        let _ = remove_file(&new_binary_path);
+        return Err(e.into());
+      }
+      if !keep_backup {
+        remove_file(&backup_path)?;
+        return Ok(None);
+      }
+
+      // early finish we have everything we need here
+      return Ok(Some(backup_path));
+    }
+  }
+
+  Err(Error::PackageInstall(
+    "no binary found in the update archive".into(),
+  ))
 }
 
 // Windows
@@ -523,9 +1046,15 @@ fn copy_files_and_run(tmp_dir: tempfile::TempDir, extract_path: PathBuf) -> Resu
 // ## EXE
 // Update server can provide a custom EXE (installer) who can run any task.
 
+// The installer replaces the application, so no backup is kept on Windows.
+
 #[cfg(target_os = "windows")]
 #[allow(clippy::unnecessary_wraps)]
-fn copy_files_and_run(tmp_dir: tempfile::TempDir, _extract_path: PathBuf) -> Result {
+fn copy_files_and_run(
+  tmp_dir: tempfile::TempDir,
+  _extract_path: PathBuf,
+  _keep_backup: bool,
+) -> Result<Option<PathBuf>> {
   let paths = read_dir(&tmp_dir).unwrap();
   // This consumes the TempDir without deleting directory on the filesystem,
   // meaning that the directory will no longer be automatically deleted.
@@ -555,7 +1084,7 @@ fn copy_files_and_run(tmp_dir: tempfile::TempDir, _extract_path: PathBuf) -> Res
     }
   }
 
-  Ok(())
+  Ok(None)
 }
 
 // MacOS
@@ -567,8 +1096,14 @@ fn copy_files_and_run(tmp_dir: tempfile::TempDir, _extract_path: PathBuf) -> Res
 // │          └── ...
 // └── ...
 
+// With `keep_backup`, the current .app is copied next to it with a `.old` suffix
+
 #[cfg(target_os = "macos")]
-fn copy_files_and_run(tmp_dir: tempfile::TempDir, extract_path: PathBuf) -> Result {
+fn copy_files_and_run(
+  tmp_dir: tempfile::TempDir,
+  extract_path: PathBuf,
+  keep_backup: bool,
+) -> Result<Option<PathBuf>> {
   // In our tempdir we expect 1 directory (should be the <app>.app)
   let paths = read_dir(&tmp_dir).unwrap();
 
@@ -576,15 +1111,25 @@ fn copy_files_and_run(tmp_dir: tempfile::TempDir, extract_path: PathBuf) -> Resu
     let found_path = path.expect("Unable to extract").path();
     // make sure it's our .app
     if found_path.extension() == Some(OsStr::new("app")) {
+      if keep_backup {
+        let backup_path = path_with_suffix(&extract_path, ".old");
+        remove_path(&backup_path)?;
+        // Walk the temp dir and copy all files by replacing existing files only
+        // and creating directories if needed, the current files are copied to the backup first
+        Move::from_source(&found_path)
+          .replace_using_temp(&backup_path)
+          .walk_to_dest(&extract_path)?;
+        return Ok(Some(backup_path));
+      }
       // Walk the temp dir and copy all files by replacing existing files only
       // and creating directories if needed
       Move::from_source(&found_path).walk_to_dest(&extract_path)?;
       // early finish we have everything we need here
-      return Ok(());
+      return Ok(None);
     }
   }
 
-  Ok(())
+  Ok(None)
 }
 
 /// Returns a target os
@@ -593,6 +1138,8 @@ fn copy_files_and_run(tmp_dir: tempfile::TempDir, extract_path: PathBuf) -> Resu
 /// the updater do not support the platform.
 ///
 /// Available target: `linux, darwin, win32, win64`
+///
+/// On Linux, [`LinuxInstall::target`] adds the installation method to the target.
 pub fn get_updater_target() -> Option<String> {
   if cfg!(target_os = "linux") {
     Some("linux".into())
@@ -678,6 +1225,60 @@ fn base64_to_string(base64_string: &str) -> Result<String> {
   Ok(result)
 }
 
+// The client of the update requests, through the proxy if any
+fn http_client(proxy: Option<&String>, retry: &RetryPolicy) -> ClientBuilder {
+  let mut client = ClientBuilder::new().retry(retry.clone());
+  if let Some(proxy) = proxy {
+    client = client.proxy(proxy.clone());
+  }
+  client
+}
+
+// Send a GET request to the update server with our custom headers
+// (they take precedence over the default ones)
+async fn send_request(
+  url: &str,
+  mut headers: HashMap<String, String>,
+  custom_headers: &HashMap<String, String>,
+  client: ClientBuilder,
+) -> crate::api::Result<Response> {
+  headers.extend(custom_headers.clone());
+
+  client
+    .build()?
+    .send(
+      HttpRequestBuilder::new("GET", url)
+        .headers(headers)
+        // wait 20sec for the firewall
+        .timeout(20),
+    )
+    .await
+}
+
+// Download the manifest signature (`<url>.sig`) and validate the manifest
+// with our trusted public keys
+async fn verify_manifest_signature(
+  url: &str,
+  manifest: &[u8],
+  pub_keys: &[UpdaterPubkey],
+  custom_headers: &HashMap<String, String>,
+  client: ClientBuilder,
+) -> Result<bool> {
+  let signature = send_request(
+    &format!("{}.sig", url),
+    HashMap::new(),
+    custom_headers,
+    client,
+  )
+  .await
+  .map_err(|_| Error::ManifestButNoSignature)?
+  .bytes()
+  .await?;
+
+  let signature = from_utf8(&signature.data)?.trim();
+  verify_data_with_keys(manifest, signature, pub_keys)
+}
+
 // Validate signature
 // need to be public because its been used
 // by our tests in the bundler
@@ -686,14 +1287,62 @@ pub fn verify_signature(
   release_signature: String,
   pub_key: &str,
 ) -> Result<bool> {
+  let data = read_archive(archive_path);
+  verify_data(&data, &release_signature, pub_key)
+}
+
+// Validate signature with a list of trusted public keys
+// The signature is valid if any of the non-expired keys validates it
+pub fn verify_signature_with_keys(
+  archive_path: &Path,
+  release_signature: String,
+  pub_keys: &[UpdaterPubkey],
+) -> Result<bool> {
+  let data = read_archive(archive_path);
+  verify_data_with_keys(&data, &release_signature, pub_keys)
+}
+
+fn verify_data_with_keys(
+  data: &[u8],
+  release_signature: &str,
+  pub_keys: &[UpdaterPubkey],
+) -> Result<bool> {
+  let now = SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .expect("Unable to get Unix Epoch")
+    .as_secs();
+
+  let mut last_error: Option<Error> = None;
+  for pub_key in pub_keys {
+    // skip the keys that are not trusted anymore
+    if matches!(pub_key.expires_at, Some(expires_at) if expires_at <= now) {
+      continue;
+    }
+    match verify_data(data, release_signature, &pub_key.key) {
+      Ok(valid) => return Ok(valid),
+      Err(err) => last_error = Some(err),
+    }
+  }
+
+  // if we didn't try any key, they're all expired
+  Err(last_error.unwrap_or(Error::PubkeyExpired))
+}
+
+fn verify_data(data: &[u8], release_signature: &str, pub_key: &str) -> Result<bool> {
   // we need to convert the pub key
   let pub_key_decoded = &base64_to_string(pub_key)?;
   let public_key = PublicKey::decode(pub_key_decoded)?;
-  let signature_base64_decoded = base64_to_string(&release_signature)?;
+  let signature_base64_decoded = base64_to_string(release_signature)?;
 
   let signature =
-    Signature::decode(&signature_base64_decoded).expect("Something wrong with the signature");
+    Signature::decode(&signature_base64_decoded).map_err(|e| Error::Signature(e.to_string()))?;
 
+  // Validate signature or bail out
+  public_key.verify(data, &signature)?;
+  Ok(true)
+}
+
+fn read_archive(archive_path: &Path) -> Vec<u8> {
   // We need to open the file and extract the datas to make sure its not corrupted
   let file_open = OpenOptions::new()
     .read(true)
@@ -708,9 +1357,7 @@ pub fn verify_signature(
     .read_to_end(&mut data)
     .expect("Can't read buffer to validate signature");
 
-  // Validate signature or bail out
-  public_key.verify(&data, &signature)?;
-  Ok(true)
+  data
 }
 
 #[cfg(test)]
@@ -817,6 +1464,43 @@ mod test {
     assert!(updater.should_update);
   }
 
+  #[test]
+  fn http_updater_custom_headers() {
+    let _m = mockito::mock("GET", "/private/latest.json")
+      .match_header("authorization", "Bearer license-key")
+      .with_status(200)
+      .with_header("content-type", "application/json")
+      .with_body(generate_sample_raw_json())
+      .create();
+
+    let url = format!("{}/private/latest.json", mockito::server_url());
+
+    let check_update = block!(builder().current_version("0.0.0").url(url.clone()).build());
+    assert!(check_update.is_err());
+
+    let check_update = block!(builder()
+      .current_version("0.0.0")
+      .url(url)
+      .header("Authorization", "Bearer license-key")
+      .build());
+
+    assert!(check_update.is_ok());
+    let updater = check_update.expect("Can't check update");
+
+    assert!(updater.should_update);
+  }
+
+  #[test]
+  fn http_updater_invalid_proxy() {
+    let check_update = block!(builder()
+      .current_version("0.0.0")
+      .url(mockito::server_url())
+      .proxy("not a proxy url")
+      .build());
+
+    assert!(check_update.is_err());
+  }
+
   #[test]
   fn simple_http_updater_raw_json_win64() {
     let _m = mockito::mock("GET", "/")
@@ -969,6 +1653,387 @@ mod test {
     assert!(check_update.is_err());
   }
 
+  fn read_pubkey() -> UpdaterPubkey {
+    UpdaterPubkey {
+      key: std::fs::read_to_string("./test/updater/fixture/good_signature/update.key.pub")
+        .expect("Unable to read pubkey"),
+      expires_at: None,
+    }
+  }
+
+  #[test]
+  fn http_updater_manifest_requires_pubkey() {
+    let check_update = block!(builder()
+      .url(mockito::server_url())
+      .current_version("0.0.1")
+      .verify_manifest(true)
+      .build());
+
+    assert!(matches!(check_update, Err(Error::Builder(_))));
+  }
+
+  #[test]
+  fn http_updater_manifest_without_signature() {
+    let _m = mockito::mock("GET", "/unsigned/latest.json")
+      .with_status(200)
+      .with_header("content-type", "application/json")
+      .with_body(generate_sample_raw_json())
+      .create();
+
+    let check_update = block!(builder()
+      .url(format!("{}/unsigned/latest.json", mockito::server_url()))
+      .current_version("0.0.1")
+      .pubkey(read_pubkey())
+      .verify_manifest(true)
+      .build());
+
+    assert!(check_update.is_err());
+  }
+
+  #[test]
+  fn http_updater_manifest_bad_signature() {
+    let _m = mockito::mock("GET", "/signed/latest.json")
+      .with_status(200)
+      .with_header("content-type", "application/json")
+      .with_body(generate_sample_raw_json())
+      .create();
+
+    // valid signature, but for another file
+    let _m = mockito::mock("GET", "/signed/latest.json.sig")
+      .with_status(200)
+      .with_body_from_file("./test/updater/fixture/archives/archive.tar.gz.sig")
+      .create();
+
+    let check_update = block!(builder()
+      .url(format!("{}/signed/latest.json", mockito::server_url()))
+      .current_version("0.0.1")
+      .pubkey(read_pubkey())
+      .verify_manifest(true)
+      .build());
+
+    assert!(check_update.is_err());
+  }
+
+  // the key the signing key was rotated to, `manifest/latest.json` is signed with it
+  fn read_rotated_pubkey() -> UpdaterPubkey {
+    UpdaterPubkey {
+      key: std::fs::read_to_string("./test/updater/fixture/rotated_signature/update.key.pub")
+        .expect("Unable to read pubkey"),
+      expires_at: None,
+    }
+  }
+
+  fn mock_signed_manifest(path: &str) -> (mockito::Mock, mockito::Mock) {
+    (
+      mockito::mock("GET", path)
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body_from_file("./test/updater/fixture/manifest/latest.json")
+        .create(),
+      mockito::mock("GET", format!("{}.sig", path).as_str())
+        .with_status(200)
+        .with_body_from_file("./test/updater/fixture/manifest/latest.json.sig")
+        .create(),
+    )
+  }
+
+  #[test]
+  fn http_updater_manifest_good_signature() {
+    let _m = mock_signed_manifest("/signed-good/latest.json");
+
+    let check_update = block!(builder()
+      .url(format!("{}/signed-good/latest.json", mockito::server_url()))
+      .current_version("0.0.1")
+      .pubkey(read_rotated_pubkey())
+      .verify_manifest(true)
+      .build());
+
+    let updater = check_update.expect("Can't check update");
+    assert!(updater.should_update);
+    assert_eq!(updater.version, "2.0.0");
+  }
+
+  #[test]
+  fn http_updater_manifest_rotated_key() {
+    let _m = mock_signed_manifest("/signed-rotated/latest.json");
+
+    // the previous key expired, the manifest is signed with the new one
+    let mut previous_pubkey = read_pubkey();
+    previous_pubkey.expires_at = Some(1);
+
+    let check_update = block!(builder()
+      .url(format!(
+        "{}/signed-rotated/latest.json",
+        mockito::server_url()
+      ))
+      .current_version("0.0.1")
+      .pubkeys(&[previous_pubkey, read_rotated_pubkey()])
+      .verify_manifest(true)
+      .build());
+
+    let updater = check_update.expect("Can't check update");
+    assert!(updater.should_update);
+  }
+
+  #[test]
+  fn verify_malformed_signature() {
+    // valid base64, but not a minisign signature
+    let verified = verify_data(
+      b"manifest",
+      &base64::encode("not a signature"),
+      &read_pubkey().key,
+    );
+
+    assert!(matches!(verified, Err(Error::Signature(_))));
+  }
+
+  #[test]
+  fn verify_signature_expired_keys() {
+    let signature = std::fs::read_to_string("./test/updater/fixture/archives/archive.zip.sig")
+      .expect("Unable to read signature");
+    let mut pubkey = read_pubkey();
+    pubkey.expires_at = Some(1);
+
+    let verified = verify_signature_with_keys(
+      Path::new("./test/updater/fixture/archives/archive.zip"),
+      signature,
+      &[pubkey],
+    );
+
+    assert!(matches!(verified, Err(Error::PubkeyExpired)));
+  }
+
+  #[test]
+  fn http_updater_refuse_downgrade() {
+    let _m = mockito::mock("GET", "/")
+      .with_status(200)
+      .with_header("content-type", "application/json")
+      .with_body(generate_sample_raw_json())
+      .create();
+
+    let check_update = block!(builder()
+      .current_version("10.0.0")
+      .url(mockito::server_url())
+      .build());
+
+    let updater = check_update.expect("Can't check update");
+    assert!(!updater.should_update);
+
+    let install_process = block!(updater.download_and_install());
+    assert!(matches!(install_process, Err(Error::Downgrade(_))));
+  }
+
+  #[cfg(target_os = "linux")]
+  #[test]
+  fn linux_package_with_fake_installer() {
+    let tmp_dir = tempfile::tempdir().expect("Can't create tmp_dir");
+    std::fs::write(tmp_dir.path().join("app_2.0.0_amd64.deb"), b"new package")
+      .expect("Can't write package");
+
+    let output_dir = tempfile::tempdir().expect("Can't create output_dir");
+    let installed_path = output_dir.path().join("installed.deb");
+    // the package path is appended to the arguments, so it is `$0` inside the script
+    let installer = PackageInstaller::new(
+      "sh",
+      vec![
+        "-c".into(),
+        format!("cp \"$0\" '{}'", installed_path.display()),
+      ],
+    );
+
+    let install_process = copy_files_and_run(
+      tmp_dir,
+      PathBuf::new(),
+      false,
+      LinuxInstall::Deb,
+      &installer,
+    );
+    assert!(install_process.is_ok());
+    assert_eq!(
+      std::fs::read(&installed_path).expect("Package not installed"),
+      b"new package"
+    );
+  }
+
+  #[cfg(target_os = "linux")]
+  #[test]
+  fn linux_package_installer_failure() {
+    let tmp_dir = tempfile::tempdir().expect("Can't create tmp_dir");
+    std::fs::write(tmp_dir.path().join("app_2.0.0_x86_64.rpm"), b"new package")
+      .expect("Can't write package");
+
+    let installer = PackageInstaller::new("false", Vec::new());
+    let install_process = copy_files_and_run(
+      tmp_dir,
+      PathBuf::new(),
+      false,
+      LinuxInstall::Rpm,
+      &installer,
+    );
+    assert!(matches!(install_process, Err(Error::PackageInstall(_))));
+  }
+
+  #[cfg(target_os = "linux")]
+  #[test]
+  fn linux_binary_swap() {
+    let install_dir = tempfile::tempdir().expect("Can't create install_dir");
+    let executable_path = install_dir.path().join("my_app");
+    std::fs::write(&executable_path, b"old binary").expect("Can't write binary");
+
+    let tmp_dir = tempfile::tempdir().expect("Can't create tmp_dir");
+    std::fs::write(tmp_dir.path().join("my_app"), b"new binary").expect("Can't write binary");
+
+    let install_process = copy_files_and_run(
+      tmp_dir,
+      executable_path.clone(),
+      false,
+      LinuxInstall::Binary,
+      &PackageInstaller::for_install(LinuxInstall::Binary),
+    );
+    assert!(matches!(install_process, Ok(None)));
+    assert_eq!(
+      std::fs::read(&executable_path).expect("Can't read binary"),
+      b"new binary"
+    );
+    assert!(!path_with_suffix(&executable_path, ".old").exists());
+  }
+
+  #[cfg(target_os = "linux")]
+  #[test]
+  fn linux_binary_swap_keeps_backup() {
+    let install_dir = tempfile::tempdir().expect("Can't create install_dir");
+    let executable_path = install_dir.path().join("my_app");
+    std::fs::write(&executable_path, b"old binary").expect("Can't write binary");
+
+    let tmp_dir = tempfile::tempdir().expect("Can't create tmp_dir");
+    std::fs::write(tmp_dir.path().join("my_app"), b"new binary").expect("Can't write binary");
+
+    let install_process = copy_files_and_run(
+      tmp_dir,
+      executable_path.clone(),
+      true,
+      LinuxInstall::Binary,
+      &PackageInstaller::for_install(LinuxInstall::Binary),
+    );
+    let backup_path = path_with_suffix(&executable_path, ".old");
+    assert_eq!(
+      install_process.expect("Can't swap binary"),
+      Some(backup_path.clone())
+    );
+    assert_eq!(
+      std::fs::read(&executable_path).expect("Can't read binary"),
+      b"new binary"
+    );
+    assert_eq!(
+      std::fs::read(&backup_path).expect("Can't read backup"),
+      b"old binary"
+    );
+  }
+
+  #[cfg(target_os = "linux")]
+  #[test]
+  fn linux_binary_swap_without_binary() {
+    let install_dir = tempfile::tempdir().expect("Can't create install_dir");
+    let executable_path = install_dir.path().join("my_app");
+    std::fs::write(&executable_path, b"old binary").expect("Can't write binary");
+
+    // the archive only has a directory
+    let tmp_dir = tempfile::tempdir().expect("Can't create tmp_dir");
+    std::fs::create_dir(tmp_dir.path().join("my_app")).expect("Can't create directory");
+
+    let install_process = copy_files_and_run(
+      tmp_dir,
+      executable_path.clone(),
+      false,
+      LinuxInstall::Binary,
+      &PackageInstaller::for_install(LinuxInstall::Binary),
+    );
+    assert!(matches!(install_process, Err(Error::PackageInstall(_))));
+    assert_eq!(
+      std::fs::read(&executable_path).expect("Can't read binary"),
+      b"old binary"
+    );
+  }
+
+  #[cfg(target_os = "linux")]
+  fn generate_patch_json(patch_url: &str, download_url: &str, sha256: &str) -> String {
+    format!(
+      r#"{{
+        "version": "v0.0.2",
+        "pub_date": "2020-06-25T14:14:19Z",
+        "url": "{}",
+        "patches": {{
+          "0.0.1": {{ "url": "{}", "sha256": "{}" }}
+        }}
+      }}"#,
+      download_url, patch_url, sha256
+    )
+  }
+
+  // install the patch on a plain binary, `sha256` defaults to the patched binary hash
+  #[cfg(target_os = "linux")]
+  fn install_patch(path: &str, sha256: Option<&str>) -> (PathBuf, Result) {
+    let old_binary = b"old binary".to_vec();
+    let new_binary = b"new binary, patched".to_vec();
+    let mut patch = Vec::new();
+    qbsdiff::Bsdiff::new(&old_binary, &new_binary)
+      .compare(Cursor::new(&mut patch))
+      .expect("Can't create patch");
+    let hash = format!("{:x}", Sha256::digest(&new_binary));
+
+    let install_dir = tempfile::tempdir().expect("Can't create install_dir");
+    let executable_path = install_dir.path().join("my_app");
+    std::fs::write(&executable_path, &old_binary).expect("Can't write binary");
+
+    let _m = mockito::mock("GET", format!("/{}/latest.json", path).as_str())
+      .with_status(200)
+      .with_header("content-type", "application/json")
+      .with_body(generate_patch_json(
+        &format!("{}/{}/my_app.patch", mockito::server_url(), path),
+        &format!("{}/{}/my_app.tar.gz", mockito::server_url(), path),
+        sha256.unwrap_or(&hash),
+      ))
+      .create();
+    let _p = mockito::mock("GET", format!("/{}/my_app.patch", path).as_str())
+      .with_status(200)
+      .with_body(patch)
+      .create();
+
+    let check_update = block!(builder()
+      .current_version("0.0.1")
+      .url(format!("{}/{}/latest.json", mockito::server_url(), path))
+      .executable_path(&executable_path)
+      .linux_install(LinuxInstall::Binary)
+      .build());
+    let updater = check_update.expect("Can't check update");
+    let result = block!(updater.download_and_install());
+
+    (install_dir.into_path().join("my_app"), result)
+  }
+
+  #[cfg(target_os = "linux")]
+  #[test]
+  fn linux_binary_patch() {
+    let (executable_path, result) = install_patch("patch", None);
+    assert!(result.is_ok());
+    assert_eq!(
+      std::fs::read(&executable_path).expect("Can't read binary"),
+      b"new binary, patched"
+    );
+  }
+
+  #[cfg(target_os = "linux")]
+  #[test]
+  fn linux_binary_patch_hash_mismatch() {
+    // the full archive is downloaded instead but it's not available
+    let (executable_path, result) = install_patch("bad-patch", Some("bad hash"));
+    assert!(result.is_err());
+    assert_eq!(
+      std::fs::read(&executable_path).expect("Can't read binary"),
+      b"old binary"
+    );
+  }
+
   // run complete process on mac only for now as we don't have
   // server (api) that we can use to test
   #[cfg(target_os = "macos")]
@@ -1027,6 +2092,10 @@ mod test {
     // configure the updater
     let check_update = block!(builder()
       .url(mockito::server_url())
+      .pubkey(UpdaterPubkey {
+        key: pubkey,
+        expires_at: None,
+      })
       // It should represent the executable path, that's why we add my_app.exe in our
       // test path -- in production you shouldn't have to provide it
       .executable_path(&tmp_dir_path.join("my_app.exe"))
@@ -1046,7 +2115,7 @@ mod test {
     assert_eq!(updater.version, "2.0.1");
 
     // download, install and validate signature
-    let install_process = block!(updater.download_and_install(Some(pubkey)));
+    let install_process = block!(updater.download_and_install());
     assert_eq!(install_process.is_ok(), true);
 
     // make sure the extraction went well (it should have skipped the main app.app folder)
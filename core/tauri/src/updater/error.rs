@@ -26,6 +26,9 @@ pub enum Error {
   /// UTF8 Errors in signature.
   #[error("Signature encoding error: {0}")]
   Utf8(#[from] std::str::Utf8Error),
+  /// The signature is malformed.
+  #[error("Invalid signature: {0}")]
+  Signature(String),
   /// Tauri utils, mainly extract and file move.
   #[error("Tauri API error: {0}")]
   TauriApi(#[from] crate::api::Error),
@@ -44,6 +47,21 @@ pub enum Error {
   /// Public key found in `tauri.conf.json` but no signature announced remotely.
   #[error("Signature not available but public key provided, skipping update")]
   PubkeyButNoSignature,
+  /// The manifest signature could not be downloaded but `verifyManifest` is enabled.
+  #[error("Manifest signature not available but manifest verification is enabled")]
+  ManifestButNoSignature,
+  /// All the trusted public keys are expired.
+  #[error("All the trusted public keys are expired")]
+  PubkeyExpired,
+  /// The announced version is not greater than the running version.
+  #[error("Refusing to install version {0} which is not newer than the running version")]
+  Downgrade(String),
+  /// The Linux package manager failed to install the update.
+  #[error("Unable to install the update package: {0}")]
+  PackageInstall(String),
+  /// The binary patch couldn't be applied, the full update is downloaded instead.
+  #[error("Unable to apply the update patch: {0}")]
+  Patch(String),
   /// Triggered when there is NO error and the two versions are equals.
   /// On client side, it's important to catch this error.
   #[error("No updates available")]
@@ -34,18 +34,40 @@
 //!
 //! "active" must be a boolean. By default, it's set to false.
 //!
-//! "endpoints" must be an array. The string `{{target}}` and `{{current_version}}` are automatically replaced in the URL allowing you determine [server-side](#update-server-json-format) if an update is available. If multiple endpoints are specified, the updater will fallback if a server is not responding within the pre-defined timeout.
+//! "endpoints" must be an array. The string `{{target}}` and `{{current_version}}` are automatically replaced in the URL allowing you determine [server-side](#update-server-json-format) if an update is available. If multiple endpoints are specified, the updater will fallback if a server is not responding within the pre-defined timeout. Each endpoint is retried up to 3 times, on connection errors and 408, 429, 500, 502, 503 or 504 responses, before falling back to the next one.
 //!
 //! "dialog" if present must be a boolean. By default, it's set to true. If enabled, [events](#events) are turned-off as the updater will handle everything. If you need the custom events, you MUST turn off the built-in dialog.
 //!
 //! "pubkey" if present must be a valid public-key generated with Tauri cli. See [Signing updates](#signing-updates).
 //!
+//! "pubkeys" if present must be an array of `{ "key": "", "expiresAt": 1735689600 }` objects. Each key is trusted until its optional `expiresAt` unix timestamp, which lets you rotate your signing key without stranding your users. See [Key rotation](#key-rotation).
+//!
+//! "verifyManifest" if present must be a boolean. By default, it's set to false. If enabled, the update JSON itself must be signed. See [Signing the manifest](#signing-the-manifest).
+//!
+//! "schedule" if present must be an object like `{ "interval": 21600, "jitter": 600 }`. The updater then checks for updates every `interval` seconds, plus a random delay up to `jitter` seconds, in addition to the check on startup. The results are delivered like a `tauri://update` check, through the built-in dialog or the [events](#events). A scheduled check is skipped while an update is pending (available but not installed yet, or installed and waiting for a restart) and when the `tauri::Builder::should_check_update` hook returns false, e.g. on a metered network.
+//!
+//! "proxy" if present must be a proxy URL such as `http://proxy.example.com:8080`, used for the manifest and download requests.
+//!
+//! "rollbackLaunches" if present must be a positive integer. If set, the previous version is kept as a backup and restored when the update doesn't confirm its launch within that many launches. See [Rollback](#rollback).
+//!
 //! ## Update Requests
 //!
 //! Tauri is indifferent to the request the client application provides for update checking.
 //!
 //! `Accept: application/json` is added to the request headers because Tauri is responsible for parsing the response.
 //!
+//! Release servers behind authentication need custom headers, which can be computed at runtime (e.g. from the app state) with the `tauri::Builder::updater_headers` hook.
+//! They are added to the manifest and download requests:
+//!
+//! ```rust,ignore
+//! tauri::Builder::default()
+//!   .updater_headers(|app| {
+//!     let mut headers = std::collections::HashMap::new();
+//!     headers.insert("Authorization".into(), format!("Bearer {}", app.state::<License>().key));
+//!     headers
+//!   })
+//! ```
+//!
 //! For the requirements imposed on the responses and the body format of an update, response see [Server Support](#server-support).
 //!
 //! Your update request must *at least* include a version identifier so that the server can determine whether an update for this specific version is required.
@@ -180,6 +202,34 @@
 //! });
 //! ```
 //!
+//! ### Confirm Launch
+//!
+//! Emit this event once your application started successfully, see [Rollback](#rollback).
+//!
+//! Event : `tauri://update-confirm-launch`
+//!
+//! ### Rust
+//! ```ignore
+//! tauri::updater::confirm_launch(&app)?;
+//! ```
+//!
+//! ### Javascript
+//! ```js
+//! import { emit } from "@tauri-apps/api/event";
+//! emit("tauri://update-confirm-launch");
+//! ```
+//!
+//! ## Rollback
+//!
+//! An update that crashes on startup would leave your users stuck with a broken installation.
+//! When "rollbackLaunches" is set, the updater keeps the previous version next to the installed one (with a `.old` suffix)
+//! and counts the launches of the new version until it confirms it started successfully.
+//! If the launch isn't confirmed within "rollbackLaunches" launches, the previous version is restored and relaunched on the next startup.
+//! The failed version is remembered and isn't offered again by the update checks.
+//!
+//! The backup is kept for the macOS `.app`, the Linux AppImage and plain binaries.
+//! Windows installers and Linux packages replace the application themselves, so they can't be rolled back.
+//!
 //! # Server Support
 //!
 //! Your server should determine whether an update is required based on the [Update Request](#update-requests) your client issues.
@@ -234,6 +284,25 @@
 //! }
 //! ```
 //!
+//! ### Patches
+//!
+//! On Linux, AppImage and plain binary installs can download a binary patch instead of the full update.
+//! Patches aren't supported on macOS and Windows, nor for the deb and rpm installs, which always download the full update.
+//! The "patches" object, either at the root or inside a platform, maps the version the patch applies to
+//! with the patch "url", its optional "signature" and the "sha256" of the patched file:
+//!
+//! ```json
+//! "patches": {
+//!   "0.9.0": {
+//!     "url": "https://github.com/lemarier/tauri-test/releases/download/v1.0.0/app.AppImage.0.9.0.patch",
+//!     "signature": "",
+//!     "sha256": "4fa0f3b5c1d1c6e0e8a8f5f3a2a4d9b0c6c4b2b7e7f5c0b0c1c8f2e5a3d7e9f1"
+//!   }
+//! }
+//! ```
+//!
+//! If the patch can't be downloaded, verified or applied, the full update is downloaded instead.
+//!
 //!
 //! # Bundler (Artifacts)
 //!
@@ -243,6 +312,27 @@
 //!
 //! The signature can be found in the `sig` file. The signature can be uploaded to GitHub safely or made public as long as your private key is secure.
 //!
+//! ## Update Manifest
+//!
+//! The bundler can also write the static JSON file described above to `target/release/bundle/latest.json`
+//! when `manifest` is set in the `updater` configuration of `tauri.conf.json`:
+//!
+//! ```json
+//! "manifest": {
+//!   "url": "https://github.com/lemarier/tauri-test/releases/download/v{{version}}/{{file}}",
+//!   "notes": "./RELEASE_NOTES.md",
+//!   "merge": "./latest.json"
+//! }
+//! ```
+//!
+//! "url" is the download URL of the artifacts, `{{version}}`, `{{target}}` and `{{file}}` are replaced with the app version, the platform and the artifact file name.
+//!
+//! "notes" if present is the file containing the release notes.
+//!
+//! "merge" if present is an existing manifest, e.g. generated on another platform, the current platform is added to it. Its platforms are dropped if it describes another version.
+//!
+//! The signatures are added to the manifest and the manifest itself is signed when `verifyManifest` is enabled.
+//!
 //! You can see how it's [bundled with the CI](https://github.com/tauri-apps/tauri/blob/feature/new_updater/.github/workflows/artifacts-updater.yml#L44) and a [sample tauri.conf.json](https://github.com/tauri-apps/tauri/blob/feature/new_updater/examples/updater/src-tauri/tauri.conf.json#L52)
 //!
 //! ## macOS
@@ -270,7 +360,7 @@
 //!
 //! ## Linux
 //!
-//! On Linux, we create a .tar.gz from the AppImage.
+//! On Linux, we create a .tar.gz from the AppImage, from the `.deb` and `.rpm` packages when they are bundled, and from the plain binary.
 //!
 //! ```text
 //! target/release/bundle
@@ -278,8 +368,32 @@
 //!     └── app.AppImage
 //!     └── app.AppImage.tar.gz (update bundle)
 //!     └── app.AppImage.tar.gz.sig (if signature enabled)
+//! └── deb
+//!     └── app.deb
+//!     └── app.deb.tar.gz (update bundle)
+//!     └── app.deb.tar.gz.sig (if signature enabled)
+//! └── binary
+//!     └── app.tar.gz (update bundle)
+//!     └── app.tar.gz.sig (if signature enabled)
+//! ```
+//!
+//! When `previousVersions` maps versions to their AppImage in the `updater` configuration of `tauri.conf.json`,
+//! the bundler also creates a patch from each of them and the SHA-256 of the new AppImage.
+//!
+//! ```text
+//! target/release/bundle
+//! └── appimage
+//!     └── app.AppImage.0.9.0.patch (patch from 0.9.0)
+//!     └── app.AppImage.0.9.0.patch.sig (if signature enabled)
+//!     └── app.AppImage.sha256
 //! ```
 //!
+//! The updater detects how the app was installed and requests a matching target:
+//!
+//! - `linux`: the app runs as an AppImage, replaced by the AppImage found in the update bundle.
+//! - `linux-deb` and `linux-rpm`: the app was installed from a package, the `.deb` or `.rpm` found in the update bundle is installed with `pkexec dpkg -i` or `pkexec rpm -U`.
+//! - `linux-binary`: the app is a plain binary, atomically swapped with the binary found in the update bundle.
+//!
 //! # Signing updates
 //!
 //! We offer a built-in signature to ensure your update is safe to be installed.
@@ -315,9 +429,35 @@
 //!     -k, --private-key <private-key>              Load the private key from a string
 //!     -f, --private-key-path <private-key-path>    Load the private key from a file
 //!         --sign-file <sign-file>                  Sign the specified file
+//!         --sign-manifest <sign-manifest>          Sign the specified update manifest (e.g. latest.json)
 //!     -w, --write-keys <write-keys>                Write private key to a file
 //! ```
 //!
+//! ## Key rotation
+//!
+//! To rotate your signing key, generate a new keypair and ship a version trusting both keys before you start signing with the new one:
+//!
+//! ```json
+//! "updater": {
+//!     "pubkey": "NEW PUBLIC KEY",
+//!     "pubkeys": [
+//!         { "key": "OLD PUBLIC KEY", "expiresAt": 1735689600 }
+//!     ]
+//! }
+//! ```
+//!
+//! ## Signing the manifest
+//!
+//! The archive signature does not protect the update JSON, so a compromised server could announce any validly signed archive.
+//! When `verifyManifest` is enabled, the updater downloads the manifest signature from the endpoint URL with a `.sig` suffix
+//! (e.g. `https://releases.myapp.com/latest.json.sig`) and rejects the update if it isn't signed by one of the trusted keys.
+//!
+//! ```bash
+//! tauri sign -f ~/.tauri/myapp.key --sign-manifest latest.json
+//! ```
+//!
+//! Regardless of the manifest signature, the updater never installs a version that is not greater than the running one.
+//!
 //! ***
 //!
 //! Environment variables used to sign with `tauri-bundler`:
@@ -329,18 +469,33 @@
 
 mod core;
 mod error;
+mod rollback;
 
 pub use self::error::Error;
 
 use crate::manager::tauri_event;
 use crate::{
   api::{
-    config::UpdaterConfig,
-    dialog::{ask, AskResponse},
+    config::{Config, UpdaterConfig, UpdaterPubkey, UpdaterSchedule},
+    dialog::{ask_async, AskResponse},
+    path::app_dir,
     process::restart,
+    PackageInfo,
+  },
+  sealed::ManagerBase,
+  Manager, Params, Window,
+};
+use rand::Rng;
+use std::{
+  collections::HashMap,
+  path::PathBuf,
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
   },
-  Params, Window,
+  time::Duration,
 };
+use tokio::task::JoinHandle;
 
 /// Check for new updates
 pub const EVENT_CHECK_UPDATE: &str = "tauri://update";
@@ -348,6 +503,8 @@ pub const EVENT_CHECK_UPDATE: &str = "tauri://update";
 pub const EVENT_UPDATE_AVAILABLE: &str = "tauri://update-available";
 /// Used to initialize an update *should run check-update first (once you received the update available event)*
 pub const EVENT_INSTALL_UPDATE: &str = "tauri://update-install";
+/// Confirms the installed update started successfully, see [`confirm_launch`]
+pub const EVENT_CONFIRM_LAUNCH: &str = "tauri://update-confirm-launch";
 /// Send updater status or error even if dialog is enabled, you should
 /// always listen for this event. It'll send you the install progress
 /// and any error triggered during update check and install
@@ -362,6 +519,25 @@ pub const EVENT_STATUS_SUCCESS: &str = "DONE";
 /// When you receive this status, this is because the application is running last version
 pub const EVENT_STATUS_UPTODATE: &str = "UPTODATE";
 
+/// Computes the headers added to the update requests, e.g. a license key from the app state.
+pub(crate) type HeadersHook = Arc<dyn Fn() -> HashMap<String, String> + Send + Sync>;
+
+/// Whether an update was found and is waiting to be installed or for the app to restart.
+///
+/// The scheduled update checks are skipped while an update is pending.
+#[derive(Clone, Default)]
+pub(crate) struct PendingUpdate(Arc<AtomicBool>);
+
+impl PendingUpdate {
+  fn set(&self, pending: bool) {
+    self.0.store(pending, Ordering::Relaxed);
+  }
+
+  fn is_pending(&self) -> bool {
+    self.0.load(Ordering::Relaxed)
+  }
+}
+
 #[derive(Clone, serde::Serialize)]
 struct StatusEvent {
   status: String,
@@ -380,23 +556,34 @@ pub(crate) async fn check_update_with_dialog<P: Params>(
   updater_config: UpdaterConfig,
   package_info: crate::api::PackageInfo,
   window: Window<P>,
+  pending: PendingUpdate,
+  headers: Option<HeadersHook>,
 ) {
-  if let Some(endpoints) = updater_config.endpoints.clone() {
+  if updater_config.endpoints.is_some() {
+    let builder = update_builder(
+      &window,
+      &updater_config,
+      &package_info.version,
+      headers.as_ref(),
+    );
+
     // check updates
-    match self::core::builder()
-      .urls(&endpoints[..])
-      .current_version(&package_info.version)
-      .build()
-      .await
-    {
+    match builder.build().await {
       Ok(updater) => {
-        let pubkey = updater_config.pubkey.clone();
-
         // if dialog enabled only
         if updater.should_update && updater_config.dialog {
+          // a single dialog at a time
+          if pending.is_pending() {
+            return;
+          }
+          pending.set(true);
+
           let body = updater.body.clone().unwrap_or_else(|| String::from(""));
           let dialog =
-            prompt_for_install(&updater.clone(), &package_info.name, &body.clone(), pubkey).await;
+            prompt_for_install(&updater.clone(), &package_info.name, &body.clone()).await;
+
+          // the update stays pending until the app restarts once installed
+          pending.set(matches!(dialog, Ok(true)));
 
           if dialog.is_err() {
             send_status_update(
@@ -422,6 +609,8 @@ pub(crate) fn listener<P: Params>(
   updater_config: UpdaterConfig,
   package_info: crate::api::PackageInfo,
   window: &Window<P>,
+  pending: PendingUpdate,
+  headers: Option<HeadersHook>,
 ) {
   let isolated_window = window.clone();
 
@@ -433,31 +622,27 @@ pub(crate) fn listener<P: Params>(
     move |_msg| {
       let window = isolated_window.clone();
       let package_info = package_info.clone();
-
-      // prepare our endpoints
-      let endpoints = updater_config
-        .endpoints
-        .as_ref()
-        .expect("Something wrong with endpoints")
-        .clone();
-
-      let pubkey = updater_config.pubkey.clone();
+      let pending = pending.clone();
+      let updater_config = updater_config.clone();
+      let headers = headers.clone();
 
       // check updates
       crate::async_runtime::spawn(async move {
         let window = window.clone();
         let window_isolation = window.clone();
-        let pubkey = pubkey.clone();
-
-        match self::core::builder()
-          .urls(&endpoints[..])
-          .current_version(&package_info.version)
-          .build()
-          .await
-        {
+
+        let builder = update_builder(
+          &window,
+          &updater_config,
+          &package_info.version,
+          headers.as_ref(),
+        );
+
+        match builder.build().await {
           Ok(updater) => {
             // send notification if we need to update
             if updater.should_update {
+              pending.set(true);
               let body = updater.body.clone().unwrap_or_else(|| String::from(""));
 
               // Emit `tauri://update-available`
@@ -478,7 +663,7 @@ pub(crate) fn listener<P: Params>(
                 move |_msg| {
                   let window = window_isolation.clone();
                   let updater = updater.clone();
-                  let pubkey = pubkey.clone();
+                  let pending = pending.clone();
 
                   // Start installation
                   crate::async_runtime::spawn(async move {
@@ -488,10 +673,12 @@ pub(crate) fn listener<P: Params>(
                     // Launch updater download process
                     // macOS we display the `Ready to restart dialog` asking to restart
                     // Windows is closing the current App and launch the downloaded MSI when ready (the process stop here)
-                    // Linux we replace the AppImage by launching a new install, it start a new AppImage instance, so we're closing the previous. (the process stop here)
-                    let update_result = updater.clone().download_and_install(pubkey.clone()).await;
+                    // Linux we replace the AppImage or the binary, or install the downloaded package.
+                    let update_result = updater.clone().download_and_install().await;
 
                     if let Err(err) = update_result {
+                      // the next scheduled check can try again
+                      pending.set(false);
                       // emit {"status": "ERROR", "error": "The error message"}
                       send_status_update(window.clone(), EVENT_STATUS_ERROR, Some(err.to_string()));
                     } else {
@@ -514,6 +701,121 @@ pub(crate) fn listener<P: Params>(
   );
 }
 
+/// Checks for updates periodically by triggering `tauri://update` on the window,
+/// so the results go through the built-in dialog or the usual events.
+///
+/// A check is skipped while an update is pending or if `should_check` returns false
+/// (e.g. on a metered network). The returned handle aborts the scheduled checks.
+pub(crate) fn schedule<P: Params, F: Fn() -> bool + Send + 'static>(
+  schedule: UpdaterSchedule,
+  window: Window<P>,
+  pending: PendingUpdate,
+  should_check: F,
+) -> Option<JoinHandle<()>> {
+  if schedule.interval == 0 {
+    return None;
+  }
+
+  let handle = crate::async_runtime::spawn_task(async move {
+    loop {
+      tokio::time::sleep(next_check_delay(&schedule)).await;
+      if pending.is_pending() || !should_check() {
+        continue;
+      }
+      window.trigger(&tauri_event::<P::Event>(EVENT_CHECK_UPDATE), None);
+    }
+  });
+  Some(handle)
+}
+
+// The interval with a random jitter, so the clients don't all check at the same time
+fn next_check_delay(schedule: &UpdaterSchedule) -> Duration {
+  let jitter = if schedule.jitter > 0 {
+    rand::thread_rng().gen_range(0..=schedule.jitter)
+  } else {
+    0
+  };
+  Duration::from_secs(schedule.interval + jitter)
+}
+
+/// Confirms the running version started successfully.
+///
+/// When `rollbackLaunches` is configured, the previous version is kept as a backup after an update
+/// and restored if the new version doesn't call this function within `rollbackLaunches` launches.
+/// The JS side can emit the `tauri://update-confirm-launch` event instead.
+pub fn confirm_launch<P: Params, M: Manager<P>>(manager: &M) -> crate::Result<()> {
+  let manager = manager.manager();
+  if let Some(state_path) = rollback_state_path(&manager.config()) {
+    rollback::confirm_launch(&state_path, &manager.package_info().version)?;
+  }
+  Ok(())
+}
+
+/// Restores the previous version if the installed update didn't confirm its launch in time.
+/// This function should be run on startup, before the windows are created.
+///
+/// A failing check is logged and the application starts anyway.
+pub(crate) fn check_rollback(config: &Config, package_info: &PackageInfo) {
+  if let (Some(max_launches), Some(state_path)) = (
+    config.tauri.updater.rollback_launches,
+    rollback_state_path(config),
+  ) {
+    match rollback::check_launch(&state_path, &package_info.version, max_launches) {
+      // launch the restored version
+      Ok(true) => restart(),
+      Ok(false) => {}
+      Err(e) => eprintln!("failed to check the update rollback: {}", e),
+    }
+  }
+}
+
+// The rollback state is stored in the app dir, only when the rollback is enabled.
+fn rollback_state_path(config: &Config) -> Option<PathBuf> {
+  config.tauri.updater.rollback_launches?;
+  app_dir(config).map(|dir| dir.join("updater").join("rollback.json"))
+}
+
+// Prepare the update builder from the updater configuration,
+// the headers hook is called on each check so the headers can change at runtime
+fn update_builder<'a, P: Params>(
+  window: &Window<P>,
+  updater_config: &UpdaterConfig,
+  current_version: &'a str,
+  headers: Option<&HeadersHook>,
+) -> self::core::UpdateBuilder<'a> {
+  let mut builder = self::core::builder()
+    .urls(updater_config.endpoints.as_deref().unwrap_or_default())
+    .current_version(current_version)
+    .pubkeys(&trusted_pubkeys(updater_config))
+    .verify_manifest(updater_config.verify_manifest);
+  if let Some(state_path) = rollback_state_path(&window.config()) {
+    builder = builder.rollback_state_path(state_path);
+  }
+  if let Some(proxy) = &updater_config.proxy {
+    builder = builder.proxy(proxy.clone());
+  }
+  if let Some(headers) = headers {
+    for (name, value) in headers() {
+      builder = builder.header(name, value);
+    }
+  }
+  builder
+}
+
+// Collect the public keys trusted by the updater,
+// the main `pubkey` and the keys used for rotation.
+fn trusted_pubkeys(updater_config: &UpdaterConfig) -> Vec<UpdaterPubkey> {
+  let mut pubkeys = Vec::new();
+  if let Some(pubkey) = &updater_config.pubkey {
+    pubkeys.push(UpdaterPubkey {
+      key: pubkey.clone(),
+      expires_at: None,
+    });
+  }
+  pubkeys.extend(updater_config.pubkeys.iter().cloned());
+  pubkeys
+}
+
 // Send a status update via `tauri://update-status` event.
 fn send_status_update<P: Params>(window: Window<P>, status: &str, error: Option<String>) {
   let _ = window.emit(
@@ -527,18 +829,18 @@ fn send_status_update<P: Params>(window: Window<P>, status: &str, error: Option<
 
 // Prompt a dialog asking if the user want to install the new version
 // Maybe we should add an option to customize it in future versions.
+// Returns whether the update was installed.
 async fn prompt_for_install(
   updater: &self::core::Update,
   app_name: &str,
   body: &str,
-  pubkey: Option<String>,
-) -> crate::Result<()> {
+) -> crate::Result<bool> {
   // remove single & double quote
   let escaped_body = body.replace(&['\"', '\''][..], "");
 
   // todo(lemarier): We should review this and make sure we have
   // something more conventional.
-  let should_install = ask(
+  let should_install = ask_async(
     format!(r#"A new version of {} is available! "#, app_name),
     format!(
       r#"{} {} is now available -- you have {}.
@@ -549,37 +851,66 @@ Release Notes:
 {}"#,
       app_name, updater.version, updater.current_version, escaped_body,
     ),
-  );
+  )
+  .await?;
 
   match should_install {
     AskResponse::Yes => {
       // Launch updater download process
       // macOS we display the `Ready to restart dialog` asking to restart
       // Windows is closing the current App and launch the downloaded MSI when ready (the process stop here)
-      // Linux we replace the AppImage by launching a new install, it start a new AppImage instance, so we're closing the previous. (the process stop here)
-      updater.download_and_install(pubkey.clone()).await?;
+      // Linux we replace the AppImage or the binary, or install the downloaded package.
+      updater.download_and_install().await?;
 
       // Ask user if we need to restart the application
-      let should_exit = ask(
+      let should_exit = ask_async(
         "Ready to Restart",
         "The installation was successful, do you want to restart the application now?",
-      );
+      )
+      .await?;
       match should_exit {
         AskResponse::Yes => {
           restart();
           // safely exit even if the process
           // should be killed
-          return Ok(());
+          return Ok(true);
         }
         AskResponse::No => {
           // Do nothing -- maybe we can emit some event here
         }
       }
+
+      Ok(true)
     }
     AskResponse::No => {
       // Do nothing -- maybe we can emit some event here
+      Ok(false)
     }
   }
+}
 
-  Ok(())
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn check_delay_with_jitter() {
+    let schedule = UpdaterSchedule {
+      interval: 60,
+      jitter: 30,
+    };
+    for _ in 0..100 {
+      let delay = next_check_delay(&schedule);
+      assert!(delay >= Duration::from_secs(60) && delay <= Duration::from_secs(90));
+    }
+  }
+
+  #[test]
+  fn check_delay_without_jitter() {
+    let schedule = UpdaterSchedule {
+      interval: 21600,
+      jitter: 0,
+    };
+    assert_eq!(next_check_delay(&schedule), Duration::from_secs(21600));
+  }
 }
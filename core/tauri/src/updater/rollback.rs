@@ -0,0 +1,246 @@
+// Copyright 2019-2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use super::error::Result;
+use crate::api::file::Move;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+  ffi::OsString,
+  fs,
+  path::{Path, PathBuf},
+};
+
+/// The state of an installed update waiting for its launch confirmation.
+///
+/// It is written once the update is installed and removed when the launch is confirmed
+/// or the previous version is restored.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RollbackState {
+  /// The installed version.
+  pub version: String,
+  /// Where the application is installed.
+  pub install_path: PathBuf,
+  /// Where the previous version is kept.
+  pub backup_path: PathBuf,
+  /// Number of launches of the installed version without confirmation.
+  pub launches: u32,
+}
+
+impl RollbackState {
+  /// Reads the rollback state, `None` if there's no update waiting for confirmation.
+  ///
+  /// An unreadable state is moved aside, so it can't prevent the application from starting.
+  pub fn load(state_path: &Path) -> Option<Self> {
+    read_or_discard(state_path)
+  }
+
+  /// Writes the rollback state.
+  pub fn save(&self, state_path: &Path) -> Result {
+    if let Some(parent) = state_path.parent() {
+      fs::create_dir_all(parent)?;
+    }
+    fs::write(state_path, serde_json::to_vec(self)?)?;
+    Ok(())
+  }
+}
+
+/// Confirms the running version started successfully.
+///
+/// The backup of the previous version is removed and won't be restored anymore.
+pub fn confirm_launch(state_path: &Path, current_version: &str) -> Result {
+  if let Some(state) = RollbackState::load(state_path) {
+    // only the installed update can confirm its own launch
+    if state.version == current_version {
+      remove_path(&state.backup_path)?;
+      fs::remove_file(state_path)?;
+    }
+  }
+  Ok(())
+}
+
+/// Counts a launch of the running version and restores the previous version
+/// if the installed update didn't confirm its launch within `max_launches` launches.
+///
+/// Returns `true` if the previous version was restored, the application should be restarted.
+pub fn check_launch(state_path: &Path, current_version: &str, max_launches: u32) -> Result<bool> {
+  let mut state = match RollbackState::load(state_path) {
+    Some(state) => state,
+    None => return Ok(false),
+  };
+
+  // the update isn't the running version (e.g. it was restored manually)
+  // so there's nothing to confirm anymore
+  if state.version != current_version {
+    remove_path(&state.backup_path)?;
+    fs::remove_file(state_path)?;
+    return Ok(false);
+  }
+
+  state.launches += 1;
+  if state.launches <= max_launches {
+    state.save(state_path)?;
+    return Ok(false);
+  }
+
+  restore(&state)?;
+  fs::remove_file(state_path)?;
+  record_rolled_back(state_path, &state.version)?;
+  Ok(true)
+}
+
+/// The versions restored by a rollback, they're not offered again by the update checks.
+pub fn rolled_back_versions(state_path: &Path) -> Vec<String> {
+  read_or_discard(&rolled_back_path(state_path)).unwrap_or_default()
+}
+
+fn record_rolled_back(state_path: &Path, version: &str) -> Result {
+  let mut versions = rolled_back_versions(state_path);
+  if !versions.iter().any(|v| v == version) {
+    versions.push(version.into());
+  }
+  fs::write(rolled_back_path(state_path), serde_json::to_vec(&versions)?)?;
+  Ok(())
+}
+
+// Reads a JSON file, `None` if it doesn't exist.
+// A file that can't be read or parsed is renamed with a `.corrupt` suffix and ignored.
+fn read_or_discard<T: DeserializeOwned>(path: &Path) -> Option<T> {
+  if !path.exists() {
+    return None;
+  }
+  let error = match fs::read(path) {
+    Ok(contents) => match serde_json::from_slice(&contents) {
+      Ok(value) => return Some(value),
+      Err(e) => e.to_string(),
+    },
+    Err(e) => e.to_string(),
+  };
+  eprintln!(
+    "discarding the invalid updater state {}: {}",
+    path.display(),
+    error
+  );
+  let corrupt_path = path_with_suffix(path, ".corrupt");
+  if fs::rename(path, &corrupt_path).is_err() {
+    let _ = fs::remove_file(path);
+  }
+  None
+}
+
+// The rolled back versions are stored next to the rollback state
+fn rolled_back_path(state_path: &Path) -> PathBuf {
+  state_path.with_file_name("rolled-back.json")
+}
+
+// Put the backup back in place, the failing version is moved next to it and removed.
+fn restore(state: &RollbackState) -> Result {
+  let failed_path = path_with_suffix(&state.install_path, ".failed");
+  remove_path(&failed_path)?;
+  Move::from_source(&state.backup_path)
+    .replace_using_temp(&failed_path)
+    .to_dest(&state.install_path)?;
+  remove_path(&failed_path)?;
+  Ok(())
+}
+
+// Remove a file or a directory (the macOS `.app`) if it exists
+pub(crate) fn remove_path(path: &Path) -> Result {
+  if path.is_dir() {
+    fs::remove_dir_all(path)?;
+  } else if path.exists() {
+    fs::remove_file(path)?;
+  }
+  Ok(())
+}
+
+// Append a suffix to the file name, `my-app` -> `my-app.new`
+pub(crate) fn path_with_suffix(path: &Path, suffix: &str) -> PathBuf {
+  let mut path: OsString = path.as_os_str().into();
+  path.push(suffix);
+  PathBuf::from(path)
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  fn install(dir: &Path, version: &str) -> RollbackState {
+    let install_path = dir.join("my-app");
+    let backup_path = path_with_suffix(&install_path, ".old");
+    fs::write(&install_path, version).unwrap();
+    fs::write(&backup_path, "0.0.1").unwrap();
+    RollbackState {
+      version: version.into(),
+      install_path,
+      backup_path,
+      launches: 0,
+    }
+  }
+
+  #[test]
+  fn restore_after_unconfirmed_launches() {
+    let dir = tempfile::tempdir().unwrap();
+    let state_path = dir.path().join("rollback.json");
+    let state = install(dir.path(), "0.0.2");
+    state.save(&state_path).unwrap();
+
+    assert!(!check_launch(&state_path, "0.0.2", 2).unwrap());
+    assert!(!check_launch(&state_path, "0.0.2", 2).unwrap());
+    assert_eq!(RollbackState::load(&state_path).unwrap().launches, 2);
+    assert!(check_launch(&state_path, "0.0.2", 2).unwrap());
+
+    assert_eq!(fs::read_to_string(&state.install_path).unwrap(), "0.0.1");
+    assert!(!state.backup_path.exists());
+    assert!(!state_path.exists());
+    assert_eq!(rolled_back_versions(&state_path), vec!["0.0.2"]);
+  }
+
+  #[test]
+  fn confirmed_launch_removes_backup() {
+    let dir = tempfile::tempdir().unwrap();
+    let state_path = dir.path().join("rollback.json");
+    let state = install(dir.path(), "0.0.2");
+    state.save(&state_path).unwrap();
+
+    assert!(!check_launch(&state_path, "0.0.2", 1).unwrap());
+    confirm_launch(&state_path, "0.0.2").unwrap();
+    assert!(!check_launch(&state_path, "0.0.2", 1).unwrap());
+
+    assert_eq!(fs::read_to_string(&state.install_path).unwrap(), "0.0.2");
+    assert!(!state.backup_path.exists());
+    assert!(!state_path.exists());
+    assert!(rolled_back_versions(&state_path).is_empty());
+  }
+
+  #[test]
+  fn other_version_discards_state() {
+    let dir = tempfile::tempdir().unwrap();
+    let state_path = dir.path().join("rollback.json");
+    let state = install(dir.path(), "0.0.2");
+    state.save(&state_path).unwrap();
+
+    // the previous version is running, the update was never launched
+    assert!(!check_launch(&state_path, "0.0.1", 1).unwrap());
+    assert!(!state.backup_path.exists());
+    assert!(!state_path.exists());
+  }
+
+  #[test]
+  fn corrupt_state_is_discarded() {
+    let dir = tempfile::tempdir().unwrap();
+    let state_path = dir.path().join("rollback.json");
+    fs::write(&state_path, "{ not json").unwrap();
+    fs::write(rolled_back_path(&state_path), "[0.0.2").unwrap();
+
+    assert!(!check_launch(&state_path, "0.0.2", 1).unwrap());
+    assert!(rolled_back_versions(&state_path).is_empty());
+    confirm_launch(&state_path, "0.0.2").unwrap();
+
+    assert!(!state_path.exists());
+    assert!(path_with_suffix(&state_path, ".corrupt").exists());
+    assert!(!rolled_back_path(&state_path).exists());
+    assert!(path_with_suffix(&rolled_back_path(&state_path), ".corrupt").exists());
+  }
+}
@@ -18,6 +18,7 @@ import * as process from './process'
 import * as shell from './shell'
 import * as tauri from './tauri'
 import * as updater from './updater'
+import * as websocket from './websocket'
 import * as window from './window'
 
 export {
@@ -34,5 +35,6 @@ export {
   shell,
   tauri,
   updater,
+  websocket,
   window
 }
@@ -3,7 +3,7 @@
 // SPDX-License-Identifier: MIT
 
 /**
- * Native system dialogs for opening and saving files.
+ * Native system dialogs for opening and saving files, and message dialogs.
  *
  * This package is also accessible with `window.__TAURI__.dialog` when `tauri.conf.json > build > withGlobalTauri` is set to true.
  *
@@ -21,6 +21,7 @@
  *   }
  * }
  * ```
+ * The {@link message} and {@link ask} APIs don't need to be allowlisted.
  * It is recommended to allowlist only the APIs you use for optimal bundle size and security.
  * @packageDocumentation
  */
@@ -43,22 +44,36 @@ interface DialogFilter {
 
 /** Options for the open dialog. */
 interface OpenDialogOptions {
+  /** The title of the dialog. Not supported by the current dialog backend yet, so it's ignored. */
+  title?: string
   /** The filters of the dialog. */
   filters?: DialogFilter[]
-  /** Initial directory or file path. It must exist. */
+  /** Initial directory or file path. It must exist. A file is selected in its directory. */
   defaultPath?: string
   /** Whether the dialog allows multiple selection or not. */
   multiple?: boolean
   /** Whether the dialog is a directory selection or not. */
   directory?: boolean
+  /** Whether the user can create directories from the dialog. Not supported by the current dialog backend yet, so it's ignored. */
+  canCreateDirectories?: boolean
+  /** Whether the dialog shows hidden files. Not supported by the current dialog backend yet, so it's ignored. */
+  showHiddenFiles?: boolean
 }
 
 /** Options for the save dialog. */
 interface SaveDialogOptions {
+  /** The title of the dialog. Not supported by the current dialog backend yet, so it's ignored. */
+  title?: string
   /** The filters of the dialog. */
   filters?: DialogFilter[]
-  /** Initial directory or file path. It must exist. */
+  /** Initial directory or file path. A file path doesn't need to exist, but its directory must. */
   defaultPath?: string
+  /** Initial file name, overriding the file name of `defaultPath`. */
+  defaultFileName?: string
+  /** Whether the user can create directories from the dialog. Not supported by the current dialog backend yet, so it's ignored. */
+  canCreateDirectories?: boolean
+  /** Whether the dialog shows hidden files. Not supported by the current dialog backend yet, so it's ignored. */
+  showHiddenFiles?: boolean
 }
 
 /**
@@ -101,6 +116,75 @@ async function save(options: SaveDialogOptions = {}): Promise<string> {
   })
 }
 
-export type { DialogFilter, OpenDialogOptions, SaveDialogOptions }
+/** The buttons of a message dialog. */
+type MessageDialogButtons = 'ok' | 'okCancel' | 'yesNo'
 
-export { open, save }
+/** Options for the message dialog. */
+interface MessageDialogOptions {
+  /** The title of the dialog. Defaults to the app name. */
+  title?: string
+  /** The kind of the dialog, defining its icon. Defaults to `info`. */
+  kind?: 'info' | 'warning' | 'error'
+  /** The buttons of the dialog. Defaults to `ok`. */
+  buttons?: MessageDialogButtons
+}
+
+/**
+ * Shows a message dialog.
+ * @example
+ * ```typescript
+ * import { message } from '@tauri-apps/api/dialog'
+ * const button = await message('Save the changes before closing?', {
+ *   kind: 'warning',
+ *   buttons: 'yesNo'
+ * })
+ * ```
+ *
+ * @param message The message to show.
+ * @param options The dialog options.
+ * @returns A promise resolving to the pressed button: `ok`, `cancel`, `yes` or `no`.
+ * Dismissing the dialog resolves to `cancel` or `no`.
+ */
+async function message(
+  message: string,
+  options: MessageDialogOptions = {}
+): Promise<string> {
+  const { title, ...dialogOptions } = options
+  return invokeTauriCommand<string>({
+    __tauriModule: 'Dialog',
+    message: {
+      cmd: 'messageDialog',
+      title,
+      message,
+      options: dialogOptions
+    }
+  })
+}
+
+/**
+ * Shows a question dialog with `Yes` and `No` buttons.
+ *
+ * @param message The message to show.
+ * @param title The title of the dialog. Defaults to the app name.
+ * @returns A promise resolving to `true` if the user pressed `Yes`.
+ */
+async function ask(message: string, title?: string): Promise<boolean> {
+  return invokeTauriCommand<boolean>({
+    __tauriModule: 'Dialog',
+    message: {
+      cmd: 'askDialog',
+      title,
+      message
+    }
+  })
+}
+
+export type {
+  DialogFilter,
+  OpenDialogOptions,
+  SaveDialogOptions,
+  MessageDialogButtons,
+  MessageDialogOptions
+}
+
+export { open, save, message, ask }
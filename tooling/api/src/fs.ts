@@ -23,17 +23,32 @@
  *         "createDir": true,
  *         "removeDir": true,
  *         "removeFile": true,
- *         "renameFile": true
+ *         "renameFile": true,
+ *         "watch": true,
+ *         "metadata": true, // enables `metadata` and `exists`
+ *         "setPermissions": true,
+ *         "archive": true, // enables `extractArchive` and `createArchive`
+ *         "trash": true, // enables the `trash` option of `removeFile` and `removeDir`, `listTrash` and `restoreTrash`
+ *         "scope": ["$APP/**", "$DOWNLOAD/*"] // the paths the scoped APIs can access
  *       }
  *     }
  *   }
  * }
  * ```
  * It is recommended to allowlist only the APIs you use for optimal bundle size and security.
+ *
+ * The `scope` is a list of glob patterns, which can start with a base directory variable such as `$APP`, `$HOME` or `$DOWNLOAD`.
+ * `*` doesn't match nested paths, use `**` instead. All paths are allowed if the scope isn't set.
+ * The scope applies to every fs API, including the paths moved to the trash, and to the HTTP downloads and uploads.
+ * The paths picked on a dialog are also allowed for the window that opened it, until the window is closed.
+ *
+ * Files can also be read and written incrementally with {@link open}: reading a file handle requires `readBinaryFile`
+ * and writing requires `writeBinaryFile`. The handles are closed when the window is destroyed.
  * @packageDocumentation
  */
 
 import { invokeTauriCommand } from './helpers/tauri'
+import { transformCallback } from './tauri'
 
 export enum BaseDirectory {
   Audio = 1,
@@ -54,16 +69,103 @@ export enum BaseDirectory {
   Video,
   Resource,
   App,
-  Current
+  Current,
+  Log,
+  Temp,
+  AppCache,
+  AppLocalData
 }
 
 interface FsOptions {
   dir?: BaseDirectory
+  /**
+   * Whether {@link removeFile} moves the file to the trash instead of deleting it. Requires the `trash` API.
+   * On macOS, the trashed items can't be listed or restored.
+   */
+  trash?: boolean
 }
 
 interface FsDirOptions {
   dir?: BaseDirectory
   recursive?: boolean
+  /** Whether {@link readDir} includes the metadata of the entries. */
+  metadata?: boolean
+  /** The maximum depth of a recursive {@link readDir}, the entries of the directory itself have depth 1. */
+  maxDepth?: number
+  /** Only lists the files matching these globs, e.g. `*.rs` or `docs/*.md`. The directories are always listed. */
+  include?: string[]
+  /** Skips the files and directories matching these globs. */
+  exclude?: string[]
+  /** Whether the files ignored by the `.gitignore`, `.ignore` and `.git/info/exclude` files are skipped. */
+  gitignore?: boolean
+  /** Whether the hidden files are listed. Defaults to `true`. */
+  hidden?: boolean
+  /**
+   * How the symlinks are handled: `follow` reads the linked directories, `list` lists the symlinks without following them
+   * and `skip` skips them. Defaults to `follow`.
+   */
+  symlinks?: SymlinkPolicy
+  /**
+   * Whether {@link removeDir} moves the directory to the trash instead of deleting it. Requires the `trash` API.
+   * On macOS, the trashed items can't be listed or restored.
+   */
+  trash?: boolean
+}
+
+/** An item moved to the trash. */
+interface TrashItem {
+  /** The identifier of the item, used to restore it. Empty on macOS. */
+  id: string
+  /** The name of the item. */
+  name: string
+  /** The path of the item before it was moved to the trash. */
+  originalPath: string
+  /** The deletion time, in milliseconds since the Unix epoch. */
+  deletedAt: number | null
+}
+
+/** The progress of an archive creation, reported after each entry is added. */
+interface ArchiveProgress {
+  /** The path of the last added entry in the archive. */
+  entry: string
+  /** The number of added entries. */
+  entries: number
+  /** The total number of entries. */
+  totalEntries: number
+  /** The number of added bytes, before compression. */
+  bytes: number
+  /** The total number of bytes, before compression. */
+  totalBytes: number
+}
+
+type SymlinkPolicy = 'follow' | 'list' | 'skip'
+
+interface StreamDirOptions extends FsDirOptions {
+  /** The number of entries of each page. Defaults to 256. */
+  pageSize?: number
+}
+
+/**
+ * An event of a directory stream: a page of entries, where a directory is followed by its entries,
+ * an error closing the stream, or the end of the stream.
+ */
+type StreamDirEvent =
+  | { type: 'page'; payload: FileEntry[] }
+  | { type: 'error'; payload: string }
+  | { type: 'done' }
+
+interface FsWriteFileOptions {
+  dir?: BaseDirectory
+  /** Appends the contents to the file instead of replacing it. */
+  append?: boolean
+  /** Fails if the file already exists. */
+  createNew?: boolean
+  /**
+   * Writes the contents to a temporary file in the same directory, syncs it to the disk and renames it to the path,
+   * so the file is either left untouched or fully written, even if the app crashes. Can't be combined with `append`.
+   * The replaced file keeps its permissions.
+   */
+  atomic?: boolean
 }
 
 interface FsTextFileOption {
@@ -85,6 +187,236 @@ interface FileEntry {
   name?: string
   /** Children of this entry if it's a directory; null otherwise */
   children?: FileEntry[]
+  /** The metadata of this entry, if requested with the `metadata` option */
+  metadata?: Metadata
+}
+
+/** The metadata of a file or directory. */
+interface Metadata {
+  /** The size of the file in bytes. */
+  size: number
+  /** The last modification time, in milliseconds since the Unix epoch. */
+  modifiedAt: number | null
+  /** The last access time, in milliseconds since the Unix epoch. */
+  accessedAt: number | null
+  /** The creation time, in milliseconds since the Unix epoch. Not available on every platform. */
+  createdAt: number | null
+  /** Whether the path is a file, following symlinks. */
+  isFile: boolean
+  /** Whether the path is a directory, following symlinks. */
+  isDir: boolean
+  /** Whether the path itself is a symlink. */
+  isSymlink: boolean
+  /** Whether the file is read-only. */
+  readonly: boolean
+  /** The Unix permission bits, e.g. `0o644`. `null` on Windows. */
+  mode: number | null
+}
+
+interface Permissions {
+  /** Makes the file read-only or writable. */
+  readonly?: boolean
+  /** The Unix permission bits, e.g. `0o644`. Ignored on Windows. */
+  mode?: number
+}
+
+interface OpenOptions {
+  dir?: BaseDirectory
+  /** Open the file for reading. Defaults to `true`. */
+  read?: boolean
+  /** Open the file for writing. */
+  write?: boolean
+  /** Open the file for appending, all writes go to the end of the file. */
+  append?: boolean
+  /** Truncate the file when it's opened. */
+  truncate?: boolean
+  /** Create the file if it doesn't exist. */
+  create?: boolean
+  /** Create the file, failing if it already exists. */
+  createNew?: boolean
+}
+
+type SeekMode = 'Start' | 'Current' | 'End'
+
+interface WatchOptions {
+  dir?: BaseDirectory
+  /** Whether the subdirectories are also watched. */
+  recursive?: boolean
+  /** The debounce delay in milliseconds. Defaults to 500. */
+  debounce?: number
+}
+
+/** A debounced file system event. */
+type WatchEvent =
+  | { type: 'create'; payload: string }
+  | { type: 'modify'; payload: string }
+  | { type: 'remove'; payload: string }
+  | { type: 'rename'; payload: { from: string; to: string } }
+  | { type: 'error'; payload: string }
+
+/**
+ * Watches a file or directory for changes.
+ *
+ * @example
+ * ```typescript
+ * import { watch, Dir } from '@tauri-apps/api/fs'
+ * const unwatch = await watch('logs', (event) => console.log(event), { dir: Dir.App, recursive: true })
+ * // later
+ * await unwatch()
+ * ```
+ *
+ * @param path Path to the file or directory.
+ * @param handler The event handler.
+ * @param options Configuration object.
+ * @returns A promise resolving to a function that stops the watcher.
+ */
+async function watch(
+  path: string,
+  handler: (event: WatchEvent) => void,
+  options: WatchOptions = {}
+): Promise<() => Promise<void>> {
+  return invokeTauriCommand<number>({
+    __tauriModule: 'Fs',
+    message: {
+      cmd: 'watch',
+      path,
+      options,
+      onEventFn: transformCallback(handler)
+    }
+  }).then((rid) => async () =>
+    invokeTauriCommand({
+      __tauriModule: 'Fs',
+      message: {
+        cmd: 'unwatch',
+        rid
+      }
+    })
+  )
+}
+
+/** An open file. */
+class FileHandle {
+  /** The resource identifier of the handle. */
+  rid: number
+
+  /** @ignore */
+  constructor(rid: number) {
+    this.rid = rid
+  }
+
+  /**
+   * Reads up to `len` bytes from the current position.
+   *
+   * @param len The maximum number of bytes to read.
+   * @returns A promise resolving to the bytes read, empty when the end of the file is reached.
+   */
+  async read(len: number): Promise<number[]> {
+    return invokeTauriCommand<number[]>({
+      __tauriModule: 'Fs',
+      message: {
+        cmd: 'read',
+        rid: this.rid,
+        len
+      }
+    })
+  }
+
+  /**
+   * Writes the data at the current position.
+   *
+   * @param data A UTF-8 string or a byte array.
+   * @returns A promise indicating the success or failure of the operation.
+   */
+  async write(data: string | number[] | Uint8Array): Promise<void> {
+    return invokeTauriCommand({
+      __tauriModule: 'Fs',
+      message: {
+        cmd: 'write',
+        rid: this.rid,
+        data: data instanceof Uint8Array ? Array.from(data) : data
+      }
+    })
+  }
+
+  /**
+   * Moves the position of the handle.
+   *
+   * @param offset The offset in bytes.
+   * @param whence Where the offset starts from. Defaults to the start of the file.
+   * @returns A promise resolving to the new position from the start of the file.
+   */
+  async seek(offset: number, whence: SeekMode = 'Start'): Promise<number> {
+    return invokeTauriCommand<number>({
+      __tauriModule: 'Fs',
+      message: {
+        cmd: 'seek',
+        rid: this.rid,
+        offset,
+        whence
+      }
+    })
+  }
+
+  /**
+   * Truncates or extends the file to `len` bytes.
+   *
+   * @param len The new length of the file.
+   * @returns A promise indicating the success or failure of the operation.
+   */
+  async truncate(len = 0): Promise<void> {
+    return invokeTauriCommand({
+      __tauriModule: 'Fs',
+      message: {
+        cmd: 'truncate',
+        rid: this.rid,
+        len
+      }
+    })
+  }
+
+  /**
+   * Closes the handle.
+   *
+   * @returns A promise indicating the success or failure of the operation.
+   */
+  async close(): Promise<void> {
+    return invokeTauriCommand({
+      __tauriModule: 'Fs',
+      message: {
+        cmd: 'close',
+        rid: this.rid
+      }
+    })
+  }
+}
+
+/**
+ * Opens a file handle to read or write the file incrementally.
+ *
+ * @example
+ * ```typescript
+ * import { open, Dir } from '@tauri-apps/api/fs'
+ * const file = await open('app.log', { dir: Dir.App, append: true, create: true, read: false })
+ * await file.write('new line\n')
+ * await file.close()
+ * ```
+ *
+ * @param filePath Path to the file.
+ * @param options Configuration object. The file is opened for reading by default.
+ * @returns A promise resolving to the file handle.
+ */
+async function open(
+  filePath: string,
+  options: OpenOptions = {}
+): Promise<FileHandle> {
+  return invokeTauriCommand<number>({
+    __tauriModule: 'Fs',
+    message: {
+      cmd: 'open',
+      path: filePath,
+      options
+    }
+  }).then((rid) => new FileHandle(rid))
 }
 
 /**
@@ -138,7 +470,7 @@ async function readBinaryFile(
  */
 async function writeFile(
   file: FsTextFileOption,
-  options: FsOptions = {}
+  options: FsWriteFileOptions = {}
 ): Promise<void> {
   if (typeof options === 'object') {
     Object.freeze(options)
@@ -203,7 +535,7 @@ function arrayBufferToBase64(buffer: ArrayBuffer): string {
  */
 async function writeBinaryFile(
   file: FsBinaryFileOption,
-  options: FsOptions = {}
+  options: FsWriteFileOptions = {}
 ): Promise<void> {
   if (typeof options === 'object') {
     Object.freeze(options)
@@ -244,6 +576,53 @@ async function readDir(
   })
 }
 
+/**
+ * Lists the directory files in pages, without building the whole tree in memory.
+ * Useful for large directory trees. Requires the `readDir` API.
+ *
+ * @example
+ * ```typescript
+ * import { streamDir } from '@tauri-apps/api/fs'
+ * const cancel = await streamDir('/home/user/project', (event) => {
+ *   if (event.type === 'page') {
+ *     event.payload.forEach((entry) => console.log(entry.path))
+ *   }
+ * }, { recursive: true, gitignore: true })
+ * // stop the stream early
+ * await cancel()
+ * ```
+ *
+ * @param dir Path to the directory to read.
+ * @param handler The event handler.
+ * @param options Configuration object.
+ * @returns A promise resolving to a function that stops the stream.
+ */
+async function streamDir(
+  dir: string,
+  handler: (event: StreamDirEvent) => void,
+  options: StreamDirOptions = {}
+): Promise<() => Promise<void>> {
+  const { pageSize, ...dirOptions } = options
+  return invokeTauriCommand<number>({
+    __tauriModule: 'Fs',
+    message: {
+      cmd: 'streamDir',
+      path: dir,
+      options: dirOptions,
+      pageSize,
+      onEventFn: transformCallback(handler)
+    }
+  }).then((rid) => async () =>
+    invokeTauriCommand({
+      __tauriModule: 'Fs',
+      message: {
+        cmd: 'cancelStreamDir',
+        rid
+      }
+    })
+  )
+}
+
 /**
  * Creates a directory.
  * If one of the path's parent components doesn't exist
@@ -358,12 +737,200 @@ async function renameFile(
   })
 }
 
+/**
+ * Reads the metadata of a file or directory.
+ *
+ * @example
+ * ```typescript
+ * import { metadata, Dir } from '@tauri-apps/api/fs'
+ * const { size, modifiedAt } = await metadata('app.conf', { dir: Dir.App })
+ * ```
+ *
+ * @param path Path to the file or directory.
+ * @param options Configuration object.
+ * @returns A promise resolving to the metadata.
+ */
+async function metadata(
+  path: string,
+  options: FsOptions = {}
+): Promise<Metadata> {
+  return invokeTauriCommand<Metadata>({
+    __tauriModule: 'Fs',
+    message: {
+      cmd: 'metadata',
+      path,
+      options
+    }
+  })
+}
+
+/**
+ * Checks if a path exists.
+ *
+ * @param path Path to the file or directory.
+ * @param options Configuration object.
+ * @returns A promise resolving to whether the path exists.
+ */
+async function exists(path: string, options: FsOptions = {}): Promise<boolean> {
+  return invokeTauriCommand<boolean>({
+    __tauriModule: 'Fs',
+    message: {
+      cmd: 'exists',
+      path,
+      options
+    }
+  })
+}
+
+/**
+ * Sets the permissions of a file or directory.
+ *
+ * @example
+ * ```typescript
+ * import { setPermissions } from '@tauri-apps/api/fs'
+ * await setPermissions('/usr/local/bin/tool', { mode: 0o755 })
+ * ```
+ *
+ * @param path Path to the file or directory.
+ * @param permissions The permissions to set, the omitted ones are unchanged.
+ * @param options Configuration object.
+ * @returns A promise indicating the success or failure of the operation.
+ */
+async function setPermissions(
+  path: string,
+  permissions: Permissions,
+  options: FsOptions = {}
+): Promise<void> {
+  return invokeTauriCommand({
+    __tauriModule: 'Fs',
+    message: {
+      cmd: 'setPermissions',
+      path,
+      permissions,
+      options
+    }
+  })
+}
+
+/**
+ * Extracts a tar or zip archive into a directory. The archive format is detected from the file extension:
+ * `.zip`, `.tar`, `.tar.gz` or `.tgz`, and `.tar.bz2`, `.tar.xz` or `.tar.zst` if the matching compression
+ * feature of the `tauri` crate is enabled. Entries outside of the destination directory are rejected.
+ *
+ * @param source Path to the archive.
+ * @param destination Path to the destination directory.
+ * @param options Configuration object.
+ * @returns A promise indicating the success or failure of the operation.
+ */
+async function extractArchive(
+  source: string,
+  destination: string,
+  options: FsOptions = {}
+): Promise<void> {
+  return invokeTauriCommand({
+    __tauriModule: 'Fs',
+    message: {
+      cmd: 'extractArchive',
+      source,
+      destination,
+      options
+    }
+  })
+}
+
+/**
+ * Creates a tar or zip archive from files and directories, added recursively at the root of the archive.
+ * The archive format is detected from the destination file extension.
+ *
+ * @example
+ * ```typescript
+ * import { createArchive, Dir } from '@tauri-apps/api/fs'
+ * await createArchive('export.tar.gz', ['project', 'settings.json'], {
+ *   dir: Dir.App,
+ *   onProgress: ({ bytes, totalBytes }) => console.log(`${bytes}/${totalBytes}`)
+ * })
+ * ```
+ *
+ * @param destination Path to the archive.
+ * @param paths The files and directories to add.
+ * @param options Configuration object.
+ * @returns A promise indicating the success or failure of the operation.
+ */
+async function createArchive(
+  destination: string,
+  paths: string[],
+  options: FsOptions & { onProgress?: (progress: ArchiveProgress) => void } = {}
+): Promise<void> {
+  const { onProgress, ...fsOptions } = options
+  return invokeTauriCommand({
+    __tauriModule: 'Fs',
+    message: {
+      cmd: 'createArchive',
+      destination,
+      paths,
+      options: fsOptions,
+      onProgressFn: onProgress ? transformCallback(onProgress) : undefined
+    }
+  })
+}
+
+/**
+ * Lists the items moved to the trash by the app with the `trash` option, which are still in the trash.
+ * Not supported on macOS.
+ *
+ * @returns A promise resolving to the trash items.
+ */
+async function listTrash(): Promise<TrashItem[]> {
+  return invokeTauriCommand<TrashItem[]>({
+    __tauriModule: 'Fs',
+    message: {
+      cmd: 'listTrash'
+    }
+  })
+}
+
+/**
+ * Restores an item moved to the trash by the app. Not supported on macOS.
+ *
+ * @example
+ * ```typescript
+ * import { removeFile, listTrash, restoreTrash, Dir } from '@tauri-apps/api/fs'
+ * await removeFile('notes.txt', { dir: Dir.App, trash: true })
+ * const [item] = await listTrash()
+ * await restoreTrash(item.id)
+ * ```
+ *
+ * @param id The identifier of the trash item.
+ * @returns A promise resolving to the restored path. Rejected if the original path already exists.
+ */
+async function restoreTrash(id: string): Promise<string> {
+  return invokeTauriCommand<string>({
+    __tauriModule: 'Fs',
+    message: {
+      cmd: 'restoreTrash',
+      id
+    }
+  })
+}
+
 export type {
   FsOptions,
   FsDirOptions,
+  FsWriteFileOptions,
+  SymlinkPolicy,
+  StreamDirOptions,
+  StreamDirEvent,
   FsTextFileOption,
   FsBinaryFileOption,
-  FileEntry
+  FileEntry,
+  Metadata,
+  Permissions,
+  ArchiveProgress,
+  TrashItem,
+  OpenOptions,
+  SeekMode,
+  WatchOptions,
+  WatchEvent
 }
 
 export {
@@ -373,9 +940,20 @@ export {
   writeFile,
   writeBinaryFile,
   readDir,
+  streamDir,
   createDir,
   removeDir,
   copyFile,
   removeFile,
-  renameFile
+  renameFile,
+  open,
+  FileHandle,
+  watch,
+  metadata,
+  exists,
+  setPermissions,
+  extractArchive,
+  createArchive,
+  listTrash,
+  restoreTrash
 }
@@ -25,10 +25,52 @@
  */
 
 import { invokeTauriCommand } from './helpers/tauri'
+import { transformCallback } from './tauri'
+import { BaseDirectory } from './fs'
+
+/** A client certificate used for mutual TLS. */
+interface ClientCertificate {
+  /** The PKCS#12 archive (`.p12` or `.pfx`) with the certificate chain and the private key. */
+  path: string
+  /** The password of the archive. */
+  password?: string
+}
+
+/**
+ * The retry policy of the HTTP requests.
+ * Only idempotent requests (`GET`, `HEAD`, `OPTIONS`, `PUT`, `DELETE` and `TRACE`) are retried unless `nonIdempotent` is set.
+ */
+interface RetryPolicy {
+  /** The maximum number of attempts, including the first one. Defaults to 3. */
+  maxAttempts?: number
+  /** The delay in milliseconds before the first retry, doubled on each attempt. Defaults to 200. */
+  initialDelay?: number
+  /** The maximum delay in milliseconds between two attempts. Defaults to 5000. */
+  maxDelay?: number
+  /** The response status codes that are retried. Defaults to 408, 429, 500, 502, 503 and 504. */
+  statusCodes?: number[]
+  /** Whether connection errors and timeouts are retried. Defaults to true. */
+  connectErrors?: boolean
+  /** Whether non idempotent requests (e.g. `POST`) are also retried. Defaults to false. */
+  nonIdempotent?: boolean
+}
 
 interface ClientOptions {
-  maxRedirections: number
-  connectTimeout: number
+  maxRedirections?: number
+  connectTimeout?: number
+  /** Proxy URL used for all requests, e.g. `http://proxy.example.com:8080` or `socks5://localhost:1080`. */
+  proxy?: string
+  /** PEM encoded root certificate files trusted in addition to the system certificates. Requires the `reqwest-client` feature. */
+  rootCertificates?: string[]
+  /** The client certificate used for mutual TLS. Requires the `reqwest-client` feature. */
+  clientCertificate?: ClientCertificate
+  /**
+   * The name of the cookie jar persisting the cookies across restarts, stored on the app directory.
+   * Only alphanumeric characters, `-` and `_` are allowed. Requires the `reqwest-client` feature.
+   */
+  cookieJar?: string
+  /** The retry policy of the requests. Requests are sent only once by default. */
+  retry?: RetryPolicy
 }
 
 enum ResponseType {
@@ -37,7 +79,21 @@ enum ResponseType {
   Binary = 3
 }
 
-type Part = 'string' | number[]
+/**
+ * A file uploaded with a form body, its contents are sent as a `multipart/form-data` part.
+ * Uploading files requires the `fs > readBinaryFile` allowlist and the path must be allowed by the `fs > scope` configuration.
+ * The file is read in memory unless the app enables the `reqwest-client` feature of the `tauri` crate, which streams it.
+ */
+interface FilePart {
+  /** The path of the file to upload. */
+  file: string
+  /** The MIME type of the file. Guessed from the file extension by default. */
+  mime?: string
+  /** The file name sent to the server. Defaults to the name of the file. */
+  fileName?: string
+}
+
+type Part = string | Uint8Array | number[] | FilePart
 
 /** The body object to be used on POST and PUT requests. */
 class Body {
@@ -51,14 +107,21 @@ class Body {
   }
 
   /**
-   * Creates a new form data body.
+   * Creates a new `multipart/form-data` body.
+   *
+   * Strings are sent as text fields, byte arrays as binary and {@link FilePart} objects upload the file contents.
    *
    * @param data The body data.
    *
    * @return The body object ready to be used on the POST and PUT requests.
    */
   static form(data: Record<string, Part>): Body {
-    return new Body('Form', data)
+    const form: Record<string, string | number[] | FilePart> = {}
+    for (const name of Object.keys(data)) {
+      const part = data[name]
+      form[name] = part instanceof Uint8Array ? Array.from(part) : part
+    }
+    return new Body('Form', form)
   }
 
   /**
@@ -116,6 +179,13 @@ interface HttpOptions {
   body?: Body
   timeout?: number
   responseType?: ResponseType
+  /**
+   * Whether 4xx and 5xx responses reject the promise, defaults to `true`.
+   * Set it to `false` to read the status, headers and body of error responses.
+   */
+  errorForStatus?: boolean
+  /** The retry policy, overriding the policy of the client. */
+  retry?: RetryPolicy
 }
 
 /** Request options. */
@@ -135,6 +205,55 @@ interface Response<T> {
   data: T
 }
 
+/** The progress of a download. */
+interface DownloadProgress {
+  /** Number of bytes written to the file. */
+  downloaded: number
+  /** The size of the response body, if known. */
+  total: number | null
+}
+
+/** Options for {@link Client.download}. */
+interface DownloadOptions extends RequestOptions {
+  /** The request method, defaults to `GET`. */
+  method?: HttpVerb
+  /** The base directory of the destination path. */
+  dir?: BaseDirectory
+  /** The expected SHA-256 checksum (hex encoded) of the file. The download fails if it doesn't match. */
+  sha256?: string
+  /** Called periodically with the download progress. */
+  onProgress?: (progress: DownloadProgress) => void
+}
+
+/** A download started with {@link Client.download}. */
+class Download {
+  /** The download identifier. */
+  id: number
+  /** A promise resolving once the file is written and verified. */
+  finished: Promise<void>
+
+  /** @ignore */
+  constructor(id: number, finished: Promise<void>) {
+    this.id = id
+    this.finished = finished
+  }
+
+  /**
+   * Cancels the download. The partially written file is removed and `finished` rejects.
+   *
+   * @returns A promise indicating the success or failure of the operation.
+   */
+  async cancel(): Promise<void> {
+    return invokeTauriCommand({
+      __tauriModule: 'Http',
+      message: {
+        cmd: 'cancelDownload',
+        id: this.id
+      }
+    })
+  }
+}
+
 class Client {
   id: number
   /** @ignore */
@@ -174,6 +293,61 @@ class Client {
     })
   }
 
+  /**
+   * Downloads a response body to a file.
+   * The body is streamed to the disk, the path must be allowed by the `fs` scope.
+   *
+   * @example
+   * ```typescript
+   * import { getClient } from '@tauri-apps/api/http'
+   * import { BaseDirectory } from '@tauri-apps/api/fs'
+   * const client = await getClient()
+   * const download = await client.download('https://example.com/assets.zip', 'assets.zip', {
+   *   dir: BaseDirectory.App,
+   *   onProgress: ({ downloaded, total }) => console.log(downloaded, total)
+   * })
+   * await download.finished
+   * ```
+   *
+   * @param url The request URL.
+   * @param path The destination path.
+   * @param options The download options.
+   * @returns A promise resolving to the started download, which can be awaited or cancelled.
+   */
+  async download(
+    url: string,
+    path: string,
+    options?: DownloadOptions
+  ): Promise<Download> {
+    const id = await invokeTauriCommand<number>({
+      __tauriModule: 'Http',
+      message: {
+        cmd: 'createDownload'
+      }
+    })
+    const { dir, sha256, onProgress, method, ...requestOptions } = options ?? {}
+    const finished = invokeTauriCommand<void>({
+      __tauriModule: 'Http',
+      message: {
+        cmd: 'download',
+        client: this.id,
+        id,
+        options: {
+          method: method ?? 'GET',
+          url,
+          ...requestOptions
+        },
+        download: {
+          path,
+          dir,
+          sha256,
+          onProgressFn: onProgress ? transformCallback(onProgress) : undefined
+        }
+      }
+    })
+    return new Download(id, finished)
+  }
+
   /**
    * Makes a GET request.
    *
@@ -270,11 +444,13 @@ class Client {
  * @return A promise resolving to the client instance.
  */
 async function getClient(options?: ClientOptions): Promise<Client> {
+  const { cookieJar, ...clientOptions } = options ?? {}
   return invokeTauriCommand<number>({
     __tauriModule: 'Http',
     message: {
       cmd: 'createClient',
-      options
+      options: clientOptions,
+      cookieJar
     }
   }).then((id) => new Client(id))
 }
@@ -305,13 +481,18 @@ async function fetch<T>(
 
 export type {
   ClientOptions,
+  ClientCertificate,
+  RetryPolicy,
   ResponseType,
   Part,
+  FilePart,
   HttpVerb,
   HttpOptions,
   RequestOptions,
   FetchOptions,
-  Response
+  Response,
+  DownloadProgress,
+  DownloadOptions
 }
 
-export { getClient, fetch, Body, Client }
+export { getClient, fetch, Body, Client, Download }
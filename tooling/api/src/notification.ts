@@ -24,6 +24,7 @@
  */
 
 import { invokeTauriCommand } from './helpers/tauri'
+import { transformCallback } from './tauri'
 
 /**
  * Options to send a notification.
@@ -35,8 +36,32 @@ interface Options {
   body?: string
   /** Optional notification icon. */
   icon?: string
+  /** The ID of a notification to replace, returned by {@link notify}. Only supported on Linux. */
+  id?: number
+  /** The action buttons. Only supported on Linux. */
+  actions?: NotificationAction[]
+  /** The urgency level. Only supported on Linux. */
+  urgency?: 'low' | 'normal' | 'critical'
+  /** The expiration timeout in milliseconds, `0` means the notification never expires. Only supported on Linux. */
+  timeout?: number
+  /** The sound to play, e.g. `message-new-instant` on Linux or `Ping` on macOS. */
+  sound?: string
 }
 
+/** An action button of a notification. */
+interface NotificationAction {
+  /** The identifier of the action, sent with the `action` event. */
+  id: string
+  /** The label of the action button. */
+  title: string
+}
+
+/** The first event of a notification: a click, an action or its closing. */
+type NotificationEvent =
+  | { type: 'clicked' }
+  | { type: 'action'; payload: string }
+  | { type: 'closed' }
+
 /** Possible permission values. */
 type Permission = 'granted' | 'denied' | 'default'
 
@@ -81,6 +106,63 @@ function sendNotification(options: Options | string): void {
   }
 }
 
-export type { Options, Permission }
+/**
+ * Shows a notification, calling the handler with its first event.
+ * The handler, the ID, actions, urgency and timeout are only supported on Linux.
+ * On Windows and macOS, the promise is rejected if any of them is set.
+ * @example
+ * ```typescript
+ * import { notify } from '@tauri-apps/api/notification'
+ * const id = await notify(
+ *   { title: 'New message', actions: [{ id: 'reply', title: 'Reply' }] },
+ *   (event) => {
+ *     if (event.type === 'action' && event.payload === 'reply') {
+ *       // show the reply form
+ *     }
+ *   }
+ * )
+ * ```
+ *
+ * @param options Notification options.
+ * @param handler The event handler.
+ * @returns A promise resolving to the notification ID, used to replace or close it. `null` on Windows and macOS.
+ */
+async function notify(
+  options: Options,
+  handler?: (event: NotificationEvent) => void
+): Promise<number | null> {
+  return invokeTauriCommand<number | null>({
+    __tauriModule: 'Notification',
+    message: {
+      cmd: 'notification',
+      options,
+      onEventFn: handler ? transformCallback(handler, true) : undefined
+    }
+  })
+}
+
+/**
+ * Closes a notification. Does nothing on Windows and macOS.
+ *
+ * @param id The notification ID returned by {@link notify}.
+ * @returns
+ */
+async function closeNotification(id: number): Promise<void> {
+  return invokeTauriCommand({
+    __tauriModule: 'Notification',
+    message: {
+      cmd: 'closeNotification',
+      id
+    }
+  })
+}
+
+export type { Options, Permission, NotificationAction, NotificationEvent }
 
-export { sendNotification, requestPermission, isPermissionGranted }
+export {
+  sendNotification,
+  requestPermission,
+  isPermissionGranted,
+  notify,
+  closeNotification
+}
@@ -6,7 +6,7 @@ import { invokeTauriCommand } from './helpers/tauri'
 import { BaseDirectory } from './fs'
 
 /**
- * Read common system paths such as home, config and cache directories.
+ * Read common system paths such as home, config and cache directories, and manipulate paths with the platform semantics.
  *
  * This package is also accessible with `window.__TAURI__.path` when `tauri.conf.json > build > withGlobalTauri` is set to true.
  *
@@ -449,6 +449,193 @@ async function resolvePath(
   })
 }
 
+/**
+ * Returns the path to the suggested directory for your app cache files.
+ * Resolves to `${cacheDir}/${bundleIdentifier}`.
+ *
+ * @returns
+ */
+async function appCacheDir(): Promise<string> {
+  return invokeTauriCommand<string>({
+    __tauriModule: 'Fs',
+    message: {
+      cmd: 'resolvePath',
+      path: '',
+      directory: BaseDirectory.AppCache
+    }
+  })
+}
+
+/**
+ * Returns the path to the suggested directory for your app local data files.
+ * Resolves to `${localDataDir}/${bundleIdentifier}`.
+ *
+ * @returns
+ */
+async function appLocalDataDir(): Promise<string> {
+  return invokeTauriCommand<string>({
+    __tauriModule: 'Fs',
+    message: {
+      cmd: 'resolvePath',
+      path: '',
+      directory: BaseDirectory.AppLocalData
+    }
+  })
+}
+
+/**
+ * Returns the path to the suggested directory for your app log files.
+ * # Linux and Windows
+ * Resolves to `${localDataDir}/${bundleIdentifier}/logs`.
+ * # macOS
+ * Resolves to `${homeDir}/Library/Logs/${bundleIdentifier}`.
+ *
+ * @returns
+ */
+async function logDir(): Promise<string> {
+  return invokeTauriCommand<string>({
+    __tauriModule: 'Fs',
+    message: {
+      cmd: 'resolvePath',
+      path: '',
+      directory: BaseDirectory.Log
+    }
+  })
+}
+
+/**
+ * Returns the path to the temporary directory.
+ *
+ * @returns
+ */
+async function tempDir(): Promise<string> {
+  return invokeTauriCommand<string>({
+    __tauriModule: 'Fs',
+    message: {
+      cmd: 'resolvePath',
+      path: '',
+      directory: BaseDirectory.Temp
+    }
+  })
+}
+
+/**
+ * Joins the path segments using the platform separator, then normalizes the result.
+ * An absolute segment replaces the previous ones.
+ *
+ * @param paths The path segments to join.
+ * @returns The joined path.
+ */
+async function join(...paths: string[]): Promise<string> {
+  return invokeTauriCommand<string>({
+    __tauriModule: 'Path',
+    message: {
+      cmd: 'join',
+      paths
+    }
+  })
+}
+
+/**
+ * Normalizes the path, removing the `.` segments and resolving the `..` segments without accessing the file system.
+ *
+ * @param path The path to normalize.
+ * @returns The normalized path, `.` if it's empty.
+ */
+async function normalize(path: string): Promise<string> {
+  return invokeTauriCommand<string>({
+    __tauriModule: 'Path',
+    message: {
+      cmd: 'normalize',
+      path
+    }
+  })
+}
+
+/**
+ * Returns the parent directory of the path, `.` if the path has a single segment.
+ *
+ * @param path The path.
+ * @returns The parent directory. Rejects if the path is a root.
+ */
+async function dirname(path: string): Promise<string> {
+  return invokeTauriCommand<string>({
+    __tauriModule: 'Path',
+    message: {
+      cmd: 'dirname',
+      path
+    }
+  })
+}
+
+/**
+ * Returns the last segment of the path.
+ *
+ * @param path The path.
+ * @param ext An extension to remove from the result, e.g. `.txt`.
+ * @returns The last segment of the path.
+ */
+async function basename(path: string, ext?: string): Promise<string> {
+  return invokeTauriCommand<string>({
+    __tauriModule: 'Path',
+    message: {
+      cmd: 'basename',
+      path,
+      ext
+    }
+  })
+}
+
+/**
+ * Returns the extension of the path, without the leading dot.
+ *
+ * @param path The path.
+ * @returns The extension. Rejects if the path has no extension.
+ */
+async function extname(path: string): Promise<string> {
+  return invokeTauriCommand<string>({
+    __tauriModule: 'Path',
+    message: {
+      cmd: 'extname',
+      path
+    }
+  })
+}
+
+/**
+ * Checks if the path is absolute on the current platform.
+ *
+ * @param path The path.
+ * @returns Whether the path is absolute.
+ */
+async function isAbsolute(path: string): Promise<boolean> {
+  return invokeTauriCommand<boolean>({
+    __tauriModule: 'Path',
+    message: {
+      cmd: 'isAbsolute',
+      path
+    }
+  })
+}
+
+/**
+ * Returns the relative path from `from` to `to`, after normalizing both.
+ *
+ * @param from The starting path.
+ * @param to The target path.
+ * @returns The relative path. Rejects if only one of the paths is absolute or if they are on different drives.
+ */
+async function relative(from: string, to: string): Promise<string> {
+  return invokeTauriCommand<string>({
+    __tauriModule: 'Path',
+    message: {
+      cmd: 'relative',
+      from,
+      to
+    }
+  })
+}
+
 export {
   appDir,
   audioDir,
@@ -470,5 +657,16 @@ export {
   videoDir,
   currentDir,
   resolvePath,
+  appCacheDir,
+  appLocalDataDir,
+  logDir,
+  tempDir,
+  join,
+  normalize,
+  dirname,
+  basename,
+  extname,
+  isAbsolute,
+  relative,
   BaseDirectory
 }
@@ -150,6 +150,18 @@ async function checkUpdate(): Promise<UpdateResult> {
   })
 }
 
+/**
+ * Confirms the installed update started successfully.
+ *
+ * When `tauri.conf.json > tauri > updater > rollbackLaunches` is set, the previous version
+ * is restored if the update doesn't confirm its launch within that many launches.
+ *
+ * @return A promise indicating the success or failure of the operation.
+ */
+async function confirmLaunch(): Promise<void> {
+  return emit('tauri://update-confirm-launch')
+}
+
 export type { UpdateStatus, UpdateStatusResult, UpdateManifest, UpdateResult }
 
-export { installUpdate, checkUpdate }
+export { installUpdate, checkUpdate, confirmLaunch }
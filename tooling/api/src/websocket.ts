@@ -0,0 +1,149 @@
+// Copyright 2019-2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+/**
+ * Connect to WebSocket servers from the Rust side, with custom handshake headers.
+ *
+ * This package is also accessible with `window.__TAURI__.websocket` when `tauri.conf.json > build > withGlobalTauri` is set to true.
+ *
+ * The APIs must be allowlisted on `tauri.conf.json`:
+ * ```json
+ * {
+ *   "tauri": {
+ *     "allowlist": {
+ *       "websocket": {
+ *         "all": true, // enable all WebSocket APIs
+ *         "scope": ["wss://dashboard.example.com/*"] // the URLs the API can connect to
+ *       }
+ *     }
+ *   }
+ * }
+ * ```
+ * It is recommended to allowlist only the APIs you use for optimal bundle size and security.
+ * @packageDocumentation
+ */
+
+import { invokeTauriCommand } from './helpers/tauri'
+import { transformCallback } from './tauri'
+
+interface ConnectionConfig {
+  /** Headers added to the handshake request. */
+  headers?: Record<string, string>
+}
+
+/** A message received from the server. */
+type Message =
+  | { type: 'Text'; data: string }
+  | { type: 'Binary'; data: number[] }
+
+/** The close frame sent by the server. */
+interface CloseFrame {
+  code: number
+  reason: string
+}
+
+/** An event of the connection. */
+type WebSocketEvent =
+  | { event: 'Message'; payload: Message }
+  | { event: 'Error'; payload: string }
+  | { event: 'Closed'; payload: CloseFrame | null }
+
+class WebSocket {
+  /** The connection identifier. */
+  id: number
+  /** @ignore */
+  private readonly listeners: Array<(event: WebSocketEvent) => void>
+
+  /** @ignore */
+  constructor(id: number, listeners: Array<(event: WebSocketEvent) => void>) {
+    this.id = id
+    this.listeners = listeners
+  }
+
+  /**
+   * Connects to a WebSocket server.
+   *
+   * @example
+   * ```typescript
+   * import { WebSocket } from '@tauri-apps/api/websocket'
+   * const socket = await WebSocket.connect('wss://dashboard.example.com/live', {
+   *   headers: { Authorization: 'Bearer token' }
+   * })
+   * socket.addListener((event) => {
+   *   if (event.event === 'Message') {
+   *     console.log(event.payload.data)
+   *   }
+   * })
+   * await socket.send('subscribe')
+   * ```
+   *
+   * @param url The `ws://` or `wss://` URL.
+   * @param options The connection options.
+   * @returns A promise resolving to the connection.
+   */
+  static async connect(
+    url: string,
+    options?: ConnectionConfig
+  ): Promise<WebSocket> {
+    const listeners: Array<(event: WebSocketEvent) => void> = []
+    const handler = (event: WebSocketEvent): void => {
+      listeners.forEach((listener) => listener(event))
+    }
+
+    return invokeTauriCommand<number>({
+      __tauriModule: 'Websocket',
+      message: {
+        cmd: 'connect',
+        url,
+        headers: options?.headers,
+        onEventFn: transformCallback(handler)
+      }
+    }).then((id) => new WebSocket(id, listeners))
+  }
+
+  /**
+   * Listens to the connection events: the received messages, errors and the connection close.
+   *
+   * @param listener The event handler.
+   */
+  addListener(listener: (event: WebSocketEvent) => void): void {
+    this.listeners.push(listener)
+  }
+
+  /**
+   * Sends a message to the server.
+   *
+   * @param message A text message, or a binary message as a byte array.
+   * @return A promise indicating the success or failure of the operation.
+   */
+  async send(message: string | number[] | Uint8Array): Promise<void> {
+    return invokeTauriCommand({
+      __tauriModule: 'Websocket',
+      message: {
+        cmd: 'send',
+        id: this.id,
+        buffer: message instanceof Uint8Array ? Array.from(message) : message
+      }
+    })
+  }
+
+  /**
+   * Closes the connection.
+   *
+   * @return A promise indicating the success or failure of the operation.
+   */
+  async disconnect(): Promise<void> {
+    return invokeTauriCommand({
+      __tauriModule: 'Websocket',
+      message: {
+        cmd: 'disconnect',
+        id: this.id
+      }
+    })
+  }
+}
+
+export type { ConnectionConfig, Message, CloseFrame, WebSocketEvent }
+
+export { WebSocket }
@@ -12,6 +12,7 @@ mod path_utils;
 mod platform;
 mod settings;
 mod updater_bundle;
+mod updater_manifest;
 #[cfg(target_os = "windows")]
 mod windows;
 
@@ -19,8 +20,9 @@ pub use self::{
   category::AppCategory,
   settings::{
     BundleBinary, BundleSettings, DebianSettings, MacOsSettings, PackageSettings, PackageType,
-    Settings, SettingsBuilder, UpdaterSettings,
+    Settings, SettingsBuilder, UpdaterManifestSettings, UpdaterSettings,
   },
+  updater_manifest::{write_updater_manifest, UpdaterArtifact},
 };
 pub use settings::{WindowsSettings, WixSettings};
 
@@ -40,7 +42,9 @@ pub struct Bundle {
 /// Returns the list of paths where the bundles can be found.
 pub fn bundle_project(settings: Settings) -> crate::Result<Vec<Bundle>> {
   let mut bundles = Vec::new();
-  let package_types = settings.package_types()?;
+  let mut package_types = settings.package_types()?;
+  // the updater archives the other bundles, so it's bundled last
+  package_types.sort_by_key(|package_type| *package_type == PackageType::Updater);
 
   for package_type in &package_types {
     let bundle_paths = match package_type {
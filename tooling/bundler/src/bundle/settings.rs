@@ -117,6 +117,24 @@ pub struct UpdaterSettings {
   pub pubkey: Option<String>,
   /// Display built-in dialog or use event system if disabled.
   pub dialog: bool,
+  /// The previous versions to generate binary patches from, mapped to their updater artifact.
+  pub previous_versions: HashMap<String, PathBuf>,
+  /// The update manifest to generate from the updater artifacts.
+  pub manifest: Option<UpdaterManifestSettings>,
+}
+
+/// The update manifest (`latest.json`) settings.
+#[derive(Debug, Clone)]
+pub struct UpdaterManifestSettings {
+  /// The download URL of the updater artifacts.
+  ///
+  /// `{{version}}`, `{{target}}` and `{{file}}` are replaced with the app version,
+  /// the updater target and the artifact file name.
+  pub url: String,
+  /// The file containing the release notes.
+  pub notes: Option<PathBuf>,
+  /// An existing manifest to add the platform entry to.
+  pub merge: Option<PathBuf>,
 }
 
 /// The Linux debian bundle settings.
@@ -642,6 +660,24 @@ impl Settings {
     }
   }
 
+  /// The previous versions to generate the updater binary patches from.
+  pub fn updater_previous_versions(&self) -> Option<&HashMap<String, PathBuf>> {
+    self
+      .bundle_settings
+      .updater
+      .as_ref()
+      .map(|updater| &updater.previous_versions)
+  }
+
+  /// The update manifest settings, if the manifest should be generated.
+  pub fn updater_manifest(&self) -> Option<&UpdaterManifestSettings> {
+    self
+      .bundle_settings
+      .updater
+      .as_ref()
+      .and_then(|updater| updater.manifest.as_ref())
+  }
+
   /// Get pubkey (mainly for testing)
   #[cfg(test)]
   pub fn updater_pubkey(&self) -> Option<&str> {
@@ -27,6 +27,13 @@ use std::{
 use anyhow::Context;
 use std::path::{Path, PathBuf};
 
+#[cfg(target_os = "linux")]
+use qbsdiff::Bsdiff;
+#[cfg(target_os = "linux")]
+use sha2::{Digest, Sha256};
+#[cfg(target_os = "linux")]
+use std::io::Cursor;
+
 // Build update
 pub fn bundle_project(settings: &Settings, bundles: &[Bundle]) -> crate::Result<Vec<PathBuf>> {
   if cfg!(unix) || cfg!(windows) || cfg!(macos) {
@@ -77,43 +84,115 @@ fn bundle_update(settings: &Settings, bundles: &[Bundle]) -> crate::Result<Vec<P
   Ok(vec![osx_archived_path])
 }
 
-// Create simple update-linux_<arch>.tar.gz
-// Including the AppImage
-// Right now in linux we hot replace the bin and request a restart
-// No assets are replaced
+// Create the update-linux_<arch>.tar.gz archives, one per installation method:
+// the AppImage, the `.deb` and `.rpm` packages when they were bundled, and the plain binary.
+// The updater downloads the archive matching how the app was installed
+// (the `linux`, `linux-deb`, `linux-rpm` and `linux-binary` targets).
 #[cfg(target_os = "linux")]
 fn bundle_update(settings: &Settings, bundles: &[Bundle]) -> crate::Result<Vec<PathBuf>> {
-  // build our app actually we support only appimage on linux
-  let bundle_path = match bundles
+  // build our AppImage if it wasn't bundled yet
+  let appimage_path = match find_bundle(bundles, crate::PackageType::AppImage, "AppImage") {
+    Some(path) => path,
+    None => appimage::bundle_project(settings)?
+      .into_iter()
+      .next()
+      .ok_or(crate::Error::UnableToFindProject)?,
+  };
+
+  let mut paths = vec![archive_linux_bundle(&appimage_path)?];
+  paths.extend(create_patches(settings, &appimage_path)?);
+
+  // the packages are installed by the package manager, they're only archived if they were bundled
+  for (package_type, extension) in &[
+    (crate::PackageType::Deb, "deb"),
+    (crate::PackageType::Rpm, "rpm"),
+  ] {
+    if let Some(package_path) = find_bundle(bundles, *package_type, extension) {
+      paths.push(archive_linux_bundle(&package_path)?);
+    }
+  }
+
+  // the binary archive goes next to the other bundles, `bundle/binary/[AppName].tar.gz`
+  let binary_path = settings
+    .project_out_directory()
+    .join(settings.main_binary_name());
+  let binary_archived_path = settings
+    .project_out_directory()
+    .join("bundle")
+    .join("binary")
+    .join(format!("{}.tar.gz", settings.main_binary_name()));
+  create_tar(&binary_path, &binary_archived_path)
+    .with_context(|| "Failed to tar.gz update binary")?;
+  common::print_bundling(format!("{:?}", &binary_archived_path).as_str())?;
+  paths.push(binary_archived_path);
+
+  Ok(paths)
+}
+
+// Find the bundle of the given type, by its extension
+#[cfg(target_os = "linux")]
+fn find_bundle(
+  bundles: &[Bundle],
+  package_type: crate::PackageType,
+  extension: &str,
+) -> Option<PathBuf> {
+  bundles
     .iter()
-    .filter(|bundle| bundle.package_type == crate::PackageType::AppImage)
+    .filter(|bundle| bundle.package_type == package_type)
     .find_map(|bundle| {
       bundle
         .bundle_paths
         .iter()
-        .find(|path| path.extension() == Some(OsStr::new("AppImage")))
-    }) {
-    Some(path) => vec![path.clone()],
-    None => appimage::bundle_project(settings)?,
+        .find(|path| path.extension() == Some(OsStr::new(extension)))
+        .cloned()
+    })
+}
+
+// Create `[bundle].tar.gz` next to the bundle
+#[cfg(target_os = "linux")]
+fn archive_linux_bundle(source_path: &Path) -> crate::Result<PathBuf> {
+  let archived_path = PathBuf::from(format!("{}.tar.gz", source_path.display()));
+  create_tar(source_path, &archived_path).with_context(|| "Failed to tar.gz update bundle")?;
+  common::print_bundling(format!("{:?}", &archived_path).as_str())?;
+  Ok(archived_path)
+}
+
+// Create the binary patches from the previous versions AppImage to the new one
+// `[AppName]_[version]_amd64.AppImage.[previous version].patch`
+// The updater applies them to the installed AppImage instead of downloading the archive.
+#[cfg(target_os = "linux")]
+fn create_patches(settings: &Settings, target_path: &Path) -> crate::Result<Vec<PathBuf>> {
+  let previous_versions = match settings.updater_previous_versions() {
+    Some(previous_versions) if !previous_versions.is_empty() => previous_versions,
+    _ => return Ok(vec![]),
   };
 
-  // we expect our .app to be on bundle[0]
-  if bundle_path.is_empty() {
-    return Err(crate::Error::UnableToFindProject);
-  }
+  let target = fs::read(target_path)?;
+  let mut patch_paths = Vec::new();
 
-  let source_path = &bundle_path[0];
+  for (version, source_path) in previous_versions {
+    let source = fs::read(source_path)
+      .with_context(|| format!("Failed to read the {} updater artifact", version))?;
 
-  // add .tar.gz to our path
-  let appimage_archived = format!("{}.tar.gz", source_path.display());
-  let appimage_archived_path = PathBuf::from(&appimage_archived);
+    let mut patch = Vec::new();
+    Bsdiff::new(&source, &target)
+      .compare(Cursor::new(&mut patch))
+      .with_context(|| format!("Failed to create the {} patch", version))?;
 
-  // Create our gzip file
-  create_tar(&source_path, &appimage_archived_path)
-    .with_context(|| "Failed to tar.gz update directory")?;
+    let patch_path = PathBuf::from(format!("{}.{}.patch", target_path.display(), version));
+    fs::write(&patch_path, &patch)?;
+    common::print_bundling(format!("{:?}", &patch_path).as_str())?;
+    patch_paths.push(patch_path);
+  }
+
+  // the updater verifies the patched AppImage against this hash,
+  // it's announced with the patches in the update JSON (`sha256`)
+  let hash = format!("{:x}", Sha256::digest(&target));
+  let hash_path = PathBuf::from(format!("{}.sha256", target_path.display()));
+  fs::write(&hash_path, &hash)?;
+  common::print_info(format!("patched AppImage sha256: {}", hash).as_str())?;
 
-  common::print_bundling(format!("{:?}", &appimage_archived_path).as_str())?;
-  Ok(vec![appimage_archived_path])
+  Ok(patch_paths)
 }
 
 // Create simple update-win_<arch>.zip
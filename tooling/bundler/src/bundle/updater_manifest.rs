@@ -0,0 +1,333 @@
+// Copyright 2019-2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use super::common;
+use crate::Settings;
+
+use anyhow::Context;
+use serde_json::{json, Map, Value};
+use std::{
+  fs,
+  path::{Path, PathBuf},
+};
+
+/// An updater artifact to publish in the update manifest.
+#[derive(Debug, Clone)]
+pub struct UpdaterArtifact {
+  /// The path to the update archive or binary patch.
+  pub path: PathBuf,
+  /// The artifact signature, if the updater artifacts are signed.
+  pub signature: Option<String>,
+}
+
+/// Writes the `latest.json` update manifest to the bundle directory.
+///
+/// Each updater archive gets the platform entry of its target, e.g. `linux` for the AppImage and `linux-deb` for the `.deb` package.
+/// The entries are added to the existing manifest if one is configured,
+/// so each platform can contribute its entries to the same release manifest.
+/// Returns `None` if the manifest generation isn't configured.
+pub fn write_updater_manifest(
+  settings: &Settings,
+  artifacts: &[UpdaterArtifact],
+) -> crate::Result<Option<PathBuf>> {
+  let manifest_settings = match settings.updater_manifest() {
+    Some(manifest_settings) => manifest_settings,
+    None => return Ok(None),
+  };
+
+  let archives: Vec<&UpdaterArtifact> = artifacts
+    .iter()
+    .filter(|artifact| !is_patch(&artifact.path))
+    .collect();
+  if archives.is_empty() {
+    return Err(crate::Error::UpdaterArchiveNotFound(
+      expected_archive().into(),
+    ));
+  }
+
+  let version = settings.version_string();
+
+  let notes = match &manifest_settings.notes {
+    Some(notes) => Some(
+      fs::read_to_string(notes)
+        .with_context(|| format!("Failed to read the release notes {:?}", notes))?,
+    ),
+    None => None,
+  };
+
+  let existing = match &manifest_settings.merge {
+    Some(path) if path.exists() => Some(
+      serde_json::from_str(&fs::read_to_string(path)?)
+        .with_context(|| format!("Failed to parse the update manifest {:?}", path))?,
+    ),
+    _ => None,
+  };
+
+  let mut platforms = Vec::new();
+  for archive in archives {
+    let target = updater_target(settings, &archive.path);
+    let url = |path: &Path| render_url(&manifest_settings.url, version, &target, path);
+    let mut platform = Map::new();
+    platform.insert("url".into(), Value::String(url(&archive.path)));
+    if let Some(signature) = &archive.signature {
+      platform.insert("signature".into(), Value::String(signature.clone()));
+    }
+
+    let patches = patches(&archive.path, artifacts, url)?;
+    if !patches.is_empty() {
+      platform.insert("patches".into(), Value::Object(patches));
+    }
+    platforms.push((target, Value::Object(platform)));
+  }
+
+  let manifest = merge_manifest(
+    existing,
+    version,
+    notes,
+    &chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+    platforms,
+  );
+
+  let manifest_path = settings
+    .project_out_directory()
+    .join("bundle")
+    .join("latest.json");
+  fs::write(&manifest_path, serde_json::to_string_pretty(&manifest)?)?;
+  common::print_bundling(format!("{:?}", &manifest_path).as_str())?;
+
+  Ok(Some(manifest_path))
+}
+
+// The platform name the updater looks for in the manifest
+fn updater_target(settings: &Settings, archive: &Path) -> String {
+  if cfg!(target_os = "linux") {
+    linux_target(archive).into()
+  } else if cfg!(target_os = "macos") {
+    "darwin".into()
+  } else if settings.binary_arch() == "x86" {
+    "win32".into()
+  } else {
+    "win64".into()
+  }
+}
+
+// The Linux target depends on the installation method the archive updates
+fn linux_target(archive: &Path) -> &'static str {
+  let file_name = archive
+    .file_name()
+    .map(|name| name.to_string_lossy().into_owned())
+    .unwrap_or_default();
+  if file_name.ends_with(".AppImage.tar.gz") {
+    "linux"
+  } else if file_name.ends_with(".deb.tar.gz") {
+    "linux-deb"
+  } else if file_name.ends_with(".rpm.tar.gz") {
+    "linux-rpm"
+  } else {
+    "linux-binary"
+  }
+}
+
+// The updater archive created for the current platform, named by the missing archive error
+fn expected_archive() -> &'static str {
+  if cfg!(target_os = "linux") {
+    ".AppImage.tar.gz"
+  } else if cfg!(target_os = "macos") {
+    ".app.tar.gz"
+  } else {
+    ".msi.zip"
+  }
+}
+
+// Replace {{version}}, {{target}} and {{file}} in the download URL template
+fn render_url(template: &str, version: &str, target: &str, path: &Path) -> String {
+  let file_name = path
+    .file_name()
+    .map(|name| name.to_string_lossy().into_owned())
+    .unwrap_or_default();
+  template
+    .replace("{{version}}", version)
+    .replace("{{target}}", target)
+    .replace("{{file}}", &file_name)
+}
+
+fn is_patch(path: &Path) -> bool {
+  path.extension().map_or(false, |ext| ext == "patch")
+}
+
+// The binary patches created by the bundler are named `<artifact>.<version>.patch`,
+// next to `<artifact>.sha256`
+fn patches<F: Fn(&Path) -> String>(
+  archive: &Path,
+  artifacts: &[UpdaterArtifact],
+  url: F,
+) -> crate::Result<Map<String, Value>> {
+  let mut patches = Map::new();
+  let archive = archive.to_string_lossy();
+  let source = PathBuf::from(archive.trim_end_matches(".tar.gz").trim_end_matches(".zip"));
+  let source_name = match source.file_name() {
+    Some(name) => format!("{}.", name.to_string_lossy()),
+    None => return Ok(patches),
+  };
+
+  for artifact in artifacts.iter().filter(|artifact| is_patch(&artifact.path)) {
+    let file_name = artifact
+      .path
+      .file_name()
+      .map(|name| name.to_string_lossy().into_owned())
+      .unwrap_or_default();
+    let from_version = match file_name
+      .strip_prefix(&source_name)
+      .and_then(|name| name.strip_suffix(".patch"))
+    {
+      Some(version) => version.to_string(),
+      None => continue,
+    };
+
+    let sha256_path = PathBuf::from(format!("{}.sha256", source.display()));
+    let sha256 = fs::read_to_string(&sha256_path)
+      .with_context(|| format!("Failed to read the patched file hash {:?}", sha256_path))?;
+
+    let mut patch = Map::new();
+    patch.insert("url".into(), Value::String(url(&artifact.path)));
+    if let Some(signature) = &artifact.signature {
+      patch.insert("signature".into(), Value::String(signature.clone()));
+    }
+    patch.insert("sha256".into(), Value::String(sha256.trim().into()));
+    patches.insert(from_version, Value::Object(patch));
+  }
+
+  Ok(patches)
+}
+
+// Add the platform entries to the existing manifest,
+// the other platforms are only kept if they were published for the same version
+fn merge_manifest(
+  existing: Option<Value>,
+  version: &str,
+  notes: Option<String>,
+  pub_date: &str,
+  new_platforms: Vec<(String, Value)>,
+) -> Value {
+  let mut platforms = existing
+    .filter(|manifest| {
+      manifest
+        .get("version")
+        .or_else(|| manifest.get("name"))
+        .and_then(|v| v.as_str())
+        .map_or(false, |v| v.trim_start_matches('v') == version)
+    })
+    .and_then(|mut manifest| manifest.get_mut("platforms").map(|p| p.take()))
+    .and_then(|platforms| match platforms {
+      Value::Object(platforms) => Some(platforms),
+      _ => None,
+    })
+    .unwrap_or_default();
+  platforms.extend(new_platforms);
+
+  let mut manifest = json!({
+    "version": version,
+    "pub_date": pub_date,
+    "platforms": platforms,
+  });
+  if let Some(notes) = notes {
+    manifest["notes"] = Value::String(notes.trim().into());
+  }
+  manifest
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn renders_url_template() {
+    assert_eq!(
+      render_url(
+        "https://releases.myapp.com/{{version}}/{{target}}/{{file}}",
+        "1.0.0",
+        "linux",
+        Path::new("/bundle/appimage/app.AppImage.tar.gz")
+      ),
+      "https://releases.myapp.com/1.0.0/linux/app.AppImage.tar.gz"
+    );
+  }
+
+  #[test]
+  fn merges_platforms_of_same_version() {
+    let existing = json!({
+      "version": "1.0.0",
+      "pub_date": "2021-05-01T10:00:00Z",
+      "platforms": {
+        "darwin": { "url": "https://releases.myapp.com/app.app.tar.gz" }
+      }
+    });
+    let manifest = merge_manifest(
+      Some(existing),
+      "1.0.0",
+      Some("Bug fixes\n".into()),
+      "2021-05-01T11:00:00Z",
+      vec![
+        (
+          "linux".into(),
+          json!({ "url": "https://releases.myapp.com/app.AppImage.tar.gz" }),
+        ),
+        (
+          "linux-deb".into(),
+          json!({ "url": "https://releases.myapp.com/app.deb.tar.gz" }),
+        ),
+      ],
+    );
+    assert_eq!(manifest["notes"], "Bug fixes");
+    assert_eq!(manifest["pub_date"], "2021-05-01T11:00:00Z");
+    assert!(manifest["platforms"]["darwin"].is_object());
+    assert!(manifest["platforms"]["linux"].is_object());
+    assert!(manifest["platforms"]["linux-deb"].is_object());
+  }
+
+  #[test]
+  fn linux_targets_per_package() {
+    assert_eq!(
+      linux_target(Path::new(
+        "/bundle/appimage/app_1.0.0_amd64.AppImage.tar.gz"
+      )),
+      "linux"
+    );
+    assert_eq!(
+      linux_target(Path::new("/bundle/deb/app_1.0.0_amd64.deb.tar.gz")),
+      "linux-deb"
+    );
+    assert_eq!(
+      linux_target(Path::new("/bundle/rpm/app-1.0.0.x86_64.rpm.tar.gz")),
+      "linux-rpm"
+    );
+    assert_eq!(
+      linux_target(Path::new("/bundle/binary/app.tar.gz")),
+      "linux-binary"
+    );
+  }
+
+  #[test]
+  fn drops_platforms_of_other_versions() {
+    let existing = json!({
+      "version": "0.9.0",
+      "platforms": {
+        "darwin": { "url": "https://releases.myapp.com/app.app.tar.gz" }
+      }
+    });
+    let manifest = merge_manifest(
+      Some(existing),
+      "1.0.0",
+      None,
+      "2021-05-01T11:00:00Z",
+      vec![(
+        "win64".into(),
+        json!({ "url": "https://releases.myapp.com/app.x64.msi.zip" }),
+      )],
+    );
+    assert!(manifest.get("notes").is_none());
+    assert_eq!(manifest["platforms"].as_object().unwrap().len(), 1);
+    assert!(manifest["platforms"]["win64"].is_object());
+  }
+}
@@ -77,6 +77,9 @@ pub enum Error {
   /// No bundled project found for the updater.
   #[error("Unable to find a bundled project for the updater")]
   UnableToFindProject,
+  /// The updater archive to publish in the update manifest wasn't found.
+  #[error("Unable to find the `{0}` updater archive for the update manifest")]
+  UpdaterArchiveNotFound(String),
   /// String is not UTF-8.
   #[error("string is not UTF-8")]
   Utf8(#[from] std::str::Utf8Error),
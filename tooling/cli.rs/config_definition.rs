@@ -339,6 +339,21 @@ pub struct FsAllowlistConfig {
   pub rename_file: bool,
   #[serde(default)]
   pub path: bool,
+  #[serde(default)]
+  pub watch: bool,
+  #[serde(default)]
+  pub metadata: bool,
+  #[serde(default)]
+  pub set_permissions: bool,
+  #[serde(default)]
+  pub archive: bool,
+  #[serde(default)]
+  pub trash: bool,
+  /// The paths the fs APIs and the HTTP downloads and uploads can access, as glob patterns.
+  ///
+  /// Patterns can start with a base directory variable such as `$APP`, `$HOME` or `$DOWNLOAD`.
+  /// All paths are allowed if the scope isn't set.
+  pub scope: Option<Vec<String>>,
 }
 
 impl Allowlist for FsAllowlistConfig {
@@ -358,6 +373,11 @@ impl Allowlist for FsAllowlistConfig {
       check_feature!(self, features, remove_file, "fs-remove-file");
       check_feature!(self, features, rename_file, "fs-rename-file");
       check_feature!(self, features, path, "fs-path");
+      check_feature!(self, features, watch, "fs-watch");
+      check_feature!(self, features, metadata, "fs-metadata");
+      check_feature!(self, features, set_permissions, "fs-set-permissions");
+      check_feature!(self, features, archive, "fs-archive");
+      check_feature!(self, features, trash, "fs-trash");
       features
     }
   }
@@ -487,6 +507,28 @@ impl Allowlist for GlobalShortcutAllowlistConfig {
   }
 }
 
+#[derive(Debug, Default, PartialEq, Clone, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct WebsocketAllowlistConfig {
+  #[serde(default)]
+  pub all: bool,
+  /// The URLs the WebSocket API can connect to, as glob patterns, e.g. `wss://dashboard.example.com/*`.
+  /// The scheme, host, port and path are matched separately, e.g. `wss://*.example.com/*` only matches the subdomains.
+  ///
+  /// All URLs are allowed if the scope isn't set.
+  pub scope: Option<Vec<String>>,
+}
+
+impl Allowlist for WebsocketAllowlistConfig {
+  fn to_features(&self) -> Vec<&str> {
+    if self.all {
+      vec!["websocket-all"]
+    } else {
+      vec![]
+    }
+  }
+}
+
 #[derive(Debug, Default, PartialEq, Clone, Deserialize, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct AllowlistConfig {
@@ -506,6 +548,8 @@ pub struct AllowlistConfig {
   pub notification: NotificationAllowlistConfig,
   #[serde(default)]
   pub global_shortcut: GlobalShortcutAllowlistConfig,
+  #[serde(default)]
+  pub websocket: WebsocketAllowlistConfig,
 }
 
 impl Allowlist for AllowlistConfig {
@@ -521,6 +565,7 @@ impl Allowlist for AllowlistConfig {
       features.extend(self.http.to_features());
       features.extend(self.notification.to_features());
       features.extend(self.global_shortcut.to_features());
+      features.extend(self.websocket.to_features());
       features
     }
   }
@@ -570,6 +615,71 @@ pub struct UpdaterConfig {
   pub endpoints: Option<Vec<String>>,
   /// Optional pubkey.
   pub pubkey: Option<String>,
+  /// Additional trusted public keys, used to rotate the signing key.
+  ///
+  /// A signature is valid if it matches `pubkey` or any non-expired key on this list.
+  #[serde(default)]
+  pub pubkeys: Vec<UpdaterPubkey>,
+  /// Whether the update manifest must be signed or not.
+  ///
+  /// The manifest signature is downloaded from the endpoint URL with a `.sig` suffix.
+  #[serde(default)]
+  pub verify_manifest: bool,
+  /// Number of launches the installed update has to confirm it started successfully,
+  /// before the previous version is restored.
+  ///
+  /// The previous version is only kept as a backup when this is set.
+  pub rollback_launches: Option<u32>,
+  /// Periodic update checks, in addition to the check on startup.
+  pub schedule: Option<UpdaterSchedule>,
+  /// The proxy used to reach the update server, e.g. `http://proxy.example.com:8080`.
+  pub proxy: Option<String>,
+  /// Previous versions to generate binary patches from, so the clients don't download the whole update.
+  ///
+  /// Maps the version to the path of its AppImage. Patches are only generated on Linux.
+  #[serde(default)]
+  pub previous_versions: HashMap<String, PathBuf>,
+  /// Generate the update manifest (`latest.json`) from the updater artifacts.
+  pub manifest: Option<UpdaterManifestConfig>,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Default, PartialEq, Clone, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct UpdaterManifestConfig {
+  /// The download URL of the updater artifacts.
+  ///
+  /// `{{version}}`, `{{target}}` and `{{file}}` are replaced with the app version,
+  /// the updater target (e.g. `darwin`) and the artifact file name.
+  pub url: String,
+  /// Path to a file containing the release notes.
+  pub notes: Option<PathBuf>,
+  /// Path to an existing manifest to add the platform entry to, e.g. the manifest of another platform.
+  ///
+  /// Its platform entries are kept if it describes the same version.
+  pub merge: Option<PathBuf>,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Default, PartialEq, Clone, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct UpdaterSchedule {
+  /// Number of seconds between two update checks.
+  pub interval: u64,
+  /// Maximum number of seconds randomly added to each interval,
+  /// so the clients don't all check at the same time.
+  #[serde(default)]
+  pub jitter: u64,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Default, PartialEq, Clone, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct UpdaterPubkey {
+  /// The public key generated with the Tauri CLI.
+  pub key: String,
+  /// Unix timestamp (in seconds) after which the key is no longer trusted.
+  pub expires_at: Option<u64>,
 }
 
 #[skip_serializing_none]
@@ -680,5 +790,12 @@ fn default_updater() -> UpdaterConfig {
     dialog: Some(true),
     endpoints: None,
     pubkey: None,
+    pubkeys: Vec::new(),
+    verify_manifest: false,
+    rollback_launches: None,
+    schedule: None,
+    proxy: None,
+    previous_versions: HashMap::new(),
+    manifest: None,
   }
 }
@@ -3,7 +3,9 @@
 // SPDX-License-Identifier: MIT
 
 use anyhow::Context;
-use tauri_bundler::bundle::{bundle_project, PackageType, SettingsBuilder};
+use tauri_bundler::bundle::{
+  bundle_project, write_updater_manifest, PackageType, SettingsBuilder, UpdaterArtifact,
+};
 
 use crate::helpers::{
   app_paths::{app_dir, tauri_dir},
@@ -199,27 +201,47 @@ impl Build {
         .build()
         .with_context(|| "failed to build bundler settings")?;
 
-      let bundles = bundle_project(settings).with_context(|| "failed to bundle project")?;
+      let bundles = bundle_project(settings.clone()).with_context(|| "failed to bundle project")?;
+
+      let updater_paths: Vec<PathBuf> = bundles
+        .iter()
+        .filter(|bundle| bundle.package_type == PackageType::Updater)
+        .flat_map(|bundle| bundle.bundle_paths.iter().cloned())
+        .collect();
+
+      // If updater is active and a pubkey is available
+      let sign_updater = config_.tauri.updater.active
+        && (config_.tauri.updater.pubkey.is_some() || !config_.tauri.updater.pubkeys.is_empty());
+
+      let mut artifacts = Vec::new();
+      let mut signed_paths = Vec::new();
+      // we expect to have only one path in the vec but we iter if we add
+      // another type of updater package who require multiple file signature
+      for path in updater_paths {
+        let signature = if sign_updater {
+          // sign our path from environment variables
+          let (signature_path, signature) = sign_file_from_env_variables(&path)?;
+          signed_paths.push(signature_path);
+          Some(signature)
+        } else {
+          None
+        };
+        artifacts.push(UpdaterArtifact { path, signature });
+      }
+      if !signed_paths.is_empty() {
+        print_signed_updater_archive(&signed_paths)?;
+      }
 
-      // If updater is active and pubkey is available
-      if config_.tauri.updater.active && config_.tauri.updater.pubkey.is_some() {
-        // make sure we have our package builts
-        let mut signed_paths = Vec::new();
-        for elem in bundles
-          .iter()
-          .filter(|bundle| bundle.package_type == PackageType::Updater)
+      if !artifacts.is_empty() {
+        if let Some(manifest_path) = write_updater_manifest(&settings, &artifacts)
+          .with_context(|| "failed to write the update manifest")?
         {
-          // we expect to have only one path in the vec but we iter if we add
-          // another type of updater package who require multiple file signature
-          for path in elem.bundle_paths.iter() {
-            // sign our path from environment variables
-            let (signature_path, _signature) = sign_file_from_env_variables(path)?;
-            signed_paths.append(&mut vec![signature_path]);
+          // the updater validates the manifest signature when `verifyManifest` is enabled
+          if sign_updater && config_.tauri.updater.verify_manifest {
+            let (signature_path, _signature) = sign_file_from_env_variables(&manifest_path)?;
+            print_signed_updater_archive(&[signature_path])?;
           }
         }
-        if !signed_paths.is_empty() {
-          print_signed_updater_archive(&signed_paths)?;
-        }
       }
     }
 
@@ -16,7 +16,7 @@ use serde::Deserialize;
 use crate::helpers::{app_paths::tauri_dir, config::Config, manifest::Manifest};
 use tauri_bundler::{
   AppCategory, BundleBinary, BundleSettings, DebianSettings, MacOsSettings, PackageSettings,
-  UpdaterSettings, WindowsSettings,
+  UpdaterManifestSettings, UpdaterSettings, WindowsSettings,
 };
 
 /// The `workspace` section of the app configuration (read from Cargo.toml).
@@ -439,6 +439,14 @@ fn tauri_config_to_bundle_settings(
       dialog: updater_config.dialog.unwrap_or(true),
       pubkey: updater_config.pubkey,
       endpoints: updater_config.endpoints,
+      previous_versions: updater_config.previous_versions,
+      manifest: updater_config
+        .manifest
+        .map(|manifest| UpdaterManifestSettings {
+          url: manifest.url,
+          notes: manifest.notes,
+          merge: manifest.merge,
+        }),
     }),
     ..Default::default()
   })
@@ -118,6 +118,12 @@ pub fn all_allowlist_features() -> Vec<&'static str> {
       remove_file: true,
       rename_file: true,
       path: true,
+      watch: true,
+      metadata: true,
+      set_permissions: true,
+      archive: true,
+      trash: true,
+      scope: None,
     },
     window: WindowAllowlistConfig {
       all: true,
@@ -139,6 +145,10 @@ pub fn all_allowlist_features() -> Vec<&'static str> {
     },
     notification: NotificationAllowlistConfig { all: true },
     global_shortcut: GlobalShortcutAllowlistConfig { all: true },
+    websocket: WebsocketAllowlistConfig {
+      all: true,
+      scope: None,
+    },
   }
   .to_features()
 }
@@ -144,6 +144,28 @@ where
   Ok((fs::canonicalize(&signature_path)?, encoded_signature))
 }
 
+/// Sign an update manifest (e.g. `latest.json`)
+///
+/// The manifest is parsed before signing so we never publish a signature for a broken file.
+/// The updater validates it against the `pubkey` when `verifyManifest` is enabled.
+pub fn sign_manifest<P>(
+  private_key: String,
+  password: String,
+  manifest_path: P,
+) -> crate::Result<(PathBuf, String)>
+where
+  P: AsRef<Path>,
+{
+  let manifest: serde_json::Value = serde_json::from_str(&fs::read_to_string(&manifest_path)?)?;
+  // the updater reads the release version from `version`, or from `name` on older manifests
+  if manifest.get("version").is_none() && manifest.get("name").is_none() {
+    return Err(anyhow::anyhow!(
+      "Invalid update manifest: it needs a `version` or a `name` field"
+    ));
+  }
+  sign_file(private_key, password, manifest_path, false)
+}
+
 /// Sign files using the TAURI_KEY_PASSWORD and TAURI_PRIVATE_KEY environment variables
 pub fn sign_file_from_env_variables<P>(path_to_sign: P) -> crate::Result<(PathBuf, String)>
 where
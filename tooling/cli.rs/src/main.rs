@@ -203,6 +203,7 @@ fn sign_command(matches: &ArgMatches) -> Result<()> {
   let private_key = matches.value_of("private-key");
   let private_key_path = matches.value_of("private-key-path");
   let file = matches.value_of("sign-file");
+  let manifest = matches.value_of("sign-manifest");
   let password = matches.value_of("password");
   let no_password = matches.is_present("no-password");
   let write_keys = matches.value_of("write-keys");
@@ -245,6 +246,10 @@ fn sign_command(matches: &ArgMatches) -> Result<()> {
     sign_runner = sign_runner.file_to_sign(file);
   }
 
+  if let Some(manifest) = manifest {
+    sign_runner = sign_runner.manifest_to_sign(manifest);
+  }
+
   if let Some(password) = password {
     sign_runner = sign_runner.password(password);
   }
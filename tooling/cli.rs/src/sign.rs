@@ -3,7 +3,7 @@
 // SPDX-License-Identifier: MIT
 
 use crate::helpers::updater_signature::{
-  generate_key, read_key_from_file, save_keypair, sign_file,
+  generate_key, read_key_from_file, save_keypair, sign_file, sign_manifest,
 };
 use std::path::{Path, PathBuf};
 
@@ -14,6 +14,7 @@ pub struct Signer {
   private_key: Option<String>,
   password: Option<String>,
   file: Option<PathBuf>,
+  manifest: Option<PathBuf>,
 }
 
 impl Signer {
@@ -41,6 +42,11 @@ impl Signer {
     self
   }
 
+  pub fn manifest_to_sign(mut self, manifest_path: &str) -> Self {
+    self.manifest = Some(Path::new(manifest_path).to_path_buf());
+    self
+  }
+
   pub fn private_key_path(mut self, private_key: &str) -> Self {
     self.private_key =
       Some(read_key_from_file(Path::new(private_key)).expect("Unable to extract private key"));
@@ -60,13 +66,23 @@ impl Signer {
           ));
     }
 
-    let (manifest_dir, signature) = sign_file(
-      self.private_key.unwrap(),
-      self.password.unwrap(),
-      self.file.unwrap(),
-      false,
-    )
-    .with_context(|| "failed to sign file")?;
+    let (manifest_dir, signature) = if let Some(manifest) = self.manifest {
+      sign_manifest(self.private_key.unwrap(), self.password.unwrap(), manifest)
+        .with_context(|| "failed to sign update manifest")?
+    } else if let Some(file) = self.file {
+      sign_file(
+        self.private_key.unwrap(),
+        self.password.unwrap(),
+        file,
+        false,
+      )
+      .with_context(|| "failed to sign file")?
+    } else {
+      return Err(anyhow::anyhow!(
+        "Please use --sign-file <file> or --sign-manifest <manifest> to select what to sign."
+          .to_string(),
+      ));
+    };
 
     println!(
          "\nYour file was signed successfully, You can find the signature here:\n{}\n\nPublic signature:\n{}\n\nMake sure to include this into the signature field of your update server.",